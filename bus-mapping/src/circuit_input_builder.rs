@@ -1,20 +1,28 @@
 //! This module contains the CircuitInputBuilder, which is an object that takes
 //! types from geth / web3 and outputs the circuit inputs.
 use crate::eth_types::{
-    self, Address, GethExecStep, GethExecTrace, ToAddress, ToBigEndian, Word,
-    H256,
+    self, AccessList, Address, GethExecStep, GethExecTrace, ToAddress,
+    ToBigEndian, ToWord, Word, H256, U64,
+};
+use crate::evm::{
+    gas, Gas, GasCost, GlobalCounter, OpcodeId, Precompile, ProgramCounter,
+    MAX_REFUND_QUOTIENT_OF_GAS_USED,
 };
-use crate::evm::{Gas, GasCost, GlobalCounter, OpcodeId, ProgramCounter};
 use crate::exec_trace::OperationRef;
 use crate::geth_errors::*;
 use crate::operation::container::OperationContainer;
 use crate::operation::RW;
-use crate::operation::{Op, Operation};
+use crate::operation::{
+    AccountDestructedOp, AccountField, AccountOp, CallContextField,
+    CallContextOp, Op, Operation, StorageOp, Target, TxAccessListAccountOp,
+    TxAccessListStorageSlotOp, TxRefundOp,
+};
 use crate::state_db::StateDB;
 use crate::{BlockConstants, Error};
 use core::fmt::Debug;
 use ethers_core::utils::{get_contract_address, get_create2_address};
 use std::collections::{hash_map::Entry, HashMap, HashSet};
+use std::ops::Range;
 
 /// Out of Gas errors by opcode
 #[derive(Debug, PartialEq)]
@@ -87,11 +95,22 @@ pub enum ExecError {
     MaxCodeSizeExceeded,
 }
 
+/// State of the EVM circuit in which an [`ExecStep`] is executed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExecState {
+    /// Execution of an opcode
+    Op(OpcodeId),
+    /// Beginning of a [`Transaction`], before its root call is executed
+    BeginTx,
+    /// End of a [`Transaction`], after its root call has returned
+    EndTx,
+}
+
 /// An execution step of the EVM.
 #[derive(Debug)]
 pub struct ExecStep {
-    /// The opcode ID
-    pub op: OpcodeId,
+    /// Execution state
+    pub exec_state: ExecState,
     /// Program Counter
     pub pc: ProgramCounter,
     /// Stack size
@@ -128,7 +147,7 @@ impl ExecStep {
         swc: usize, // State Write Counter
     ) -> Self {
         ExecStep {
-            op: step.op,
+            exec_state: ExecState::Op(step.op),
             pc: step.pc,
             stack_size: step.stack.0.len(),
             memory_size: step.memory.0.len(),
@@ -142,6 +161,30 @@ impl ExecStep {
             reverted: false,
         }
     }
+
+    /// Create a new Self for the `exec_state` at the beginning or at the end
+    /// of a [`Transaction`], which is done in its root call.
+    fn new_tx_step(
+        exec_state: ExecState,
+        gas_left: Gas,
+        gc: GlobalCounter,
+        swc: usize,
+    ) -> Self {
+        ExecStep {
+            exec_state,
+            pc: ProgramCounter(0),
+            stack_size: 0,
+            memory_size: 0,
+            gas_left,
+            gas_cost: GasCost::ZERO,
+            call_index: 0,
+            gc,
+            swc,
+            bus_mapping_instance: Vec::new(),
+            error: None,
+            reverted: false,
+        }
+    }
 }
 
 /// Context of a [`Block`] which can mutate in a [`Transaction`].
@@ -173,8 +216,10 @@ pub struct Block {
     pub constants: BlockConstants,
     /// Container of operations done in this block.
     pub container: OperationContainer,
+    /// Inputs of the keccak hashes computed in this block with their digests,
+    /// in the order in which they are computed.
+    pub keccak_inputs: Vec<(Vec<u8>, H256)>,
     txs: Vec<Transaction>,
-    code: HashMap<H256, Vec<u8>>,
 }
 
 impl Block {
@@ -186,8 +231,8 @@ impl Block {
         Self {
             constants,
             container: OperationContainer::new(),
+            keccak_inputs: Vec::new(),
             txs: Vec::new(),
-            code: HashMap::new(),
         }
     }
 
@@ -203,7 +248,7 @@ impl Block {
 }
 
 /// Type of a *CALL* Function.
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum CallKind {
     /// CALL
     Call,
@@ -244,16 +289,50 @@ impl TryFrom<OpcodeId> for CallKind {
 /// Circuit Input related to an Ethereum Call
 #[derive(Debug)]
 pub struct Call {
+    /// Unique identifier of the call in the block, which is the global counter
+    /// of the first operation done in the call.
+    pub call_id: usize,
     /// Type of call
-    kind: CallKind,
+    pub kind: CallKind,
     /// This call is being executed without write access (STATIC)
-    is_static: bool,
+    pub is_static: bool,
     /// This call generated implicity by a Transaction.
-    is_root: bool,
+    pub is_root: bool,
+    /// Caller Address.  For DELEGATECALL this is the caller of the parent
+    /// call.
+    pub caller_address: Address,
     /// Address where this call is being executed
     pub address: Address,
+    /// Address of the account whose code is being executed.  It differs from
+    /// `address` in CALLCODE and DELEGATECALL.
+    pub code_address: Address,
     /// Code Hash
-    code_hash: H256,
+    pub code_hash: H256,
+    /// Value transferred by this call.  For DELEGATECALL this is the value of
+    /// the parent call.
+    pub value: Word,
+    /// Offset of the call data in the caller memory
+    pub call_data_offset: u64,
+    /// Length of the call data
+    pub call_data_length: u64,
+    /// Call data.  For the root call this is the transaction input, and for
+    /// internal calls it's the caller memory at the call data region.
+    pub call_data: Vec<u8>,
+    /// Offset of the region in the caller memory where the return data is
+    /// copied
+    pub return_data_offset: u64,
+    /// Length of the region in the caller memory where the return data is
+    /// copied
+    pub return_data_length: u64,
+    /// The call succeeds
+    pub is_success: bool,
+    /// The state writes of the call are persistent, which happens when the
+    /// call and all its callers succeed
+    pub is_persistent: bool,
+    /// Global counter at the end of the reversion of the state writes of the
+    /// call, which are reverted in reverse order ending at it.  It's only
+    /// set when the call is not persistent.
+    pub rw_counter_end_of_reversion: usize,
 }
 
 impl Call {
@@ -271,6 +350,49 @@ pub struct CallContext {
     /// call.  When a subcall in this call succeeds, the `swc` increases by the
     /// number of successful state writes in the subcall.
     pub swc: usize,
+    /// Index of the first [`ExecStep`] of the call in the [`Transaction`].
+    pub step_index: usize,
+    /// Return data of the last subcall done in this call.
+    pub return_data: Vec<u8>,
+}
+
+impl CallContext {
+    /// Create a new Self for a call whose first [`ExecStep`] is at
+    /// `step_index` in the [`Transaction`].
+    pub fn new(step_index: usize) -> Self {
+        Self {
+            swc: 0,
+            step_index,
+            return_data: Vec::new(),
+        }
+    }
+}
+
+/// Group of non persistent calls whose state writes are reverted together
+/// when the first call of the group, which is the one that fails, returns.
+/// The rest of calls are the successful sub-calls of the failed call.
+#[derive(Debug)]
+struct ReversionGroup {
+    /// Index of each call in the group with the number of state writes done
+    /// in the group before the call starts.
+    calls: Vec<(usize, usize)>,
+    /// Reference to the state write operations done in the group.
+    op_refs: Vec<OperationRef>,
+    /// Reference to the reads of the end of the reversion in the context of
+    /// the calls in the group, with the position of their call in `calls`.
+    /// They are done before the end of the reversion is known.
+    call_context_op_refs: Vec<(usize, OperationRef)>,
+}
+
+impl ReversionGroup {
+    /// Create a new Self started by the failed call at `call_index`.
+    fn new(call_index: usize) -> Self {
+        Self {
+            calls: vec![(call_index, 0)],
+            op_refs: Vec::new(),
+            call_context_op_refs: Vec::new(),
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -280,13 +402,17 @@ pub struct TransactionContext {
     /// The call_stack will always have a fixed element at index 0 which
     /// corresponds to the call implicitly created by the transaction.
     call_stack: Vec<(usize, CallContext)>,
+    /// Stack of the reversion groups of the failed calls in the call stack.
+    reversion_groups: Vec<ReversionGroup>,
 }
 
 impl TransactionContext {
     /// Create a new Self.
     pub fn new(_eth_tx: &eth_types::Transaction) -> Self {
+        // The first step of the root call comes right after the BeginTx step.
         Self {
-            call_stack: vec![(0, CallContext { swc: 0 })],
+            call_stack: vec![(0, CallContext::new(1))],
+            reversion_groups: Vec::new(),
         }
     }
 
@@ -309,6 +435,17 @@ impl TransactionContext {
         call_ctx
     }
 
+    /// Return the context of the caller of the current call, if the current
+    /// call is not the root one.
+    fn caller_ctx_mut(&mut self) -> Option<&mut CallContext> {
+        let len = self.call_stack.len();
+        if len < 2 {
+            return None;
+        }
+        let (_, ref mut caller_ctx) = self.call_stack[len - 2];
+        Some(caller_ctx)
+    }
+
     /// Push a new call index and context into the call stack.
     fn push_call_index_ctx(&mut self, index: usize, call_ctx: CallContext) {
         self.call_stack.push((index, call_ctx));
@@ -327,6 +464,12 @@ pub struct Transaction {
     pub nonce: u64,
     /// Gas
     pub gas: u64,
+    /// Gas Price
+    pub gas_price: Word,
+    /// Max fee per gas of an EIP-1559 transaction
+    pub max_fee_per_gas: Option<Word>,
+    /// Max priority fee per gas of an EIP-1559 transaction
+    pub max_priority_fee_per_gas: Option<Word>,
     /// From / Caller Address
     pub from: Address, // caller_address
     /// To / Callee Address
@@ -335,6 +478,8 @@ pub struct Transaction {
     pub value: Word,
     /// Input / Call Data
     pub input: Vec<u8>, // call_data
+    /// Access List (EIP-2930)
+    pub access_list: AccessList,
     calls: Vec<Call>,
     steps: Vec<ExecStep>,
 }
@@ -342,43 +487,111 @@ pub struct Transaction {
 impl Transaction {
     /// Create a new Self.
     pub fn new(eth_tx: &eth_types::Transaction) -> Self {
-        let mut calls = Vec::new();
-        let code_hash = H256::zero();
-        if let Some(address) = eth_tx.to {
-            calls.push(Call {
-                kind: CallKind::Call,
-                is_static: false,
-                is_root: true,
-                address,
-                code_hash,
-            });
-        } else {
-            calls.push(Call {
-                kind: CallKind::Create,
-                is_static: false,
-                is_root: true,
-                address: Address::zero(),
-                code_hash,
-            });
-        }
+        // The input of a create transaction is the init code, so its root
+        // call has no call data.
+        let (kind, address, call_data) = match eth_tx.to {
+            Some(address) => (CallKind::Call, address, eth_tx.input.to_vec()),
+            None => (
+                CallKind::Create,
+                get_contract_address(eth_tx.from, eth_tx.nonce),
+                Vec::new(),
+            ),
+        };
+        // The id and the result of the root call are set when the
+        // transaction is handled.
+        let calls = vec![Call {
+            call_id: 0,
+            kind,
+            is_static: false,
+            is_root: true,
+            caller_address: eth_tx.from,
+            address,
+            code_address: address,
+            code_hash: H256::zero(),
+            value: eth_tx.value,
+            call_data_offset: 0,
+            call_data_length: call_data.len() as u64,
+            call_data,
+            return_data_offset: 0,
+            return_data_length: 0,
+            is_success: false,
+            is_persistent: false,
+            rw_counter_end_of_reversion: 0,
+        }];
+        // The fee caps of an EIP-1559 transaction replace its gas price.
+        let (max_fee_per_gas, max_priority_fee_per_gas) =
+            if eth_tx.transaction_type == Some(U64::from(2)) {
+                (eth_tx.max_fee_per_gas, eth_tx.max_priority_fee_per_gas)
+            } else {
+                (None, None)
+            };
         Self {
             nonce: eth_tx.nonce.as_u64(),
             gas: eth_tx.gas.as_u64(),
+            gas_price: eth_tx.gas_price.unwrap_or_default(),
+            max_fee_per_gas,
+            max_priority_fee_per_gas,
             from: eth_tx.from,
             to: eth_tx.to.unwrap_or_default(),
             value: eth_tx.value,
             input: eth_tx.input.to_vec(),
+            access_list: eth_tx.access_list.clone().unwrap_or_default(),
 
             calls,
             steps: Vec::new(),
         }
     }
 
+    /// Return the price paid for each unit of gas of this transaction in a
+    /// block with `base_fee`, which is capped by the max fee per gas of an
+    /// EIP-1559 transaction.
+    pub fn effective_gas_price(&self, base_fee: Word) -> Word {
+        match (self.max_fee_per_gas, self.max_priority_fee_per_gas) {
+            (Some(max_fee), Some(max_priority_fee)) => {
+                max_fee.min(base_fee + max_priority_fee)
+            }
+            _ => self.gas_price,
+        }
+    }
+
     /// Wether this [`Transaction`] is a create one
     pub fn is_create(&self) -> bool {
         self.calls[0].is_create()
     }
 
+    /// Return the intrinsic gas of this transaction, which is charged before
+    /// its root call is executed: the base cost, the cost of each byte of the
+    /// input and the cost of each entry of the access list.
+    pub fn intrinsic_gas(&self) -> u64 {
+        let base_cost = if self.is_create() {
+            GasCost::CREATION_TX
+        } else {
+            GasCost::TX
+        };
+        let input_cost: u64 = self
+            .input
+            .iter()
+            .map(|byte| {
+                if *byte == 0 {
+                    GasCost::TX_DATA_ZERO.as_u64()
+                } else {
+                    GasCost::TX_DATA_NON_ZERO.as_u64()
+                }
+            })
+            .sum();
+        let access_list_cost: u64 = self
+            .access_list
+            .0
+            .iter()
+            .map(|item| {
+                GasCost::TX_ACCESS_LIST_ADDRESS.as_u64()
+                    + item.storage_keys.len() as u64
+                        * GasCost::TX_ACCESS_LIST_STORAGE_KEY.as_u64()
+            })
+            .sum();
+        base_cost.as_u64() + input_cost + access_list_cost
+    }
+
     /// Return the list of execution steps of this transaction.
     pub fn steps(&self) -> &[ExecStep] {
         &self.steps
@@ -389,22 +602,13 @@ impl Transaction {
         &mut self.steps
     }
 
-    fn push_call(
-        &mut self,
-        parent_index: usize,
-        kind: CallKind,
-        address: Address,
-    ) -> usize {
-        let is_static =
-            kind == CallKind::StaticCall || self.calls[parent_index].is_static;
-        let code_hash = H256::zero();
-        self.calls.push(Call {
-            kind,
-            is_static,
-            is_root: false,
-            address,
-            code_hash,
-        });
+    /// Return the list of calls of this transaction.
+    pub fn calls(&self) -> &[Call] {
+        &self.calls
+    }
+
+    fn push_call(&mut self, call: Call) -> usize {
+        self.calls.push(call);
         self.calls.len() - 1
     }
 }
@@ -426,29 +630,49 @@ pub struct CircuitInputStateRef<'a> {
     pub step: &'a mut ExecStep,
 }
 
-/// Helper function to push a call into a Transaction and TransactionContext
-fn push_call(
-    tx: &mut Transaction,
-    tx_ctx: &mut TransactionContext,
-    kind: CallKind,
-    address: Address,
-) {
-    let parent_index = tx_ctx.call_index();
-    let index = tx.push_call(parent_index, kind, address);
-    tx_ctx.push_call_index_ctx(index, CallContext { swc: 0 });
-}
-
 impl<'a> CircuitInputStateRef<'a> {
     /// Push an [`Operation`] into the [`OperationContainer`] with the next
     /// [`GlobalCounter`] and then adds a reference to the stored operation
     /// ([`OperationRef`]) inside the bus-mapping instance of the current
     /// [`ExecStep`].  Then increase the block_ctx [`GlobalCounter`] by one.
-    pub fn push_op<T: Op>(&mut self, op: T) {
+    pub fn push_op<T: Op>(&mut self, op: T) -> OperationRef {
         let op_ref = self
             .block
             .container
             .insert(Operation::new(self.block_ctx.gc.inc_pre(), op));
         self.step.bus_mapping_instance.push(op_ref);
+        op_ref
+    }
+
+    /// Push an [`Operation`] that writes the state, which is reverted if the
+    /// current [`Call`] is not persistent, like [`Self::push_op`].  This
+    /// increases the state write counter of the current [`CallContext`].
+    pub fn push_op_reversible<T: Op>(
+        &mut self,
+        op: T,
+    ) -> Result<OperationRef, Error> {
+        let op_ref = self.push_op(op);
+        self.call_ctx_mut().swc += 1;
+        if !self.call().is_persistent {
+            self.tx_ctx
+                .reversion_groups
+                .last_mut()
+                .ok_or(Error::ReversionGroupNotFound)?
+                .op_refs
+                .push(op_ref);
+        }
+        Ok(op_ref)
+    }
+
+    /// Push an [`Operation`] that reverts a previous state write, like
+    /// [`Self::push_op`].
+    fn push_op_reversion<T: Op>(&mut self, op: T) -> OperationRef {
+        let op_ref = self
+            .block
+            .container
+            .insert(Operation::new_reversion(self.block_ctx.gc.inc_pre(), op));
+        self.step.bus_mapping_instance.push(op_ref);
+        op_ref
     }
 
     /// Reference to the current Call
@@ -461,15 +685,215 @@ impl<'a> CircuitInputStateRef<'a> {
         self.tx_ctx.call_ctx()
     }
 
+    /// Mutable reference to the current CallContext
+    pub fn call_ctx_mut(&mut self) -> &mut CallContext {
+        self.tx_ctx.call_ctx_mut()
+    }
+
     /// Mutable reference to the current Call
     pub fn call_mut(&mut self) -> &mut Call {
         &mut self.tx.calls[self.tx_ctx.call_index()]
     }
 
     /// Push a new [`Call`] into the [`Transaction`], and add its index and
-    /// [`CallContext`] in the `call_stack` of the [`TransactionContext`]
-    pub fn push_call(&mut self, kind: CallKind, address: Address) {
-        push_call(self.tx, self.tx_ctx, kind, address)
+    /// [`CallContext`] in the `call_stack` of the [`TransactionContext`].  A
+    /// failed call starts a new reversion group, and a successful call that
+    /// is not persistent joins the one of its caller.
+    pub fn push_call(&mut self, call: Call) -> Result<(), Error> {
+        let (is_success, is_persistent) = (call.is_success, call.is_persistent);
+        let index = self.tx.push_call(call);
+        if !is_success {
+            self.tx_ctx
+                .reversion_groups
+                .push(ReversionGroup::new(index));
+        } else if !is_persistent {
+            let group = self
+                .tx_ctx
+                .reversion_groups
+                .last_mut()
+                .ok_or(Error::ReversionGroupNotFound)?;
+            group.calls.push((index, group.op_refs.len()));
+        }
+        // The current step, which is the caller one, is not in the transaction
+        // yet, so the first step of the new call comes right after it.
+        let step_index = self.tx.steps.len() + 1;
+        self.tx_ctx
+            .push_call_index_ctx(index, CallContext::new(step_index));
+        Ok(())
+    }
+
+    /// Id of the current [`Transaction`] in the [`Block`], starting at 1.
+    pub fn tx_id(&self) -> usize {
+        self.block.txs.len() + 1
+    }
+
+    /// Id of a [`Call`] pushed next, which is the global counter of the next
+    /// operation.
+    pub fn next_call_id(&self) -> usize {
+        self.block_ctx.gc.0 + 1
+    }
+
+    /// Depth of the current [`Call`], which is 1 for the root call.
+    pub fn call_depth(&self) -> usize {
+        self.tx_ctx.call_stack.len()
+    }
+
+    /// Push a [`CallContextOp`] into the `field` of the context of the current
+    /// [`Call`].
+    pub fn push_call_context_op(
+        &mut self,
+        rw: RW,
+        field: CallContextField,
+        value: Word,
+    ) {
+        let call_id = self.call().call_id;
+        self.push_op(CallContextOp::new(rw, call_id, field, value));
+    }
+
+    /// Push the [`CallContextOp`] writes that save the context of the current
+    /// call before the *CALL* or {CREATE, CREATE2} at `steps[0]` executes a
+    /// new call.  The saved context is the one found in the current call after
+    /// the new call returns, except for the gas left which is the one before
+    /// the new call consumes it.
+    pub(crate) fn save_call_context(&mut self, steps: &[GethExecStep]) {
+        let step = &steps[0];
+        let next_step = steps[1..]
+            .iter()
+            .find(|s| s.depth == step.depth)
+            .unwrap_or(step);
+        let swc = self.call_ctx().swc;
+        for (field, value) in [
+            (CallContextField::ProgramCounter, next_step.pc.0),
+            (
+                CallContextField::StackPointer,
+                1024 - next_step.stack.0.len(),
+            ),
+            (
+                CallContextField::GasLeft,
+                (step.gas.0 - step.gas_cost.as_u64()) as usize,
+            ),
+            (CallContextField::MemorySize, next_step.memory.0.len() / 32),
+            (CallContextField::StateWriteCounter, swc),
+        ] {
+            self.push_call_context_op(RW::WRITE, field, Word::from(value));
+        }
+    }
+
+    /// Push the [`CallContextOp`] reads of the context of the current call,
+    /// right after it has been pushed by a *CALL* or {CREATE, CREATE2}.
+    pub(crate) fn init_call_context(&mut self) -> Result<(), Error> {
+        self.push_rw_counter_end_of_reversion_op()?;
+        let caller_index = self.tx_ctx.call_stack[self.call_depth() - 2].0;
+        let caller_call_id = self.tx.calls[caller_index].call_id;
+        let tx_id = self.tx_id();
+        let depth = self.call_depth();
+        let call = self.call();
+        let fields = [
+            (CallContextField::CallerCallId, Word::from(caller_call_id)),
+            (CallContextField::TxId, Word::from(tx_id)),
+            (CallContextField::Depth, Word::from(depth)),
+            (
+                CallContextField::CallerAddress,
+                call.caller_address.to_word(),
+            ),
+            (CallContextField::CalleeAddress, call.address.to_word()),
+            (
+                CallContextField::CallDataOffset,
+                Word::from(call.call_data_offset),
+            ),
+            (
+                CallContextField::CallDataLength,
+                Word::from(call.call_data_length),
+            ),
+            (
+                CallContextField::ReturnDataOffset,
+                Word::from(call.return_data_offset),
+            ),
+            (
+                CallContextField::ReturnDataLength,
+                Word::from(call.return_data_length),
+            ),
+            (CallContextField::Value, call.value),
+            (CallContextField::Result, Word::from(call.is_success as u64)),
+            (
+                CallContextField::IsPersistent,
+                Word::from(call.is_persistent as u64),
+            ),
+            (
+                CallContextField::IsStatic,
+                Word::from(call.is_static as u64),
+            ),
+            (CallContextField::IsRoot, Word::from(call.is_root as u64)),
+            (
+                CallContextField::IsCreate,
+                Word::from(call.is_create() as u64),
+            ),
+            (CallContextField::OpcodeSource, call.code_hash.to_word()),
+        ];
+        for (field, value) in fields {
+            self.push_call_context_op(RW::READ, field, value);
+        }
+        Ok(())
+    }
+
+    /// Push the [`CallContextOp`] read of the end of the reversion of the
+    /// current [`Call`].  When the call is not persistent, the value is only
+    /// known once its reversion group is reverted, so the read is recorded in
+    /// the group to be patched then.
+    fn push_rw_counter_end_of_reversion_op(&mut self) -> Result<(), Error> {
+        let call = self.call();
+        let op = CallContextOp::new(
+            RW::READ,
+            call.call_id,
+            CallContextField::RwCounterEndOfReversion,
+            Word::from(call.rw_counter_end_of_reversion),
+        );
+        let is_persistent = call.is_persistent;
+        let op_ref = self.push_op(op);
+        if !is_persistent {
+            let group = self
+                .tx_ctx
+                .reversion_groups
+                .last_mut()
+                .ok_or(Error::ReversionGroupNotFound)?;
+            group
+                .call_context_op_refs
+                .push((group.calls.len() - 1, op_ref));
+        }
+        Ok(())
+    }
+
+    /// Push the [`CallContextOp`] reads that restore the context of the caller
+    /// saved by [`Self::save_call_context`] when the current call returns.
+    fn restore_caller_context(&mut self) {
+        let caller_index = self.tx_ctx.call_stack[self.call_depth() - 2].0;
+        let caller_call_id = self.tx.calls[caller_index].call_id;
+        for field in [
+            CallContextField::ProgramCounter,
+            CallContextField::StackPointer,
+            CallContextField::GasLeft,
+            CallContextField::MemorySize,
+            CallContextField::StateWriteCounter,
+        ] {
+            let value = self
+                .block
+                .container
+                .call_context
+                .iter()
+                .rev()
+                .map(Operation::op)
+                .find(|op| {
+                    op.call_id() == caller_call_id && op.field() == &field
+                })
+                .map(|op| *op.value())
+                .unwrap_or_else(Word::zero);
+            self.push_op(CallContextOp::new(
+                RW::READ,
+                caller_call_id,
+                field,
+                value,
+            ));
+        }
     }
 }
 
@@ -537,14 +961,17 @@ impl<'a> CircuitInputBuilder {
     /// Handle a transaction with its corresponding execution trace to generate
     /// all the associated operations.  Each operation is registered in
     /// `self.block.container`, and each step stores the [`OperationRef`] to
-    /// each of the generated operations.
+    /// each of the generated operations.  The gas cost of each successful step
+    /// is validated against the one computed by [`gas::step_gas_cost`].
     pub fn handle_tx(
         &mut self,
         eth_tx: &eth_types::Transaction,
         geth_trace: &GethExecTrace,
     ) -> Result<(), Error> {
+        self.sdb.commit_tx();
         let mut tx = Transaction::new(eth_tx);
         let mut tx_ctx = TransactionContext::new(eth_tx);
+        self.begin_tx(&mut tx, &mut tx_ctx, geth_trace)?;
         for (index, geth_step) in geth_trace.struct_logs.iter().enumerate() {
             let mut step = ExecStep::new(
                 geth_step,
@@ -553,21 +980,58 @@ impl<'a> CircuitInputBuilder {
                 tx_ctx.call_ctx().swc,
             );
             let mut state_ref = self.state_ref(&mut tx, &mut tx_ctx, &mut step);
-            geth_step.op.gen_associated_ops(
-                &mut state_ref,
-                &geth_trace.struct_logs[index..],
+            // The error is found before generating the associated operations
+            // since they modify the state used to detect it.
+            state_ref.step.error = state_ref.get_step_err(
+                geth_step,
+                geth_trace.struct_logs.get(index + 1),
             )?;
+            // The gas cost of a successful step is computed independently of
+            // the traced one, also before its associated operations.
+            if state_ref.step.error.is_none() {
+                let gas_cost = gas::step_gas_cost(&state_ref, geth_step)?;
+                if gas_cost != geth_step.gas_cost {
+                    return Err(Error::GasCostMismatch(
+                        index,
+                        gas_cost,
+                        Box::new(geth_step.clone()),
+                    ));
+                }
+            }
+            // The arguments of the step can't be read when the step fails due
+            // to a stack error or an invalid opcode, so no operation is done.
+            if !matches!(
+                state_ref.step.error,
+                Some(
+                    ExecError::StackOverflow
+                        | ExecError::StackUnderflow
+                        | ExecError::InvalidOpcode
+                )
+            ) {
+                geth_step.op.gen_associated_ops(
+                    &mut state_ref,
+                    &geth_trace.struct_logs[index..],
+                )?;
+            }
+            match geth_trace.struct_logs.get(index + 1) {
+                Some(geth_next_step)
+                    if geth_step.depth - 1 == geth_next_step.depth =>
+                {
+                    let success = !geth_next_step.stack.last()?.is_zero();
+                    state_ref.handle_return(geth_step, success)?;
+                }
+                None => {
+                    state_ref.handle_return(geth_step, !geth_trace.failed)?
+                }
+                _ => {}
+            }
             tx.steps.push(step);
 
+            // The new call of a *CALL* is pushed into the call stack by the
+            // opcode implementation, which knows how to derive its context.
             if let Some(geth_next_step) = geth_trace.struct_logs.get(index + 1)
             {
-                if geth_step.depth + 1 == geth_next_step.depth {
-                    // Handle *CALL*
-                    // TODO: Set the proper address according to the call kind.
-                    let address = Address::zero();
-                    let kind = CallKind::try_from(geth_step.op)?;
-                    push_call(&mut tx, &mut tx_ctx, kind, address);
-                } else if geth_step.depth - 1 == geth_next_step.depth {
+                if geth_step.depth - 1 == geth_next_step.depth {
                     // Handle *CALL* return
                     if tx_ctx.call_stack.len() == 1 {
                         return Err(Error::InvalidGethExecStep(
@@ -586,25 +1050,107 @@ impl<'a> CircuitInputBuilder {
                 }
             }
         }
+        self.end_tx(&mut tx, &mut tx_ctx, geth_trace)?;
+        // The accounts destructed by SELFDESTRUCT are removed at the end of the
+        // transaction.
+        self.sdb.clear_destructed_accounts();
         self.block.txs.push(tx);
         Ok(())
     }
+
+    /// Set up the root call of the transaction `tx` with the result found in
+    /// its `geth_trace`, and push the [`ExecState::BeginTx`] step with all
+    /// its associated operations into `tx`.  The step charges the intrinsic
+    /// gas of the transaction.
+    pub fn begin_tx(
+        &mut self,
+        tx: &mut Transaction,
+        tx_ctx: &mut TransactionContext,
+        geth_trace: &GethExecTrace,
+    ) -> Result<(), Error> {
+        // The root call executes the code of the callee account, or the tx
+        // input as init code in a create transaction.
+        tx.calls[0].code_hash = if tx.is_create() {
+            self.sdb.set_code(tx.input.clone())
+        } else {
+            let (_, account) = self.sdb.get_account(&tx.to);
+            account.code_hash()
+        };
+        tx.calls[0].call_id = self.block_ctx.gc.0 + 1;
+        tx.calls[0].is_success = !geth_trace.failed;
+        tx.calls[0].is_persistent = !geth_trace.failed;
+        if geth_trace.failed {
+            tx_ctx.reversion_groups.push(ReversionGroup::new(0));
+        }
+
+        let mut step = ExecStep::new_tx_step(
+            ExecState::BeginTx,
+            Gas(tx.gas),
+            self.block_ctx.gc,
+            0,
+        );
+        step.gas_cost = GasCost::from(tx.intrinsic_gas());
+        self.state_ref(tx, tx_ctx, &mut step).gen_begin_tx_ops()?;
+        tx.steps.push(step);
+        Ok(())
+    }
+
+    /// Push the [`ExecState::EndTx`] step of the transaction `tx`, after its
+    /// root call has returned, with all its associated operations into `tx`.
+    pub fn end_tx(
+        &mut self,
+        tx: &mut Transaction,
+        tx_ctx: &mut TransactionContext,
+        geth_trace: &GethExecTrace,
+    ) -> Result<(), Error> {
+        // A step that fails with an error other than REVERT consumes all the
+        // gas left, and the gas left after the intrinsic gas is returned
+        // when there are no steps.
+        let gas_left = match geth_trace.struct_logs.last() {
+            Some(geth_step) if geth_step.error.is_some() => 0,
+            Some(geth_step) => geth_step
+                .gas
+                .0
+                .checked_sub(geth_step.gas_cost.as_u64())
+                .ok_or_else(|| {
+                    Error::InvalidGethExecStep(
+                        "gas cost greater than gas left",
+                        Box::new(geth_step.clone()),
+                    )
+                })?,
+            None => tx.gas.saturating_sub(tx.intrinsic_gas()),
+        };
+        let mut step = ExecStep::new_tx_step(
+            ExecState::EndTx,
+            Gas(gas_left),
+            self.block_ctx.gc,
+            tx_ctx.call_ctx().swc,
+        );
+        self.state_ref(tx, tx_ctx, &mut step)
+            .gen_end_tx_ops(gas_left);
+        tx.steps.push(step);
+        Ok(())
+    }
 }
 
-fn get_step_reported_error(op: &OpcodeId, error: &str) -> ExecError {
-    if error == GETH_ERR_WRITE_PROTECTION {
+fn get_step_reported_error(
+    step: &GethExecStep,
+    error: &str,
+) -> Result<ExecError, Error> {
+    Ok(if error == GETH_ERR_WRITE_PROTECTION {
         ExecError::WriteProtection
     } else if error == GETH_ERR_OUT_OF_GAS
         || error == GETH_ERR_GAS_UINT_OVERFLOW
     {
         // NOTE: We report a GasUintOverflow error as an OutOfGas error
-        let oog_err = match op {
+        let oog_err = match step.op {
             OpcodeId::SHA3 => OogError::Sha3,
             OpcodeId::CALLDATACOPY => OogError::CallDataCopy,
             OpcodeId::CODECOPY => OogError::CodeCopy,
             OpcodeId::EXTCODECOPY => OogError::ExtCodeCopy,
             OpcodeId::RETURNDATACOPY => OogError::ReturnDataCopy,
             OpcodeId::LOG0
+            | OpcodeId::LOG1
             | OpcodeId::LOG2
             | OpcodeId::LOG3
             | OpcodeId::LOG4 => OogError::Log,
@@ -629,20 +1175,565 @@ fn get_step_reported_error(op: &OpcodeId, error: &str) -> ExecError {
     } else if error.starts_with(GETH_ERR_INVALID_OPCODE) {
         ExecError::InvalidOpcode
     } else {
-        panic!("Unknown GethExecStep.error: {}", error);
+        return Err(Error::UnexpectedExecStepError(
+            "unknown GethExecStep.error",
+            Box::new(step.clone()),
+        ));
+    })
+}
+
+/// Return the range of memory of `length` bytes from `offset` accessed by a
+/// step, which is empty when `length` is zero regardless of `offset` since the
+/// memory isn't expanded then.  Returns [`Error::WordToMemAddr`] when the range
+/// doesn't fit in the memory address space.
+pub fn get_memory_range(
+    offset: Word,
+    length: Word,
+) -> Result<Range<usize>, Error> {
+    if length.is_zero() {
+        return Ok(0..0);
+    }
+    let to_usize =
+        |value: Word| (value.bits() <= 64).then(|| value.low_u64() as usize);
+    match (to_usize(offset), to_usize(length)) {
+        (Some(offset), Some(length)) => offset
+            .checked_add(length)
+            .map(|end| offset..end)
+            .ok_or(Error::WordToMemAddr),
+        _ => Err(Error::WordToMemAddr),
     }
 }
 
-/// Retreive the init_code from memory for {CREATE, CREATE2}
-pub fn get_create_init_code(step: &GethExecStep) -> Result<&[u8], Error> {
-    let offset = step.stack.nth_last(1)?;
-    let length = step.stack.nth_last(2)?;
-    Ok(&step.memory.0[offset.low_u64() as usize
-        ..(offset.low_u64() + length.low_u64()) as usize])
+/// Retreive the bytes in `range` from the memory of `step`.  Bytes beyond the
+/// memory size are returned as zero since the memory is expanded by the step.
+pub fn get_memory_bytes(step: &GethExecStep, range: Range<usize>) -> Vec<u8> {
+    range
+        .map(|addr| step.memory.0.get(addr).copied().unwrap_or(0))
+        .collect()
+}
+
+/// Retreive the returned data from memory for {RETURN, REVERT}.  Bytes beyond
+/// the memory size are returned as zero since the memory is expanded by the
+/// step.
+pub fn get_return_data(step: &GethExecStep) -> Result<Vec<u8>, Error> {
+    let range =
+        get_memory_range(step.stack.nth_last(0)?, step.stack.nth_last(1)?)?;
+    Ok(get_memory_bytes(step, range))
+}
+
+/// Retreive the init_code from memory for {CREATE, CREATE2}.  Bytes beyond
+/// the memory size are returned as zero since the memory is expanded by the
+/// step.
+pub fn get_create_init_code(step: &GethExecStep) -> Result<Vec<u8>, Error> {
+    let range =
+        get_memory_range(step.stack.nth_last(1)?, step.stack.nth_last(2)?)?;
+    Ok(get_memory_bytes(step, range))
 }
 
 impl<'a> CircuitInputStateRef<'a> {
-    fn create_address(&self) -> Result<Address, Error> {
+    /// Handle the return from the current call, where `step` is the last step
+    /// executed in it:
+    /// - When a {CREATE, CREATE2} call succeeds, the code returned by the init
+    ///   code is deployed at the call address.
+    /// - The return data of the caller is set to the data returned by RETURN or
+    ///   REVERT, and cleared otherwise, and the context of the caller is
+    ///   restored.
+    /// - When the call fails, all its steps are marked as reverted, and the
+    ///   state writes of its reversion group are reverted.
+    fn handle_return(
+        &mut self,
+        step: &GethExecStep,
+        success: bool,
+    ) -> Result<(), Error> {
+        let data = match step.op {
+            OpcodeId::RETURN | OpcodeId::REVERT if step.error.is_none() => {
+                get_return_data(step)?
+            }
+            _ => Vec::new(),
+        };
+
+        let return_data = if self.call().is_create() {
+            if success {
+                let address = self.call().address;
+                self.deploy_code(address, data)?;
+                Vec::new()
+            } else if step.op == OpcodeId::REVERT {
+                data
+            } else {
+                Vec::new()
+            }
+        } else if success || step.op == OpcodeId::REVERT {
+            data
+        } else {
+            Vec::new()
+        };
+        if let Some(caller_ctx) = self.tx_ctx.caller_ctx_mut() {
+            caller_ctx.return_data = return_data;
+            self.restore_caller_context();
+        }
+
+        if !success {
+            let step_index = self.call_ctx().step_index;
+            for reverted_step in self.tx.steps[step_index..].iter_mut() {
+                reverted_step.reverted = true;
+            }
+            self.step.reverted = true;
+            self.handle_reversion()?;
+        }
+        Ok(())
+    }
+
+    /// Handle the return from the current call, which executes a precompiled
+    /// contract without any step, with its `output` when it succeeds:
+    /// - The return data of the caller is set to the output, and cleared when
+    ///   the call fails, and the context of the caller is restored.
+    /// - When the call fails, the state writes of its reversion group are
+    ///   reverted.
+    /// - The call is popped from the call stack, and its state writes are
+    ///   accumulated in the caller when it succeeds.
+    pub(crate) fn handle_precompile_return(
+        &mut self,
+        output: Option<Vec<u8>>,
+    ) -> Result<(), Error> {
+        let success = output.is_some();
+        if let Some(caller_ctx) = self.tx_ctx.caller_ctx_mut() {
+            caller_ctx.return_data = output.unwrap_or_default();
+            self.restore_caller_context();
+        }
+
+        if !success {
+            self.handle_reversion()?;
+        }
+        let (_, call_ctx) = self
+            .tx_ctx
+            .pop_call_index_ctx()
+            .expect("call stack is empty");
+        if success {
+            self.tx_ctx.call_ctx_mut().swc += call_ctx.swc;
+        }
+        Ok(())
+    }
+
+    /// Revert the state writes of the reversion group of the current call,
+    /// which has failed.  The reversion writes are pushed in reverse order,
+    /// so that the state write at position `i` in a call is reverted at the
+    /// global counter `rw_counter_end_of_reversion - i` of the call.
+    fn handle_reversion(&mut self) -> Result<(), Error> {
+        let group = self
+            .tx_ctx
+            .reversion_groups
+            .pop()
+            .ok_or(Error::ReversionGroupNotFound)?;
+        debug_assert_eq!(group.calls[0].0, self.tx_ctx.call_index());
+        for op_ref in group.op_refs.iter().rev() {
+            self.push_reverted_op(*op_ref);
+        }
+
+        // Set the end of the reversion of the calls in the group, also in
+        // the call context reads done before it was known.
+        let rw_counter_end_of_reversion = self.block_ctx.gc.0;
+        for (call_index, offset) in group.calls.iter() {
+            self.tx.calls[*call_index].rw_counter_end_of_reversion =
+                rw_counter_end_of_reversion - offset;
+        }
+        for (position, op_ref) in group.call_context_op_refs {
+            let call = &self.tx.calls[group.calls[position].0];
+            let value = Word::from(call.rw_counter_end_of_reversion);
+            let operation =
+                &mut self.block.container.call_context[op_ref.as_usize() - 1];
+            let op = operation.op();
+            *operation.op_mut() =
+                CallContextOp::new(op.rw(), op.call_id(), *op.field(), value);
+        }
+        Ok(())
+    }
+
+    /// Push the reversion write of the state write at `op_ref`, which writes
+    /// back its previous value, and undo the state write in the [`StateDB`].
+    fn push_reverted_op(&mut self, op_ref: OperationRef) {
+        let container = &self.block.container;
+        let index = op_ref.as_usize() - 1;
+        match op_ref.target() {
+            Target::Storage => {
+                let op = container.storage[index].op().clone();
+                let (_, value) =
+                    self.sdb.get_storage_mut(op.address(), op.key());
+                *value = *op.value_prev();
+                self.push_op_reversion(StorageOp::new(
+                    RW::WRITE,
+                    *op.address(),
+                    *op.key(),
+                    *op.value_prev(),
+                    *op.value(),
+                ));
+            }
+            Target::TxAccessListAccount => {
+                let op = container.tx_access_list_account[index].op().clone();
+                if !op.value_prev() {
+                    self.sdb.remove_account_from_access_list(op.address());
+                }
+                self.push_op_reversion(TxAccessListAccountOp::new(
+                    RW::WRITE,
+                    op.tx_id(),
+                    *op.address(),
+                    op.value_prev(),
+                    op.value(),
+                ));
+            }
+            Target::TxAccessListStorageSlot => {
+                let op =
+                    container.tx_access_list_storage_slot[index].op().clone();
+                if !op.value_prev() {
+                    self.sdb.remove_account_storage_from_access_list(
+                        op.address(),
+                        op.key(),
+                    );
+                }
+                self.push_op_reversion(TxAccessListStorageSlotOp::new(
+                    RW::WRITE,
+                    op.tx_id(),
+                    *op.address(),
+                    *op.key(),
+                    op.value_prev(),
+                    op.value(),
+                ));
+            }
+            Target::TxRefund => {
+                let op = container.tx_refund[index].op().clone();
+                self.sdb.set_refund(op.value_prev());
+                self.push_op_reversion(TxRefundOp::new(
+                    RW::WRITE,
+                    op.tx_id(),
+                    op.value_prev(),
+                    op.value(),
+                ));
+            }
+            Target::Account => {
+                let op = container.account[index].op().clone();
+                let (_, account) = self.sdb.get_account_mut(op.address());
+                let value_prev = *op.value_prev();
+                match op.field() {
+                    AccountField::Nonce => account.nonce = value_prev,
+                    AccountField::Balance => account.balance = value_prev,
+                    AccountField::CodeHash => {
+                        account.codeHash = H256(value_prev.to_be_bytes())
+                    }
+                }
+                self.push_op_reversion(AccountOp::new(
+                    RW::WRITE,
+                    *op.address(),
+                    *op.field(),
+                    value_prev,
+                    *op.value(),
+                ));
+            }
+            Target::AccountDestructed => {
+                let op = container.account_destructed[index].op().clone();
+                if !op.value_prev() {
+                    self.sdb.undo_destruct_account(op.address());
+                }
+                self.push_op_reversion(AccountDestructedOp::new(
+                    RW::WRITE,
+                    *op.address(),
+                    op.value_prev(),
+                    op.value(),
+                ));
+            }
+            target => {
+                unreachable!("{:?} operations are not reversible", target)
+            }
+        }
+    }
+
+    /// Generate the operations of the [`ExecState::BeginTx`] step of the
+    /// current transaction:
+    /// - The transaction id, the end of the reversion and the persistence of
+    ///   the root call are read from its context.
+    /// - The nonce of the sender is increased and the fee of all the gas of the
+    ///   transaction is charged to it.  These writes are never reverted.
+    /// - The sender, the callee, the precompiled contracts and the entries of
+    ///   the access list of the transaction (EIP-2930) are added to the access
+    ///   list (EIP-2929), which is never reverted either.
+    /// - The value of the transaction is transferred from the sender to the
+    ///   callee, which is reverted when the root call fails.
+    /// - The code hash of the callee is read, unless it's a create transaction,
+    ///   whose root call executes the init code.
+    fn gen_begin_tx_ops(&mut self) -> Result<(), Error> {
+        let tx_id = self.tx_id();
+        let call = self.call();
+        let (callee, value, code_hash, is_persistent) =
+            (call.address, call.value, call.code_hash, call.is_persistent);
+        self.push_call_context_op(
+            RW::READ,
+            CallContextField::TxId,
+            Word::from(tx_id),
+        );
+        self.push_rw_counter_end_of_reversion_op()?;
+        self.push_call_context_op(
+            RW::READ,
+            CallContextField::IsPersistent,
+            Word::from(is_persistent as u64),
+        );
+
+        let sender = self.tx.from;
+        let gas_price = self
+            .tx
+            .effective_gas_price(*self.block.constants.base_fee());
+        let (_, account) = self.sdb.get_account_mut(&sender);
+        let (nonce_prev, balance_prev) = (account.nonce, account.balance);
+        let balance = gas_price
+            .checked_mul(Word::from(self.tx.gas))
+            .and_then(|fee| balance_prev.checked_sub(fee))
+            .ok_or(Error::InsufficientBalance(sender))?;
+        account.nonce = nonce_prev + 1;
+        account.balance = balance;
+        self.push_op(AccountOp::new(
+            RW::WRITE,
+            sender,
+            AccountField::Nonce,
+            nonce_prev + 1,
+            nonce_prev,
+        ));
+        self.push_op(AccountOp::new(
+            RW::WRITE,
+            sender,
+            AccountField::Balance,
+            balance,
+            balance_prev,
+        ));
+
+        let addresses = [sender, callee]
+            .into_iter()
+            .chain(Precompile::ALL.iter().map(Precompile::address))
+            .chain(self.tx.access_list.0.iter().map(|item| item.address))
+            .collect::<Vec<_>>();
+        for address in addresses {
+            let is_warm = self.sdb.add_account_to_access_list(&address);
+            self.push_op(TxAccessListAccountOp::new(
+                RW::WRITE,
+                tx_id,
+                address,
+                true,
+                is_warm,
+            ));
+        }
+        let storage_slots = self
+            .tx
+            .access_list
+            .0
+            .iter()
+            .flat_map(|item| {
+                item.storage_keys
+                    .iter()
+                    .map(|key| (item.address, key.to_word()))
+            })
+            .collect::<Vec<_>>();
+        for (address, key) in storage_slots {
+            let is_warm =
+                self.sdb.add_account_storage_to_access_list(&address, &key);
+            self.push_op(TxAccessListStorageSlotOp::new(
+                RW::WRITE,
+                tx_id,
+                address,
+                key,
+                true,
+                is_warm,
+            ));
+        }
+
+        // The account created by a create transaction starts with nonce 1
+        // (EIP-161), which is reverted with the value transfer when the init
+        // code fails.
+        if self.tx.is_create() {
+            let (_, account) = self.sdb.get_account_mut(&callee);
+            if account.has_create_collision() {
+                return Err(Error::ContractAddressCollision(callee));
+            }
+            account.nonce = Word::one();
+            self.push_op_reversible(AccountOp::new(
+                RW::WRITE,
+                callee,
+                AccountField::Nonce,
+                Word::one(),
+                Word::zero(),
+            ))?;
+        }
+
+        self.transfer(sender, callee, value)?;
+
+        if !self.tx.is_create() {
+            self.push_op(AccountOp::new(
+                RW::READ,
+                callee,
+                AccountField::CodeHash,
+                code_hash.to_word(),
+                code_hash.to_word(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Generate the operations of the [`ExecState::EndTx`] step of the
+    /// current transaction, with `gas_left` after its root call returns:
+    /// - The transaction id is read from the context of the root call, and the
+    ///   refund counter of the transaction is read.
+    /// - The gas left and the refund, capped to a quotient of the gas used
+    ///   (EIP-3529), are paid back to the sender.
+    /// - The priority fee over the block base fee (EIP-1559) of the gas used is
+    ///   paid to the coinbase.
+    fn gen_end_tx_ops(&mut self, gas_left: u64) {
+        let tx_id = self.tx_id();
+        self.push_call_context_op(
+            RW::READ,
+            CallContextField::TxId,
+            Word::from(tx_id),
+        );
+        let refund = self.sdb.refund();
+        self.push_op(TxRefundOp::new(RW::READ, tx_id, refund, refund));
+
+        let gas_used = self.tx.gas - gas_left;
+        let refund = refund.min(gas_used / MAX_REFUND_QUOTIENT_OF_GAS_USED);
+        let base_fee = *self.block.constants.base_fee();
+        let gas_price = self.tx.effective_gas_price(base_fee);
+        let priority_fee = gas_price.saturating_sub(base_fee);
+        let coinbase = *self.block.constants.coinbase();
+        for (address, value) in [
+            (self.tx.from, gas_price * (gas_left + refund)),
+            (coinbase, priority_fee * (gas_used - refund)),
+        ] {
+            let (_, account) = self.sdb.get_account_mut(&address);
+            let balance_prev = account.balance;
+            account.balance = balance_prev + value;
+            self.push_op(AccountOp::new(
+                RW::WRITE,
+                address,
+                AccountField::Balance,
+                balance_prev + value,
+                balance_prev,
+            ));
+        }
+    }
+
+    /// Store the `code` in the [`StateDB`] code database and set its hash in
+    /// the [`Account`](crate::state_db::Account) at `address`, pushing the
+    /// corresponding [`AccountOp`] write.
+    pub(crate) fn deploy_code(
+        &mut self,
+        address: Address,
+        code: Vec<u8>,
+    ) -> Result<(), Error> {
+        let code_hash = self.sdb.set_code(code);
+        let (_, account) = self.sdb.get_account_mut(&address);
+        let code_hash_prev = account.codeHash;
+        account.codeHash = code_hash;
+        self.push_op_reversible(AccountOp::new(
+            RW::WRITE,
+            address,
+            AccountField::CodeHash,
+            code_hash.to_word(),
+            code_hash_prev.to_word(),
+        ))?;
+        Ok(())
+    }
+
+    /// Transfer `value` from the [`Account`](crate::state_db::Account) at
+    /// `sender` to the one at `receiver`, pushing the corresponding
+    /// [`AccountOp`] writes of their balances.  Returns an error when the
+    /// balance of `sender` is lower than `value`.
+    pub(crate) fn transfer(
+        &mut self,
+        sender: Address,
+        receiver: Address,
+        value: Word,
+    ) -> Result<(), Error> {
+        for (address, is_sender) in [(sender, true), (receiver, false)] {
+            let (_, account) = self.sdb.get_account_mut(&address);
+            let balance_prev = account.balance;
+            account.balance = if is_sender {
+                balance_prev
+                    .checked_sub(value)
+                    .ok_or(Error::InsufficientBalance(sender))?
+            } else {
+                balance_prev + value
+            };
+            let balance = account.balance;
+            self.push_op_reversible(AccountOp::new(
+                RW::WRITE,
+                address,
+                AccountField::Balance,
+                balance,
+                balance_prev,
+            ))?;
+        }
+        Ok(())
+    }
+
+    /// Add the [`Account`](crate::state_db::Account) at `address` to the
+    /// access list of the current transaction, pushing the corresponding
+    /// [`TxAccessListAccountOp`] write.  Returns whether the account was
+    /// already in it (warm).
+    pub(crate) fn add_address_to_access_list(
+        &mut self,
+        address: Address,
+    ) -> Result<bool, Error> {
+        let is_warm = self.sdb.add_account_to_access_list(&address);
+        let tx_id = self.tx_id();
+        self.push_op_reversible(TxAccessListAccountOp::new(
+            RW::WRITE,
+            tx_id,
+            address,
+            true,
+            is_warm,
+        ))?;
+        Ok(is_warm)
+    }
+
+    /// Add the storage slot at `key` of the
+    /// [`Account`](crate::state_db::Account) at `address` to the access list
+    /// of the current transaction, pushing the corresponding
+    /// [`TxAccessListStorageSlotOp`] write.  Returns whether the storage slot
+    /// was already in it (warm).
+    pub(crate) fn add_storage_slot_to_access_list(
+        &mut self,
+        address: Address,
+        key: Word,
+    ) -> Result<bool, Error> {
+        let is_warm =
+            self.sdb.add_account_storage_to_access_list(&address, &key);
+        let tx_id = self.tx_id();
+        self.push_op_reversible(TxAccessListStorageSlotOp::new(
+            RW::WRITE,
+            tx_id,
+            address,
+            key,
+            true,
+            is_warm,
+        ))?;
+        Ok(is_warm)
+    }
+
+    /// Push the [`TxRefundOp`] write of the refund counter of the current
+    /// transaction, which has been updated in the [`StateDB`] from
+    /// `refund_prev`.
+    pub(crate) fn push_refund_op(
+        &mut self,
+        refund_prev: u64,
+    ) -> Result<(), Error> {
+        let tx_id = self.tx_id();
+        let refund = self.sdb.refund();
+        self.push_op_reversible(TxRefundOp::new(
+            RW::WRITE,
+            tx_id,
+            refund,
+            refund_prev,
+        ))?;
+        Ok(())
+    }
+
+    /// Return the address of the contract created by a CREATE in the current
+    /// call, which depends on the current nonce of the creator.
+    pub(crate) fn create_address(&self) -> Result<Address, Error> {
         let sender = self.call().address;
         let (found, account) = self.sdb.get_account(&sender);
         if !found {
@@ -651,47 +1742,78 @@ impl<'a> CircuitInputStateRef<'a> {
         Ok(get_contract_address(sender, account.nonce))
     }
 
-    fn create2_address(&self, step: &GethExecStep) -> Result<Address, Error> {
+    /// Return the address of the contract created by the CREATE2 at `step` in
+    /// the current call.
+    pub(crate) fn create2_address(
+        &self,
+        step: &GethExecStep,
+    ) -> Result<Address, Error> {
         let salt = step.stack.nth_last(3)?;
         let init_code = get_create_init_code(step)?;
         Ok(get_create2_address(
             self.call().address,
             salt.to_be_bytes().to_vec(),
-            init_code.to_vec(),
+            init_code,
         ))
     }
 
+    /// Return whether the range of the RETURNDATACOPY at `step` is out of the
+    /// bounds of the return data of the last sub-call of the current call.
+    pub(crate) fn return_data_out_of_bounds(
+        &self,
+        step: &GethExecStep,
+    ) -> Result<bool, Error> {
+        let offset = step.stack.nth_last(1)?;
+        let length = step.stack.nth_last(2)?;
+        let return_data_length = Word::from(self.call_ctx().return_data.len());
+        Ok(match offset.checked_add(length) {
+            Some(end) => end > return_data_length,
+            None => true,
+        })
+    }
+
     fn get_step_err(
         &self,
         step: &GethExecStep,
         next_step: Option<&GethExecStep>,
     ) -> Result<Option<ExecError>, Error> {
         if let Some(error) = &step.error {
-            return Ok(Some(get_step_reported_error(&step.op, error)));
+            return Ok(Some(get_step_reported_error(step, error)?));
         }
 
-        // When last step is RETURN or STOP there's no error.
+        // When last step is RETURN, STOP or SELFDESTRUCT there's no error.
         if matches!(next_step, None)
-            && matches!(step.op, OpcodeId::RETURN | OpcodeId::STOP)
+            && matches!(
+                step.op,
+                OpcodeId::RETURN | OpcodeId::STOP | OpcodeId::SELFDESTRUCT
+            )
         {
             return Ok(None);
         }
 
+        // The return data bounds are checked against the return data of the
+        // last sub-call kept in the call context.
+        if step.op == OpcodeId::RETURNDATACOPY
+            && self.return_data_out_of_bounds(step)?
+        {
+            return Ok(Some(ExecError::ReturnDataOutOfBounds));
+        }
+
         let next_depth = next_step.map(|s| s.depth).unwrap_or(0);
         let next_result = next_step
             .map(|s| s.stack.last().unwrap_or_else(|_| Word::zero()))
             .unwrap_or_else(Word::zero);
+        // The step is the last one of the call.  A *CALL* that executes the
+        // callee code is followed by a step in a deeper call instead.
+        let is_return = next_depth < step.depth;
 
         // Return from a call with a failure
-        if step.depth != next_depth && next_result.is_zero() {
+        if is_return && next_result.is_zero() {
             if !matches!(step.op, OpcodeId::RETURN) {
                 // Without calling RETURN
                 return Ok(Some(match step.op {
                     OpcodeId::REVERT => ExecError::ExecutionReverted,
                     OpcodeId::JUMP | OpcodeId::JUMPI => ExecError::InvalidJump,
-                    OpcodeId::RETURNDATACOPY => {
-                        ExecError::ReturnDataOutOfBounds
-                    }
                     _ => {
                         return Err(Error::UnexpectedExecStepError(
                             "call failure without return",
@@ -734,17 +1856,20 @@ impl<'a> CircuitInputStateRef<'a> {
             }
         }
 
-        // Return from a call via RETURN or STOP and having a success result is
-        // OK.
+        // Return from a call via RETURN, STOP or SELFDESTRUCT and having a
+        // success result is OK.
 
-        // Return from a call without calling RETURN or STOP and having success
-        // is unexpected.
-        if step.depth != next_depth
+        // Return from a call without calling RETURN, STOP or SELFDESTRUCT and
+        // having success is unexpected.
+        if is_return
             && next_result != Word::zero()
-            && !matches!(step.op, OpcodeId::RETURN | OpcodeId::STOP)
+            && !matches!(
+                step.op,
+                OpcodeId::RETURN | OpcodeId::STOP | OpcodeId::SELFDESTRUCT
+            )
         {
             return Err(Error::UnexpectedExecStepError(
-                "success result without {RETURN, STOP}",
+                "success result without {RETURN, STOP, SELFDESTRUCT}",
                 Box::new(step.clone()),
             ));
         }
@@ -785,6 +1910,17 @@ impl<'a> CircuitInputStateRef<'a> {
                 return Ok(Some(ExecError::InsufficientBalance));
             }
 
+            // A precompiled contract has no step, and it fails in the callee
+            // when it runs out of gas or its input is invalid.
+            if !matches!(step.op, OpcodeId::CREATE | OpcodeId::CREATE2)
+                && Precompile::from_address(
+                    &step.stack.nth_last(1)?.to_address(),
+                )
+                .is_some()
+            {
+                return Ok(None);
+            }
+
             // Address collision
             if matches!(step.op, OpcodeId::CREATE | OpcodeId::CREATE2) {
                 let address = match step.op {
@@ -792,8 +1928,8 @@ impl<'a> CircuitInputStateRef<'a> {
                     OpcodeId::CREATE2 => self.create2_address(step)?,
                     _ => unreachable!(),
                 };
-                let (found, _) = self.sdb.get_account(&address);
-                if found {
+                let (_, account) = self.sdb.get_account(&address);
+                if account.has_create_collision() {
                     return Ok(Some(ExecError::ContractAddressCollision));
                 }
             }
@@ -836,7 +1972,7 @@ impl Access {
 
 /// Given a trace and assuming that the first step is a *CALL* kind opcode,
 /// return the result if found.
-fn get_call_result(trace: &[GethExecStep]) -> Option<Word> {
+pub(crate) fn get_call_result(trace: &[GethExecStep]) -> Option<Word> {
     let depth = trace[0].depth;
     trace[1..]
         .iter()
@@ -1018,7 +2154,7 @@ mod tracer_tests {
     use crate::{
         address, bytecode,
         bytecode::Bytecode,
-        eth_types::{ToWord, Word},
+        eth_types::{AccessListItem, ToWord, Word},
         evm::{stack::Stack, Gas, OpcodeId},
         mock,
         state_db::Account,
@@ -1059,6 +2195,35 @@ mod tracer_tests {
                 &mut self.step,
             )
         }
+
+        // Push a sub-call of `kind` executing at `address` with no value nor
+        // call data.
+        fn push_call(&mut self, kind: CallKind, address: Address) {
+            let mut state_ref = self.state_ref();
+            let caller_address = state_ref.call().address;
+            let call_id = state_ref.next_call_id();
+            state_ref
+                .push_call(Call {
+                    call_id,
+                    kind,
+                    is_static: false,
+                    is_root: false,
+                    caller_address,
+                    address,
+                    code_address: address,
+                    code_hash: H256::zero(),
+                    value: Word::zero(),
+                    call_data_offset: 0,
+                    call_data_length: 0,
+                    call_data: Vec::new(),
+                    return_data_offset: 0,
+                    return_data_length: 0,
+                    is_success: true,
+                    is_persistent: true,
+                    rw_counter_end_of_reversion: 0,
+                })
+                .unwrap();
+        }
     }
 
     lazy_static! {
@@ -1289,7 +2454,7 @@ mod tracer_tests {
 
         let mut builder = CircuitInputBuilderTx::new(&block, step);
         // Set up call context at CREATE2
-        builder.state_ref().push_call(CallKind::Create, *ADDR_B);
+        builder.push_call(CallKind::Create, *ADDR_B);
         // Set up account and contract that exist during the second CREATE2
         builder.builder.sdb.set_account(
             &ADDR_B,
@@ -1301,10 +2466,26 @@ mod tracer_tests {
                 codeHash: H256::zero(),
             },
         );
+        // A pre-funded account without nonce and code doesn't collide
         builder.builder.sdb.set_account(
             &create2_address,
             Account {
                 nonce: Word::zero(),
+                balance: Word::from(555u64),
+                storage: HashMap::new(),
+                codeHash: H256::zero(),
+            },
+        );
+        assert!(!matches!(
+            builder.state_ref().get_step_err(step, next_step),
+            Ok(Some(ExecError::ContractAddressCollision))
+        ));
+        // The contract created by the first CREATE2 has a nonce of 1
+        // (EIP-161)
+        builder.builder.sdb.set_account(
+            &create2_address,
+            Account {
+                nonce: Word::one(),
                 balance: Word::zero(),
                 storage: HashMap::new(),
                 codeHash: H256::zero(),
@@ -1396,7 +2577,7 @@ mod tracer_tests {
 
         let mut builder = CircuitInputBuilderTx::new(&block, step);
         // Set up call context at CREATE
-        builder.state_ref().push_call(CallKind::Create, *ADDR_B);
+        builder.push_call(CallKind::Create, *ADDR_B);
         assert_eq!(
             builder.state_ref().get_step_err(step, next_step).unwrap(),
             Some(ExecError::CodeStoreOutOfGas)
@@ -1485,7 +2666,7 @@ mod tracer_tests {
 
         let mut builder = CircuitInputBuilderTx::new(&block, step);
         // Set up call context at RETURN
-        builder.state_ref().push_call(CallKind::Create, *ADDR_B);
+        builder.push_call(CallKind::Create, *ADDR_B);
         assert_eq!(
             builder.state_ref().get_step_err(step, next_step).unwrap(),
             Some(ExecError::InvalidCode)
@@ -1572,7 +2753,7 @@ mod tracer_tests {
 
         let mut builder = CircuitInputBuilderTx::new(&block, step);
         // Set up call context at RETURN
-        builder.state_ref().push_call(CallKind::Create, *ADDR_B);
+        builder.push_call(CallKind::Create, *ADDR_B);
         assert_eq!(
             builder.state_ref().get_step_err(step, next_step).unwrap(),
             Some(ExecError::MaxCodeSizeExceeded)
@@ -1644,7 +2825,7 @@ mod tracer_tests {
 
         let mut builder = CircuitInputBuilderTx::new(&block, step);
         // Set up call context at STOP
-        builder.state_ref().push_call(CallKind::Create, *ADDR_B);
+        builder.push_call(CallKind::Create, *ADDR_B);
         assert_eq!(
             builder.state_ref().get_step_err(step, next_step).unwrap(),
             None
@@ -1882,6 +3063,8 @@ mod tracer_tests {
         assert!(check_err_return_data_out_of_bounds(step, next_step));
 
         let mut builder = CircuitInputBuilderTx::new(&block, step);
+        // The sub-call returned 1 byte
+        builder.tx_ctx.call_ctx_mut().return_data = vec![0x00];
         assert_eq!(
             builder.state_ref().get_step_err(step, next_step).unwrap(),
             Some(ExecError::ReturnDataOutOfBounds)
@@ -2049,6 +3232,471 @@ mod tracer_tests {
     // Circuit Input Builder tests
     //
 
+    #[test]
+    fn handle_tx_step_errors() {
+        // A stack underflow in the caller after a REVERT in the callee
+        let code_b = bytecode! {
+            PUSH1(0x0)
+            PUSH2(0x0)
+            REVERT
+        };
+        let code_a = bytecode! {
+            PUSH1(0x0) // retLength
+            PUSH1(0x0) // retOffset
+            PUSH1(0x0) // argsLength
+            PUSH1(0x0) // argsOffset
+            PUSH1(0x0) // value
+            PUSH32(*WORD_ADDR_B) // addr
+            PUSH32(0x1_0000) // gas
+            CALL
+            SWAP5
+        };
+        let block =
+            mock::BlockData::new_single_tx_trace_code_2(&code_a, &code_b)
+                .unwrap();
+
+        let mut builder = CircuitInputBuilder::new(
+            block.eth_block.clone(),
+            block.block_ctants.clone(),
+        );
+        builder.handle_tx(&block.eth_tx, &block.geth_trace).unwrap();
+
+        let errors: Vec<_> = builder.block.txs()[0]
+            .steps()
+            .iter()
+            .map(|step| (step.exec_state, step.error.as_ref()))
+            .filter(|(_, error)| error.is_some())
+            .collect();
+        assert_eq!(
+            errors,
+            vec![
+                (
+                    ExecState::Op(OpcodeId::REVERT),
+                    Some(&ExecError::ExecutionReverted)
+                ),
+                (
+                    ExecState::Op(OpcodeId::SWAP5),
+                    Some(&ExecError::StackUnderflow)
+                ),
+            ]
+        );
+
+        // An unknown error is reported instead of being classified
+        let mut block =
+            mock::BlockData::new_single_tx_trace_code(&code_b).unwrap();
+        block.geth_trace.struct_logs[2].error =
+            Some("unknown error".to_string());
+        let mut builder = CircuitInputBuilder::new(
+            block.eth_block.clone(),
+            block.block_ctants.clone(),
+        );
+        assert!(matches!(
+            builder.handle_tx(&block.eth_tx, &block.geth_trace),
+            Err(Error::UnexpectedExecStepError(..))
+        ));
+    }
+
+    #[test]
+    fn handle_tx_reversion() {
+        // The callee writes the storage and reverts
+        let code_b = bytecode! {
+            PUSH1(0x6f) // value
+            PUSH1(0x0) // key
+            SSTORE
+            PUSH1(0x0)
+            PUSH1(0x0)
+            REVERT
+        };
+        let code_a = bytecode! {
+            PUSH1(0x0) // retLength
+            PUSH1(0x0) // retOffset
+            PUSH1(0x0) // argsLength
+            PUSH1(0x0) // argsOffset
+            PUSH1(0x0) // value
+            PUSH32(*WORD_ADDR_B) // addr
+            PUSH32(0x1_0000) // gas
+            CALL
+            STOP
+        };
+        let block =
+            mock::BlockData::new_single_tx_trace_code_2(&code_a, &code_b)
+                .unwrap();
+
+        let mut builder = CircuitInputBuilder::new(
+            block.eth_block.clone(),
+            block.block_ctants.clone(),
+        );
+        builder.handle_tx(&block.eth_tx, &block.geth_trace).unwrap();
+
+        // The storage write is reverted, as well as the slot access and the
+        // refund update, in reverse order.
+        let container = &builder.block.container;
+        assert_eq!(
+            container
+                .storage
+                .iter()
+                .map(|op| (op.revert(), op.op().clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    false,
+                    StorageOp::new(
+                        RW::WRITE,
+                        *ADDR_B,
+                        Word::zero(),
+                        Word::from(0x6f),
+                        Word::zero(),
+                    )
+                ),
+                (
+                    true,
+                    StorageOp::new(
+                        RW::WRITE,
+                        *ADDR_B,
+                        Word::zero(),
+                        Word::zero(),
+                        Word::from(0x6f),
+                    )
+                ),
+            ]
+        );
+        let access_reversion = &container.tx_access_list_storage_slot[1];
+        assert!(access_reversion.revert());
+        assert_eq!(
+            access_reversion.op(),
+            &TxAccessListStorageSlotOp::new(
+                RW::WRITE,
+                1,
+                *ADDR_B,
+                Word::zero(),
+                false,
+                true,
+            )
+        );
+        assert!(container.tx_refund[1].revert());
+
+        // The state write at position `i` of the call is reverted at
+        // `rw_counter_end_of_reversion - i`, which is also read from the call
+        // context.
+        let call = &builder.block.txs()[0].calls()[1];
+        let rw_counter_end_of_reversion = call.rw_counter_end_of_reversion;
+        assert_eq!(access_reversion.gc().0, rw_counter_end_of_reversion);
+        assert_eq!(
+            container.storage[1].gc().0,
+            rw_counter_end_of_reversion - 1
+        );
+        assert_eq!(
+            container.tx_refund[1].gc().0,
+            rw_counter_end_of_reversion - 2
+        );
+        assert!(container.call_context.iter().any(|op| {
+            op.op()
+                == &CallContextOp::new(
+                    RW::READ,
+                    call.call_id,
+                    CallContextField::RwCounterEndOfReversion,
+                    Word::from(rw_counter_end_of_reversion),
+                )
+        }));
+
+        // The state is restored and only the callee steps are reverted
+        let (_, value) = builder.sdb.get_storage(&ADDR_B, &Word::zero());
+        assert_eq!(value, &Word::zero());
+        assert_eq!(builder.sdb.refund(), 0);
+        let reverted: Vec<_> = builder.block.txs()[0]
+            .steps()
+            .iter()
+            .map(|step| (step.exec_state, step.reverted))
+            .filter(|(exec_state, _)| {
+                matches!(
+                    exec_state,
+                    ExecState::Op(
+                        OpcodeId::SSTORE | OpcodeId::REVERT | OpcodeId::STOP
+                    ) | ExecState::BeginTx
+                        | ExecState::EndTx
+                )
+            })
+            .collect();
+        assert_eq!(
+            reverted,
+            vec![
+                (ExecState::BeginTx, false),
+                (ExecState::Op(OpcodeId::SSTORE), true),
+                (ExecState::Op(OpcodeId::REVERT), true),
+                (ExecState::Op(OpcodeId::STOP), false),
+                (ExecState::EndTx, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn handle_tx_begin_end_tx() {
+        let code = bytecode! {
+            PUSH1(0x0)
+            STOP
+        };
+        let mut block =
+            mock::BlockData::new_single_tx_trace_code(&code).unwrap();
+        // Pay a priority fee of 3 over the base fee of 97 to a coinbase
+        // different from the sender.
+        let coinbase = address!("0x00000000000000000000000000000000000c0ffe");
+        block.block_ctants = BlockConstants::from_eth_block(
+            &block.eth_block,
+            &Word::one(),
+            &coinbase,
+        );
+        block.eth_tx.gas_price = Some(Word::from(100));
+        let sender = block.eth_tx.from;
+        let gas = block.eth_tx.gas.as_u64();
+
+        let mut builder = CircuitInputBuilder::new(
+            block.eth_block.clone(),
+            block.block_ctants.clone(),
+        );
+        builder.sdb.get_account_mut(&sender).1.balance = Word::from(gas * 100);
+        builder.handle_tx(&block.eth_tx, &block.geth_trace).unwrap();
+
+        // The transaction steps are wrapped by the BeginTx and EndTx steps,
+        // and the first opcode step starts with the intrinsic gas charged.
+        let steps = builder.block.txs()[0].steps();
+        let exec_states: Vec<_> =
+            steps.iter().map(|step| step.exec_state).collect();
+        assert_eq!(
+            exec_states,
+            vec![
+                ExecState::BeginTx,
+                ExecState::Op(OpcodeId::PUSH1),
+                ExecState::Op(OpcodeId::STOP),
+                ExecState::EndTx,
+            ]
+        );
+        assert_eq!(steps[0].gas_left, Gas(gas));
+        assert_eq!(steps[0].gas_cost, GasCost::TX);
+        assert_eq!(steps[1].gas_left, Gas(gas - GasCost::TX.as_u64()));
+        let gas_used = GasCost::TX.as_u64() + GasCost::FASTEST.as_u64();
+        assert_eq!(steps[3].gas_left, Gas(gas - gas_used));
+
+        // The sender pays all the gas upfront and gets the gas left back,
+        // and the coinbase gets the priority fee of the gas used.
+        let account_ops: Vec<_> = builder
+            .block
+            .container
+            .account
+            .iter()
+            .map(|op| op.op().clone())
+            .filter(|op| op.rw() == RW::WRITE && *op.address() != *ADDR_A)
+            .collect();
+        assert_eq!(
+            account_ops,
+            vec![
+                AccountOp::new(
+                    RW::WRITE,
+                    sender,
+                    AccountField::Nonce,
+                    Word::one(),
+                    Word::zero(),
+                ),
+                AccountOp::new(
+                    RW::WRITE,
+                    sender,
+                    AccountField::Balance,
+                    Word::zero(),
+                    Word::from(gas * 100),
+                ),
+                AccountOp::new(
+                    RW::WRITE,
+                    sender,
+                    AccountField::Balance,
+                    Word::zero(),
+                    Word::zero(),
+                ),
+                AccountOp::new(
+                    RW::WRITE,
+                    sender,
+                    AccountField::Balance,
+                    Word::from((gas - gas_used) * 100),
+                    Word::zero(),
+                ),
+                AccountOp::new(
+                    RW::WRITE,
+                    coinbase,
+                    AccountField::Balance,
+                    Word::from(gas_used * 3),
+                    Word::zero(),
+                ),
+            ]
+        );
+        let (_, account) = builder.sdb.get_account(&sender);
+        assert_eq!(account.nonce, Word::one());
+        assert_eq!(account.balance, Word::from((gas - gas_used) * 100));
+        let (_, account) = builder.sdb.get_account(&coinbase);
+        assert_eq!(account.balance, Word::from(gas_used * 3));
+    }
+
+    #[test]
+    fn handle_tx_gas_cost_mismatch() {
+        let code = bytecode! {
+            PUSH1(0x0)
+            PUSH1(0x0)
+            MSTORE
+            STOP
+        };
+        let mut block =
+            mock::BlockData::new_single_tx_trace_code(&code).unwrap();
+        // The memory expansion of MSTORE is not charged
+        block.geth_trace.struct_logs[2].gas_cost = GasCost::FASTEST;
+
+        let mut builder = CircuitInputBuilder::new(
+            block.eth_block.clone(),
+            block.block_ctants.clone(),
+        );
+        match builder.handle_tx(&block.eth_tx, &block.geth_trace) {
+            Err(Error::GasCostMismatch(index, gas_cost, step)) => {
+                assert_eq!(index, 2);
+                assert_eq!(gas_cost, GasCost::from(6u64));
+                assert_eq!(step.op, OpcodeId::MSTORE);
+            }
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+
+    #[test]
+    fn begin_tx_access_list() {
+        let code = bytecode! {
+            STOP
+        };
+        let mut block =
+            mock::BlockData::new_single_tx_trace_code(&code).unwrap();
+        let sender = block.eth_tx.from;
+        let addr_b = address!("0x0000000000000000000000000000000000000123");
+        let key = H256::from_low_u64_be(0x42);
+        block.eth_tx.access_list = Some(AccessList(vec![
+            AccessListItem {
+                address: addr_b,
+                storage_keys: vec![key, key],
+            },
+            AccessListItem {
+                address: sender,
+                storage_keys: Vec::new(),
+            },
+        ]));
+
+        let mut builder = CircuitInputBuilder::new(
+            block.eth_block.clone(),
+            block.block_ctants.clone(),
+        );
+        let mut tx = Transaction::new(&block.eth_tx);
+        let mut tx_ctx = TransactionContext::new(&block.eth_tx);
+        builder
+            .begin_tx(&mut tx, &mut tx_ctx, &block.geth_trace)
+            .unwrap();
+
+        // The access list entries are charged in the intrinsic gas
+        assert_eq!(
+            tx.steps()[0].gas_cost,
+            GasCost::from(21000 + 2 * 2400 + 2 * 1900)
+        );
+
+        // The sender, the callee and the precompiles are warmed up before the
+        // access list, whose repeated entries are already warm.
+        let mut addresses = vec![(sender, false), (*ADDR_A, false)];
+        addresses.extend(
+            Precompile::ALL
+                .iter()
+                .map(|precompile| (precompile.address(), false)),
+        );
+        addresses.extend([(addr_b, false), (sender, true)]);
+        let access_list_account_ops: Vec<_> = addresses
+            .into_iter()
+            .map(|(address, is_warm)| {
+                TxAccessListAccountOp::new(RW::WRITE, 1, address, true, is_warm)
+            })
+            .collect();
+        assert_eq!(
+            builder
+                .block
+                .container
+                .tx_access_list_account
+                .iter()
+                .map(Operation::op)
+                .collect::<Vec<_>>(),
+            access_list_account_ops.iter().collect::<Vec<_>>()
+        );
+        let access_list_storage_slot_ops = [false, true].map(|is_warm| {
+            TxAccessListStorageSlotOp::new(
+                RW::WRITE,
+                1,
+                addr_b,
+                key.to_word(),
+                true,
+                is_warm,
+            )
+        });
+        assert_eq!(
+            builder
+                .block
+                .container
+                .tx_access_list_storage_slot
+                .iter()
+                .map(Operation::op)
+                .collect::<Vec<_>>(),
+            access_list_storage_slot_ops.iter().collect::<Vec<_>>()
+        );
+        assert!(builder.sdb.is_account_warm(&addr_b));
+        assert!(builder.sdb.is_account_storage_warm(&addr_b, &key.to_word()));
+
+        // The pre-warmed accounts are not reverted, so they don't increase
+        // the state write counter.
+        assert_eq!(tx_ctx.call_ctx().swc, 2);
+    }
+
+    #[test]
+    fn begin_tx_insufficient_balance() {
+        let code = bytecode! {
+            STOP
+        };
+        let mut block =
+            mock::BlockData::new_single_tx_trace_code(&code).unwrap();
+        block.eth_tx.gas_price = Some(Word::from(100));
+        let sender = block.eth_tx.from;
+        let gas = block.eth_tx.gas.as_u64();
+
+        // The sender can't pay all the gas upfront
+        let mut builder = CircuitInputBuilder::new(
+            block.eth_block.clone(),
+            block.block_ctants.clone(),
+        );
+        builder.sdb.get_account_mut(&sender).1.balance =
+            Word::from(gas * 100 - 1);
+        match builder.handle_tx(&block.eth_tx, &block.geth_trace) {
+            Err(Error::InsufficientBalance(address)) => {
+                assert_eq!(address, sender)
+            }
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+
+    #[test]
+    fn effective_gas_price() {
+        let block = mock::new_block();
+        let mut eth_tx = mock::new_tx(&block);
+        eth_tx.gas_price = Some(Word::from(100));
+        eth_tx.max_fee_per_gas = Some(Word::from(150));
+        eth_tx.max_priority_fee_per_gas = Some(Word::from(10));
+        let base_fee = Word::from(97);
+
+        // A legacy transaction pays its gas price
+        let tx = Transaction::new(&eth_tx);
+        assert_eq!(tx.effective_gas_price(base_fee), Word::from(100));
+
+        // An EIP-1559 transaction pays the base fee and the priority fee, up
+        // to its max fee.
+        eth_tx.transaction_type = Some(U64::from(2));
+        let tx = Transaction::new(&eth_tx);
+        assert_eq!(tx.effective_gas_price(base_fee), Word::from(107));
+        assert_eq!(tx.effective_gas_price(Word::from(145)), Word::from(150));
+    }
+
     #[test]
     fn create2_address() {
         // code_creator outputs 0x6050.
@@ -2124,7 +3772,7 @@ mod tracer_tests {
             .unwrap();
         let mut builder = CircuitInputBuilderTx::new(&block, step_create2);
         // Set up call context at CREATE2
-        builder.state_ref().push_call(CallKind::Create, *ADDR_B);
+        builder.push_call(CallKind::Create, *ADDR_B);
         let addr = builder.state_ref().create2_address(step_create2).unwrap();
 
         assert_eq!(addr.to_word(), addr_expect);
@@ -2212,7 +3860,7 @@ mod tracer_tests {
             .unwrap();
         let mut builder = CircuitInputBuilderTx::new(&block, step_create);
         // Set up call context at CREATE
-        builder.state_ref().push_call(CallKind::Create, *ADDR_B);
+        builder.push_call(CallKind::Create, *ADDR_B);
         builder.builder.sdb.set_account(
             &ADDR_B,
             Account {
@@ -2336,4 +3984,25 @@ mod tracer_tests {
             }
         )
     }
+
+    #[test]
+    fn memory_range() {
+        assert_eq!(
+            get_memory_range(Word::from(0x20), Word::from(0x40)).unwrap(),
+            0x20..0x60
+        );
+        // The offset is ignored when the length is zero
+        assert_eq!(
+            get_memory_range(Word::one() << 255, Word::zero()).unwrap(),
+            0..0
+        );
+        assert!(matches!(
+            get_memory_range(Word::one() << 255, Word::one()),
+            Err(Error::WordToMemAddr)
+        ));
+        assert!(matches!(
+            get_memory_range(Word::from(u64::MAX), Word::from(2)),
+            Err(Error::WordToMemAddr)
+        ));
+    }
 }
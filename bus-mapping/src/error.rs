@@ -1,6 +1,7 @@
 //! Error module for the bus-mapping crate
 
 use crate::eth_types::{Address, GethExecStep, Word};
+use crate::evm::{GasCost, OpcodeId};
 use core::fmt::{Display, Formatter, Result as FmtResult};
 use ethers_providers::ProviderError;
 use std::error::Error as StdError;
@@ -37,10 +38,23 @@ pub enum Error {
     AccountNotFound(Address),
     /// Storage key not found in the StateDB
     StorageKeyNotFound(Address, Word),
+    /// Refund counter of the transaction would go below zero
+    RefundCounterUnderflow,
+    /// Balance of the account would go below zero
+    InsufficientBalance(Address),
+    /// Contract address of a create transaction is already in use
+    ContractAddressCollision(Address),
+    /// Reversion group not found for a call that is not persistent
+    ReversionGroupNotFound,
     /// Unable to figure out error at a [`GethExecStep`]
     UnexpectedExecStepError(&'static str, Box<GethExecStep>),
     /// Invalid [`GethExecStep`] due to an invalid/unexpected value in it.
     InvalidGethExecStep(&'static str, Box<GethExecStep>),
+    /// The gas cost computed for the [`GethExecStep`] at an index of the
+    /// trace doesn't match the traced one.
+    GasCostMismatch(usize, GasCost, Box<GethExecStep>),
+    /// The associated operations of the [`OpcodeId`] can't be generated.
+    UnsupportedOpcode(OpcodeId),
 }
 
 impl From<ProviderError> for Error {
@@ -4,8 +4,11 @@ use crate::evm::{memory::Memory, stack::Stack, storage::Storage};
 use crate::evm::{Gas, GasCost, OpcodeId, ProgramCounter};
 use ethers_core::types;
 pub use ethers_core::types::{
-    transaction::response::Transaction, Address, Block, Bytes, H160, H256,
-    U256, U64,
+    transaction::{
+        eip2930::{AccessList, AccessListItem},
+        response::Transaction,
+    },
+    Address, Block, Bytes, H160, H256, U256, U64,
 };
 use pairing::arithmetic::FieldExt;
 use serde::{de, Deserialize};
@@ -134,6 +137,12 @@ impl ToWord for Address {
     }
 }
 
+impl ToWord for H256 {
+    fn to_word(&self) -> Word {
+        Word::from_big_endian(self.as_bytes())
+    }
+}
+
 impl<F: FieldExt> ToScalar<F> for Address {
     fn to_scalar(&self) -> Option<F> {
         let mut bytes = [0u8; 32];
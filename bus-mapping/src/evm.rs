@@ -1,7 +1,9 @@
 //! Evm types needed for parsing instruction sets as well
 
+pub mod gas;
 pub mod memory;
 pub(crate) mod opcodes;
+pub mod precompiles;
 pub mod stack;
 pub mod storage;
 
@@ -9,6 +11,7 @@ use serde::{Deserialize, Serialize};
 pub use {
     memory::{Memory, MemoryAddress},
     opcodes::{ids::OpcodeId, Opcode},
+    precompiles::Precompile,
     stack::{Stack, StackAddress},
     storage::Storage,
 };
@@ -123,21 +126,105 @@ impl GasCost {
     pub const EXT: Self = Self(20);
     /// Constant cost for SHA3
     pub const SHA3: Self = Self(30);
+    /// Constant cost for every word hashed by SHA3 or CREATE2
+    pub const SHA3_WORD: Self = Self(6);
+    /// Constant cost for every byte of the exponent of EXP
+    pub const EXP_BYTE: Self = Self(50);
+    /// Constant cost for LOG[0-4]
+    pub const LOG: Self = Self(375);
+    /// Constant cost for every topic of LOG[0-4]
+    pub const LOG_TOPIC: Self = Self(375);
+    /// Constant cost for every byte of data of LOG[0-4]
+    pub const LOG_DATA_BYTE: Self = Self(8);
     /// Constant cost for SELFDESTRUCT
     pub const SELFDESTRUCT: Self = Self(5000);
     /// Constant cost for CREATE
     pub const CREATE: Self = Self(32000);
     /// Constant cost for every additional word when expanding memory
     pub const MEMORY: Self = Self(3);
+    /// Constant cost for every word copied into memory
+    pub const COPY: Self = Self(3);
     /// Constant cost for a cold SLOAD
     pub const COLD_SLOAD_COST: Self = Self(2100);
     /// Constant cost for a cold account access
     pub const COLD_ACCOUNT_ACCESS_COST: Self = Self(2600);
     /// Constant cost for a warm storage read
     pub const WARM_STORAGE_READ_COST: Self = Self(100);
+    /// Constant cost for a SSTORE that sets a slot from zero to non-zero
+    pub const SSTORE_SET: Self = Self(20000);
+    /// Constant cost for a SSTORE that changes a non-zero slot
+    pub const SSTORE_RESET: Self = Self(2900);
+    /// Constant refund for a SSTORE that clears a slot (EIP-3529)
+    pub const SSTORE_CLEARS_SCHEDULE: Self = Self(4800);
+    /// Constant cost for a *CALL* that transfers value
+    pub const CALL_VALUE: Self = Self(9000);
+    /// Constant gas given to the callee of a *CALL* that transfers value
+    pub const CALL_STIPEND: Self = Self(2300);
+    /// Constant cost for a *CALL* or SELFDESTRUCT that sends value to an
+    /// empty account
+    pub const NEW_ACCOUNT: Self = Self(25000);
+    /// Constant base cost of a transaction
+    pub const TX: Self = Self(21000);
+    /// Constant base cost of a create transaction
+    pub const CREATION_TX: Self = Self(53000);
+    /// Constant cost for every zero byte of the transaction input
+    pub const TX_DATA_ZERO: Self = Self(4);
+    /// Constant cost for every non-zero byte of the transaction input
+    pub const TX_DATA_NON_ZERO: Self = Self(16);
+    /// Constant cost for every address in the transaction access list
+    pub const TX_ACCESS_LIST_ADDRESS: Self = Self(2400);
+    /// Constant cost for every storage key in the transaction access list
+    pub const TX_ACCESS_LIST_STORAGE_KEY: Self = Self(1900);
 }
 
+/// Maximum quotient of the gas used by a transaction that can be refunded
+/// (EIP-3529).
+pub const MAX_REFUND_QUOTIENT_OF_GAS_USED: u64 = 5;
+
 impl GasCost {
+    /// Returns the cost of an access to an account, which is cheaper when it
+    /// is warm in the access list of the transaction (EIP-2929).
+    pub const fn account_access(is_warm: bool) -> Self {
+        if is_warm {
+            Self::WARM_STORAGE_READ_COST
+        } else {
+            Self::COLD_ACCOUNT_ACCESS_COST
+        }
+    }
+
+    /// Returns the cost of an access to a storage slot, which is cheaper when
+    /// it is warm in the access list of the transaction (EIP-2929).
+    pub const fn storage_access(is_warm: bool) -> Self {
+        if is_warm {
+            Self::WARM_STORAGE_READ_COST
+        } else {
+            Self::COLD_SLOAD_COST
+        }
+    }
+
+    /// Returns the cost of expanding the memory from `memory_size` to
+    /// `new_memory_size` bytes, which are rounded up to words.  The cost is
+    /// zero when the memory is not expanded.
+    pub const fn memory_expansion(
+        memory_size: u64,
+        new_memory_size: u64,
+    ) -> Self {
+        const fn memory_cost(memory_size: u64) -> u64 {
+            let words = (memory_size + 31) / 32;
+            GasCost::MEMORY.0 * words + words * words / 512
+        }
+        Self(
+            memory_cost(new_memory_size)
+                .saturating_sub(memory_cost(memory_size)),
+        )
+    }
+
+    /// Returns the cost of copying `length` bytes into memory, which is
+    /// charged for every word.
+    pub const fn copy(length: u64) -> Self {
+        Self(Self::COPY.0 * ((length + 31) / 32))
+    }
+
     /// Returns the `GasCost` as a `u64`.
     #[inline]
     pub const fn as_u64(&self) -> u64 {
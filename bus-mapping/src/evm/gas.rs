@@ -0,0 +1,381 @@
+//! Gas costs of the execution steps, which are computed from the state of the
+//! builder independently of the ones traced by geth.
+use super::{GasCost, OpcodeId};
+use crate::circuit_input_builder::CircuitInputStateRef;
+use crate::eth_types::{Address, GethExecStep, ToAddress, Word};
+use crate::Error;
+
+/// Return the gas cost of the successful `step`, computed from the state in
+/// `state` before the step is executed.  This is the constant gas cost of its
+/// opcode plus its dynamic gas cost, which covers the memory expansion, the
+/// copied and hashed words, the logged data, the exponent bytes, the storage
+/// changes, the account accesses (EIP-2929) and the gas sent to a callee.
+pub fn step_gas_cost(
+    state: &CircuitInputStateRef,
+    step: &GethExecStep,
+) -> Result<GasCost, Error> {
+    let stack = &step.stack;
+    let dynamic_gas_cost = match step.op {
+        OpcodeId::MLOAD | OpcodeId::MSTORE => {
+            memory_expansion_cost(step, memory_end(stack.last()?, 32))
+        }
+        OpcodeId::MSTORE8 => {
+            memory_expansion_cost(step, memory_end(stack.last()?, 1))
+        }
+        OpcodeId::CALLDATACOPY
+        | OpcodeId::CODECOPY
+        | OpcodeId::RETURNDATACOPY => {
+            let length = stack.nth_last(2)?.low_u64();
+            memory_expansion_cost(step, memory_end(stack.nth_last(0)?, length))
+                + GasCost::copy(length).as_u64()
+        }
+        OpcodeId::EXTCODECOPY => {
+            let length = stack.nth_last(3)?.low_u64();
+            cold_account_access_cost(state, stack.nth_last(0)?.to_address())
+                + memory_expansion_cost(
+                    step,
+                    memory_end(stack.nth_last(1)?, length),
+                )
+                + GasCost::copy(length).as_u64()
+        }
+        OpcodeId::SHA3 => {
+            let length = stack.nth_last(1)?.low_u64();
+            memory_expansion_cost(step, memory_end(stack.nth_last(0)?, length))
+                + GasCost::SHA3_WORD.as_u64() * words(length)
+        }
+        OpcodeId::LOG0
+        | OpcodeId::LOG1
+        | OpcodeId::LOG2
+        | OpcodeId::LOG3
+        | OpcodeId::LOG4 => {
+            let n_topics = step.op.as_u64() - OpcodeId::LOG0.as_u64();
+            let length = stack.nth_last(1)?.low_u64();
+            memory_expansion_cost(step, memory_end(stack.nth_last(0)?, length))
+                + GasCost::LOG.as_u64()
+                + GasCost::LOG_TOPIC.as_u64() * n_topics
+                + GasCost::LOG_DATA_BYTE.as_u64() * length
+        }
+        // The constant gas cost of EXP is charged here with the cost of each
+        // byte of the exponent.
+        OpcodeId::EXP => {
+            let exponent_bytes = (stack.nth_last(1)?.bits() as u64 + 7) / 8;
+            let exponent_cost = GasCost::EXP_BYTE.as_u64() * exponent_bytes;
+            GasCost::SLOW.as_u64() + exponent_cost
+        }
+        OpcodeId::RETURN | OpcodeId::REVERT => {
+            let length = stack.nth_last(1)?.low_u64();
+            memory_expansion_cost(step, memory_end(stack.nth_last(0)?, length))
+        }
+        OpcodeId::CREATE => {
+            let length = stack.nth_last(2)?.low_u64();
+            memory_expansion_cost(step, memory_end(stack.nth_last(1)?, length))
+        }
+        // CREATE2 also hashes the init code to derive the created address
+        OpcodeId::CREATE2 => {
+            let length = stack.nth_last(2)?.low_u64();
+            memory_expansion_cost(step, memory_end(stack.nth_last(1)?, length))
+                + GasCost::SHA3_WORD.as_u64() * words(length)
+        }
+        OpcodeId::SLOAD => {
+            let address = state.call().address;
+            let is_warm =
+                state.sdb.is_account_storage_warm(&address, &stack.last()?);
+            GasCost::storage_access(is_warm).as_u64()
+        }
+        OpcodeId::SSTORE => {
+            let address = state.call().address;
+            let key = stack.nth_last(0)?;
+            let (_, committed_value) =
+                state.sdb.get_committed_storage(&address, &key);
+            let (_, current_value) = state.sdb.get_storage(&address, &key);
+            let (gas_cost, _) = sstore_gas_cost_and_refund(
+                *committed_value,
+                *current_value,
+                stack.nth_last(1)?,
+            );
+            let is_warm = state.sdb.is_account_storage_warm(&address, &key);
+            let cold_cost = if is_warm {
+                0
+            } else {
+                GasCost::COLD_SLOAD_COST.as_u64()
+            };
+            gas_cost.as_u64() + cold_cost
+        }
+        OpcodeId::BALANCE | OpcodeId::EXTCODESIZE | OpcodeId::EXTCODEHASH => {
+            cold_account_access_cost(state, stack.last()?.to_address())
+        }
+        OpcodeId::CALL
+        | OpcodeId::CALLCODE
+        | OpcodeId::DELEGATECALL
+        | OpcodeId::STATICCALL => {
+            let (gas_cost, _) = call_gas_cost(state, step)?;
+            return Ok(gas_cost);
+        }
+        // SELFDESTRUCT charges the whole cold access cost of the beneficiary,
+        // and the creation of an empty beneficiary that receives the balance.
+        OpcodeId::SELFDESTRUCT => {
+            let beneficiary = stack.last()?.to_address();
+            let mut gas_cost = 0;
+            if !state.sdb.is_account_warm(&beneficiary) {
+                gas_cost += GasCost::COLD_ACCOUNT_ACCESS_COST.as_u64();
+            }
+            let (_, account) = state.sdb.get_account(&state.call().address);
+            let (_, beneficiary_account) = state.sdb.get_account(&beneficiary);
+            if !account.balance.is_zero() && beneficiary_account.is_empty() {
+                gas_cost += GasCost::NEW_ACCOUNT.as_u64();
+            }
+            gas_cost
+        }
+        _ => 0,
+    };
+    Ok(GasCost::from(
+        step.op.constant_gas_cost().as_u64() + dynamic_gas_cost,
+    ))
+}
+
+/// Return the gas cost of the *CALL* at `step`, computed from the state in
+/// `state` before it's executed, and the gas sent to the callee, without the
+/// stipend of a value transfer.  The gas cost includes the callee access
+/// (EIP-2929), the memory expansion of the call data and the return data, the
+/// value transfer to a maybe empty callee, and the gas sent to the callee,
+/// which is at most all but one 64th of the gas left (EIP-150).
+pub fn call_gas_cost(
+    state: &CircuitInputStateRef,
+    step: &GethExecStep,
+) -> Result<(GasCost, u64), Error> {
+    let stack = &step.stack;
+    let has_value = matches!(step.op, OpcodeId::CALL | OpcodeId::CALLCODE);
+    let args_pos = if has_value { 3 } else { 2 };
+    let memory_size = memory_end(
+        stack.nth_last(args_pos)?,
+        stack.nth_last(args_pos + 1)?.low_u64(),
+    )
+    .max(memory_end(
+        stack.nth_last(args_pos + 2)?,
+        stack.nth_last(args_pos + 3)?.low_u64(),
+    ));
+
+    let code_address = stack.nth_last(1)?.to_address();
+    let mut gas_cost = step.op.constant_gas_cost().as_u64()
+        + cold_account_access_cost(state, code_address)
+        + memory_expansion_cost(step, memory_size);
+    if has_value && !stack.nth_last(2)?.is_zero() {
+        gas_cost += GasCost::CALL_VALUE.as_u64();
+        let (_, callee) = state.sdb.get_account(&code_address);
+        if step.op == OpcodeId::CALL && callee.is_empty() {
+            gas_cost += GasCost::NEW_ACCOUNT.as_u64();
+        }
+    }
+
+    let gas_left = step.gas.0.saturating_sub(gas_cost);
+    let available_gas = gas_left - gas_left / 64;
+    let requested_gas = stack.nth_last(0)?;
+    let callee_gas = if requested_gas < Word::from(available_gas) {
+        requested_gas.as_u64()
+    } else {
+        available_gas
+    };
+    Ok((GasCost::from(gas_cost + callee_gas), callee_gas))
+}
+
+/// Return the gas cost, without the EIP-2929 cold slot surcharge, and the
+/// refund counter change of a SSTORE of `value` into a slot with the
+/// `committed_value` at the beginning of the transaction and the
+/// `current_value`.  This follows EIP-2200 with the costs from EIP-2929 and
+/// the refunds from EIP-3529.
+pub fn sstore_gas_cost_and_refund(
+    committed_value: Word,
+    current_value: Word,
+    value: Word,
+) -> (GasCost, i64) {
+    let warm = GasCost::WARM_STORAGE_READ_COST.as_u64() as i64;
+    let set = GasCost::SSTORE_SET.as_u64() as i64;
+    let reset = GasCost::SSTORE_RESET.as_u64() as i64;
+    let clears = GasCost::SSTORE_CLEARS_SCHEDULE.as_u64() as i64;
+
+    // No-op
+    if current_value == value {
+        return (GasCost::WARM_STORAGE_READ_COST, 0);
+    }
+
+    // Clean slot
+    if committed_value == current_value {
+        if committed_value.is_zero() {
+            return (GasCost::SSTORE_SET, 0);
+        }
+        let refund = if value.is_zero() { clears } else { 0 };
+        return (GasCost::SSTORE_RESET, refund);
+    }
+
+    // Dirty slot
+    let mut refund = 0;
+    if !committed_value.is_zero() {
+        if current_value.is_zero() {
+            refund -= clears;
+        } else if value.is_zero() {
+            refund += clears;
+        }
+    }
+    if committed_value == value {
+        refund += if committed_value.is_zero() {
+            set - warm
+        } else {
+            reset - warm
+        };
+    }
+    (GasCost::WARM_STORAGE_READ_COST, refund)
+}
+
+/// Return the cold account access surcharge (EIP-2929) of the account at
+/// `address`, which is charged over the warm access cost already included in
+/// the constant gas cost of the opcode.
+fn cold_account_access_cost(
+    state: &CircuitInputStateRef,
+    address: Address,
+) -> u64 {
+    if state.sdb.is_account_warm(&address) {
+        0
+    } else {
+        GasCost::COLD_ACCOUNT_ACCESS_COST.as_u64()
+            - GasCost::WARM_STORAGE_READ_COST.as_u64()
+    }
+}
+
+/// Return the cost of expanding the memory before `step` to `memory_size`
+/// bytes.
+fn memory_expansion_cost(step: &GethExecStep, memory_size: u64) -> u64 {
+    GasCost::memory_expansion(step.memory.0.len() as u64, memory_size).as_u64()
+}
+
+/// Return the memory size in bytes needed to access `length` bytes from
+/// `offset`, which is zero when nothing is accessed.
+fn memory_end(offset: Word, length: u64) -> u64 {
+    if length == 0 {
+        0
+    } else {
+        offset.low_u64().saturating_add(length)
+    }
+}
+
+/// Return the number of words of `length` bytes.
+fn words(length: u64) -> u64 {
+    (length + 31) / 32
+}
+
+#[cfg(test)]
+mod gas_tests {
+    use super::*;
+    use crate::{
+        bytecode,
+        circuit_input_builder::{CircuitInputBuilder, ExecState},
+        mock,
+    };
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn memory_expansion_gas_cost() {
+        assert_eq!(GasCost::memory_expansion(0, 0), GasCost::ZERO);
+        assert_eq!(GasCost::memory_expansion(0, 1), GasCost::from(3));
+        assert_eq!(GasCost::memory_expansion(0, 32), GasCost::from(3));
+        assert_eq!(GasCost::memory_expansion(32, 64), GasCost::from(3));
+        // The quadratic cost starts at 23 words
+        assert_eq!(GasCost::memory_expansion(0, 23 * 32), GasCost::from(70));
+        assert_eq!(
+            GasCost::memory_expansion(0, 1024 * 32),
+            GasCost::from(5120)
+        );
+        // The memory is never shrunk
+        assert_eq!(GasCost::memory_expansion(64, 32), GasCost::ZERO);
+    }
+
+    #[test]
+    fn step_gas_cost_dynamic() {
+        let code = bytecode! {
+            // EXP with a 2 bytes exponent
+            PUSH2(0x0100u64)
+            PUSH1(0x02u64)
+            EXP
+            // SHA3 of 33 bytes, which expands the memory to 2 words
+            PUSH1(0x21u64)
+            PUSH1(0x00u64)
+            SHA3
+            // LOG2 of 3 bytes in the expanded memory
+            PUSH1(0x00u64)
+            PUSH1(0x00u64)
+            PUSH1(0x03u64)
+            PUSH1(0x00u64)
+            LOG2
+            // CALLDATACOPY of 40 bytes, which expands the memory to 3 words
+            PUSH1(0x28u64)
+            PUSH1(0x00u64)
+            PUSH1(0x20u64)
+            CALLDATACOPY
+            STOP
+        };
+
+        // Get the execution steps from the external tracer
+        let block = mock::BlockData::new_single_tx_trace_code(&code).unwrap();
+
+        let mut builder = CircuitInputBuilder::new(
+            block.eth_block.clone(),
+            block.block_ctants.clone(),
+        );
+        builder.handle_tx(&block.eth_tx, &block.geth_trace).unwrap();
+
+        // The gas costs are validated by the builder, and they match the
+        // traced ones.
+        let gas_costs: Vec<_> = builder.block.txs()[0]
+            .steps()
+            .iter()
+            .filter(|step| {
+                [
+                    OpcodeId::EXP,
+                    OpcodeId::SHA3,
+                    OpcodeId::LOG2,
+                    OpcodeId::CALLDATACOPY,
+                ]
+                .map(ExecState::Op)
+                .contains(&step.exec_state)
+            })
+            .map(|step| step.gas_cost.as_u64())
+            .collect();
+        assert_eq!(
+            gas_costs,
+            vec![
+                10 + 50 * 2,
+                30 + 3 * 2 + 6 * 2,
+                375 + 375 * 2 + 8 * 3,
+                3 + 3 + 3 * 2,
+            ]
+        );
+    }
+
+    #[test]
+    fn sstore_gas_cost_and_refund_eip2200() {
+        let (zero, one, two) = (Word::zero(), Word::one(), Word::from(2));
+        let cases = [
+            // (committed, current, new, gas cost, refund)
+            (zero, zero, zero, 100, 0),
+            (zero, zero, one, 20000, 0),
+            (one, one, zero, 2900, 4800),
+            (one, one, two, 2900, 0),
+            (one, one, one, 100, 0),
+            (zero, one, zero, 100, 19900),
+            (zero, one, two, 100, 0),
+            (one, zero, one, 100, -4800 + 2800),
+            (one, zero, two, 100, -4800),
+            (one, two, zero, 100, 4800),
+            (one, two, one, 100, 2800),
+        ];
+        for (committed, current, new, gas_cost, refund) in cases {
+            assert_eq!(
+                sstore_gas_cost_and_refund(committed, current, new),
+                (GasCost::from(gas_cost as u64), refund),
+                "committed: {}, current: {}, new: {}",
+                committed,
+                current,
+                new
+            );
+        }
+    }
+}
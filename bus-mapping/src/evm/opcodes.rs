@@ -1,13 +1,29 @@
 //! Definition of each opcode of the EVM.
+mod balance;
+mod blockhash;
+mod call;
+mod calldata;
+mod code;
+mod create;
 mod dup;
+mod environment;
+mod gas;
 pub mod ids;
 mod jumpdest;
+mod log;
 mod mload;
+mod msize;
 mod mstore;
 mod pc;
 mod push;
+mod return_revert;
+mod returndata;
+mod selfdestruct;
+mod sha3;
 mod sload;
+mod sstore;
 mod stackonlyop;
+mod stackpoponlyop;
 mod stop;
 mod swap;
 use crate::circuit_input_builder::CircuitInputStateRef;
@@ -17,13 +33,29 @@ use core::fmt::Debug;
 use ids::OpcodeId;
 
 use self::push::Push;
+use balance::Balance;
+use blockhash::Blockhash;
+use call::Call;
+use calldata::{Calldatacopy, Calldataload, Calldatasize};
+use code::{Codecopy, Codesize, Extcodecopy, Extcodehash, Extcodesize};
+use create::Create;
 use dup::Dup;
+use environment::Environment;
+use gas::Gas;
 use jumpdest::Jumpdest;
+use log::Log;
 use mload::Mload;
+use msize::Msize;
 use mstore::Mstore;
 use pc::Pc;
+use return_revert::ReturnRevert;
+use returndata::{Returndatacopy, Returndatasize};
+use selfdestruct::Selfdestruct;
+use sha3::Sha3;
 use sload::Sload;
+use sstore::Sstore;
 use stackonlyop::StackOnlyOpcode;
+use stackpoponlyop::StackPopOnlyOpcode;
 use stop::Stop;
 use swap::Swap;
 
@@ -35,7 +67,9 @@ pub trait Opcode: Debug {
     /// Generate the associated [`MemoryOp`](crate::operation::MemoryOp)s,
     /// [`StackOp`](crate::operation::StackOp)s, and
     /// [`StorageOp`](crate::operation::StorageOp)s associated to the Opcode
-    /// is implemented for.
+    /// is implemented for.  When the step fails with the error found by the
+    /// builder in the current step of `state`, only the operations done
+    /// before the failure are generated.
     fn gen_associated_ops(
         state: &mut CircuitInputStateRef,
         next_steps: &[GethExecStep],
@@ -76,43 +110,43 @@ impl OpcodeId {
             OpcodeId::SHL => StackOnlyOpcode::<2>::gen_associated_ops,
             OpcodeId::SHR => StackOnlyOpcode::<2>::gen_associated_ops,
             OpcodeId::SAR => StackOnlyOpcode::<2>::gen_associated_ops,
-            // OpcodeId::SHA3 => {},
-            // OpcodeId::ADDRESS => {},
-            // OpcodeId::BALANCE => {},
-            // OpcodeId::ORIGIN => {},
-            // OpcodeId::CALLER => {},
-            // OpcodeId::CALLVALUE => {},
-            // OpcodeId::CALLDATALOAD => {},
-            // OpcodeId::CALLDATASIZE => {},
-            // OpcodeId::CALLDATACOPY => {},
-            // OpcodeId::CODESIZE => {},
-            // OpcodeId::CODECOPY => {},
-            // OpcodeId::GASPRICE => {},
-            // OpcodeId::EXTCODESIZE => {},
-            // OpcodeId::EXTCODECOPY => {},
-            // OpcodeId::RETURNDATASIZE => {},
-            // OpcodeId::RETURNDATACOPY => {},
-            // OpcodeId::EXTCODEHASH => {},
-            // OpcodeId::BLOCKHASH => {},
-            // OpcodeId::COINBASE => {},
-            // OpcodeId::TIMESTAMP => {},
-            // OpcodeId::NUMBER => {},
-            // OpcodeId::DIFFICULTY => {},
-            // OpcodeId::GASLIMIT => {},
-            // OpcodeId::CHAINID => {},
-            // OpcodeId::SELFBALANCE => {},
-            // OpcodeId::BASEFEE => {},
-            // OpcodeId::POP => {},
+            OpcodeId::SHA3 => Sha3::gen_associated_ops,
+            OpcodeId::ADDRESS => Environment::gen_associated_ops,
+            OpcodeId::BALANCE => Balance::gen_associated_ops,
+            OpcodeId::ORIGIN => Environment::gen_associated_ops,
+            OpcodeId::CALLER => Environment::gen_associated_ops,
+            OpcodeId::CALLVALUE => Environment::gen_associated_ops,
+            OpcodeId::CALLDATALOAD => Calldataload::gen_associated_ops,
+            OpcodeId::CALLDATASIZE => Calldatasize::gen_associated_ops,
+            OpcodeId::CALLDATACOPY => Calldatacopy::gen_associated_ops,
+            OpcodeId::CODESIZE => Codesize::gen_associated_ops,
+            OpcodeId::CODECOPY => Codecopy::gen_associated_ops,
+            OpcodeId::GASPRICE => Environment::gen_associated_ops,
+            OpcodeId::EXTCODESIZE => Extcodesize::gen_associated_ops,
+            OpcodeId::EXTCODECOPY => Extcodecopy::gen_associated_ops,
+            OpcodeId::RETURNDATASIZE => Returndatasize::gen_associated_ops,
+            OpcodeId::RETURNDATACOPY => Returndatacopy::gen_associated_ops,
+            OpcodeId::EXTCODEHASH => Extcodehash::gen_associated_ops,
+            OpcodeId::BLOCKHASH => Blockhash::gen_associated_ops,
+            OpcodeId::COINBASE => Environment::gen_associated_ops,
+            OpcodeId::TIMESTAMP => Environment::gen_associated_ops,
+            OpcodeId::NUMBER => Environment::gen_associated_ops,
+            OpcodeId::DIFFICULTY => Environment::gen_associated_ops,
+            OpcodeId::GASLIMIT => Environment::gen_associated_ops,
+            OpcodeId::CHAINID => Environment::gen_associated_ops,
+            OpcodeId::SELFBALANCE => Environment::gen_associated_ops,
+            OpcodeId::BASEFEE => Environment::gen_associated_ops,
+            OpcodeId::POP => StackPopOnlyOpcode::<1>::gen_associated_ops,
             OpcodeId::MLOAD => Mload::gen_associated_ops,
-            OpcodeId::MSTORE => Mstore::gen_associated_ops,
-            // OpcodeId::MSTORE8 => {}
+            OpcodeId::MSTORE => Mstore::<false>::gen_associated_ops,
+            OpcodeId::MSTORE8 => Mstore::<true>::gen_associated_ops,
             OpcodeId::SLOAD => Sload::gen_associated_ops,
-            // OpcodeId::SSTORE => {},
-            // OpcodeId::JUMP => {},
-            // OpcodeId::JUMPI => {},
+            OpcodeId::SSTORE => Sstore::gen_associated_ops,
+            OpcodeId::JUMP => StackPopOnlyOpcode::<1>::gen_associated_ops,
+            OpcodeId::JUMPI => StackPopOnlyOpcode::<2>::gen_associated_ops,
             OpcodeId::PC => Pc::gen_associated_ops,
-            // OpcodeId::MSIZE => {},
-            // OpcodeId::GAS => {},
+            OpcodeId::MSIZE => Msize::gen_associated_ops,
+            OpcodeId::GAS => Gas::gen_associated_ops,
             OpcodeId::JUMPDEST => Jumpdest::gen_associated_ops,
             OpcodeId::PUSH1 => Push::<1>::gen_associated_ops,
             OpcodeId::PUSH2 => Push::<2>::gen_associated_ops,
@@ -178,21 +212,21 @@ impl OpcodeId {
             OpcodeId::SWAP14 => Swap::<14>::gen_associated_ops,
             OpcodeId::SWAP15 => Swap::<15>::gen_associated_ops,
             OpcodeId::SWAP16 => Swap::<16>::gen_associated_ops,
-            // OpcodeId::LOG0 => {},
-            // OpcodeId::LOG1 => {},
-            // OpcodeId::LOG2 => {},
-            // OpcodeId::LOG3 => {},
-            // OpcodeId::LOG4 => {},
-            // OpcodeId::CREATE => {},
-            // OpcodeId::CALL => {},
-            // OpcodeId::CALLCODE => {},
-            // OpcodeId::RETURN => {},
-            // OpcodeId::DELEGATECALL => {},
-            // OpcodeId::CREATE2 => {},
-            // OpcodeId::STATICCALL => {},
-            // OpcodeId::REVERT => {},
-            // OpcodeId::SELFDESTRUCT => {},
-            _ => unimplemented!(),
+            OpcodeId::LOG0 => Log::<0>::gen_associated_ops,
+            OpcodeId::LOG1 => Log::<1>::gen_associated_ops,
+            OpcodeId::LOG2 => Log::<2>::gen_associated_ops,
+            OpcodeId::LOG3 => Log::<3>::gen_associated_ops,
+            OpcodeId::LOG4 => Log::<4>::gen_associated_ops,
+            OpcodeId::CREATE => Create::gen_associated_ops,
+            OpcodeId::CALL => Call::gen_associated_ops,
+            OpcodeId::CALLCODE => Call::gen_associated_ops,
+            OpcodeId::RETURN => ReturnRevert::gen_associated_ops,
+            OpcodeId::DELEGATECALL => Call::gen_associated_ops,
+            OpcodeId::CREATE2 => Create::gen_associated_ops,
+            OpcodeId::STATICCALL => Call::gen_associated_ops,
+            OpcodeId::REVERT => ReturnRevert::gen_associated_ops,
+            OpcodeId::SELFDESTRUCT => Selfdestruct::gen_associated_ops,
+            _ => unsupported_opcode,
         }
     }
 
@@ -207,3 +241,12 @@ impl OpcodeId {
         fn_gen_associated_ops(state, next_steps)
     }
 }
+
+/// Return an [`Error::UnsupportedOpcode`] for the opcodes whose associated
+/// operations can't be generated yet.
+fn unsupported_opcode(
+    _state: &mut CircuitInputStateRef,
+    next_steps: &[GethExecStep],
+) -> Result<(), Error> {
+    Err(Error::UnsupportedOpcode(next_steps[0].op))
+}
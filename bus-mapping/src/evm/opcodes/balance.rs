@@ -0,0 +1,173 @@
+use super::Opcode;
+use crate::circuit_input_builder::CircuitInputStateRef;
+use crate::eth_types::{GethExecStep, ToAddress};
+use crate::{
+    operation::{AccountField, AccountOp, StackOp, RW},
+    Error,
+};
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the [`OpcodeId::BALANCE`](crate::evm::OpcodeId::BALANCE)
+/// `OpcodeId`.  This is responsible of generating all of the associated
+/// [`StackOp`]s, [`AccountOp`]s and
+/// [`TxAccessListAccountOp`](crate::operation::TxAccessListAccountOp)s and
+/// place them inside the trace's
+/// [`OperationContainer`](crate::operation::OperationContainer).
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Balance;
+
+impl Opcode for Balance {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        steps: &[GethExecStep],
+    ) -> Result<(), Error> {
+        let step = &steps[0];
+
+        // Stack read of the address
+        let address_word = step.stack.last()?;
+        state.push_op(StackOp::new(
+            RW::READ,
+            step.stack.last_filled(),
+            address_word,
+        ));
+
+        // The account is not accessed when the step fails
+        if state.step.error.is_some() {
+            return Ok(());
+        }
+
+        // Mark the account as accessed in the transaction
+        let address = address_word.to_address();
+        state.add_address_to_access_list(address)?;
+
+        // Account read of the balance.  Accounts that don't exist have a zero
+        // balance.
+        let (_, account) = state.sdb.get_account(&address);
+        let balance = account.balance;
+        if balance != steps[1].stack.last()? {
+            return Err(Error::InvalidGethExecStep(
+                "BALANCE: balance mismatch",
+                Box::new(step.clone()),
+            ));
+        }
+        state.push_op(AccountOp::new(
+            RW::READ,
+            address,
+            AccountField::Balance,
+            balance,
+            balance,
+        ));
+
+        // Stack write of the balance
+        state.push_op(StackOp::new(
+            RW::WRITE,
+            step.stack.last_filled(),
+            balance,
+        ));
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod balance_tests {
+    use super::*;
+    use crate::{
+        address, bytecode,
+        circuit_input_builder::CircuitInputBuilder,
+        eth_types::{Word, H256},
+        evm::StackAddress,
+        mock,
+        operation::{Operation, TxAccessListAccountOp},
+        state_db::Account,
+    };
+    use pretty_assertions::assert_eq;
+    use std::collections::HashMap;
+
+    #[test]
+    fn balance_opcode_impl() -> Result<(), Error> {
+        let addr_b = address!("0x0000000000000000000000000000000000000123");
+        let addr_empty = address!("0x0000000000000000000000000000000000000456");
+        let code_a = bytecode! {
+            PUSH2(0x123u64)
+            BALANCE
+            PUSH2(0x456u64)
+            BALANCE
+            STOP
+        };
+        let code_b = bytecode! {
+            STOP
+        };
+
+        // Get the execution steps from the external tracer
+        let block =
+            mock::BlockData::new_single_tx_trace_code_2(&code_a, &code_b)
+                .unwrap();
+
+        let mut builder = CircuitInputBuilder::new(
+            block.eth_block.clone(),
+            block.block_ctants.clone(),
+        );
+        // Same balance as in `mock::new_tracer_account`
+        builder.sdb.set_account(
+            &addr_b,
+            Account {
+                nonce: Word::zero(),
+                balance: Word::from(555u64),
+                storage: HashMap::new(),
+                codeHash: H256::zero(),
+            },
+        );
+        builder.handle_tx(&block.eth_tx, &block.geth_trace).unwrap();
+
+        let account_ops = [
+            AccountOp::new(
+                RW::READ,
+                addr_b,
+                AccountField::Balance,
+                Word::from(555u64),
+                Word::from(555u64),
+            ),
+            AccountOp::new(
+                RW::READ,
+                addr_empty,
+                AccountField::Balance,
+                Word::zero(),
+                Word::zero(),
+            ),
+        ];
+        // The account operations of the BeginTx step come first
+        assert_eq!(
+            builder.block.container.account[5..7]
+                .iter()
+                .map(Operation::op)
+                .collect::<Vec<_>>(),
+            account_ops.iter().collect::<Vec<_>>()
+        );
+        // The sender, the callee and the precompiles are warmed up in the
+        // BeginTx step
+        let access_list_ops = [addr_b, addr_empty].map(|address| {
+            TxAccessListAccountOp::new(RW::WRITE, 1, address, true, false)
+        });
+        assert_eq!(
+            builder.block.container.tx_access_list_account[11..]
+                .iter()
+                .map(Operation::op)
+                .collect::<Vec<_>>(),
+            access_list_ops.iter().collect::<Vec<_>>()
+        );
+        let stack_ops = [
+            StackOp::new(RW::READ, StackAddress::from(1023), Word::from(0x123)),
+            StackOp::new(RW::WRITE, StackAddress::from(1023), Word::from(555)),
+        ];
+        assert_eq!(
+            builder.block.container.stack[1..3]
+                .iter()
+                .map(Operation::op)
+                .collect::<Vec<_>>(),
+            stack_ops.iter().collect::<Vec<_>>()
+        );
+
+        Ok(())
+    }
+}
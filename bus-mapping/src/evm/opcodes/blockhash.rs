@@ -0,0 +1,147 @@
+use super::Opcode;
+use crate::circuit_input_builder::CircuitInputStateRef;
+use crate::eth_types::GethExecStep;
+use crate::{
+    operation::{StackOp, RW},
+    Error,
+};
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the
+/// [`OpcodeId::BLOCKHASH`](OpcodeId::BLOCKHASH) `OpcodeId`.
+/// The hash is taken from the history hashes of the
+/// [`BlockConstants`](crate::exec_trace::BlockConstants).
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Blockhash;
+
+impl Opcode for Blockhash {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        steps: &[GethExecStep],
+    ) -> Result<(), Error> {
+        let step = &steps[0];
+
+        // Stack read of the block number
+        let number = step.stack.last()?;
+        state.push_op(StackOp::new(RW::READ, step.stack.last_filled(), number));
+
+        // The block hash is not pushed when the step fails
+        if state.step.error.is_some() {
+            return Ok(());
+        }
+
+        let hash = state.block.constants.block_hash(number);
+        if hash != steps[1].stack.last()? {
+            return Err(Error::InvalidGethExecStep(
+                "BLOCKHASH: block hash mismatch",
+                Box::new(step.clone()),
+            ));
+        }
+
+        // Stack write of the block hash
+        state.push_op(StackOp::new(RW::WRITE, step.stack.last_filled(), hash));
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod blockhash_tests {
+    use super::*;
+    use crate::{
+        bytecode,
+        circuit_input_builder::CircuitInputBuilder,
+        eth_types::{GethExecTrace, Word},
+        evm::{Gas, OpcodeId, StackAddress},
+        exec_trace::BlockConstants,
+        external_tracer, mock,
+        operation::Operation,
+    };
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn blockhash_opcode_impl() -> Result<(), Error> {
+        let block = mock::BlockData::new_single_tx_trace_code(&bytecode! {
+            STOP
+        })
+        .unwrap();
+        let ctants = &block.block_ctants;
+        let number = ctants.number().as_u64();
+        let history_hashes: Vec<Word> =
+            (0..256).map(|i| Word::from(0x1000 + i)).collect();
+        let block_ctants = BlockConstants::new(
+            *ctants.hash(),
+            *ctants.coinbase(),
+            *ctants.timestamp(),
+            *ctants.number(),
+            *ctants.difficulty(),
+            *ctants.gas_limit(),
+            *ctants.chain_id(),
+            *ctants.base_fee(),
+            history_hashes,
+        );
+
+        // The most recent and the oldest accessible blocks, the current block
+        // and a block older than 256 blocks.
+        let numbers = [number - 1, number - 256, number, number - 257];
+        let hashes = [
+            Word::from(0x10ff),
+            Word::from(0x1000),
+            Word::zero(),
+            Word::zero(),
+        ];
+        let mut code = bytecode! {};
+        for n in numbers {
+            code.push(32, Word::from(n));
+            code.write_op(OpcodeId::BLOCKHASH);
+        }
+        code.write_op(OpcodeId::STOP);
+
+        // Get the execution steps from the external tracer with the history
+        // hashes
+        let geth_trace = GethExecTrace {
+            gas: Gas(block.eth_tx.gas.as_u64()),
+            failed: false,
+            struct_logs: external_tracer::trace(
+                &block_ctants,
+                &external_tracer::Transaction::from_eth_tx(&block.eth_tx),
+                &[mock::new_tracer_account(&code)],
+            )?,
+        };
+
+        let mut builder =
+            CircuitInputBuilder::new(block.eth_block.clone(), block_ctants);
+        builder.handle_tx(&block.eth_tx, &geth_trace).unwrap();
+
+        let stack_ops: Vec<StackOp> = numbers
+            .iter()
+            .zip(hashes.iter())
+            .flat_map(|(n, hash)| {
+                [
+                    StackOp::new(
+                        RW::READ,
+                        StackAddress::from(1023),
+                        Word::from(*n),
+                    ),
+                    StackOp::new(RW::WRITE, StackAddress::from(1023), *hash),
+                ]
+            })
+            .collect();
+        // Each BLOCKHASH follows the stack write of its PUSH32
+        assert_eq!(
+            builder
+                .block
+                .container
+                .stack
+                .iter()
+                .skip(1)
+                .step_by(3)
+                .zip(builder.block.container.stack.iter().skip(2).step_by(3))
+                .flat_map(|(read, write)| [read.op(), write.op()])
+                .collect::<Vec<_>>(),
+            stack_ops.iter().collect::<Vec<_>>()
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,733 @@
+use super::Opcode;
+use crate::circuit_input_builder::{
+    self, get_call_result, get_memory_bytes, get_memory_range, CallKind,
+    CircuitInputStateRef, ExecError,
+};
+use crate::eth_types::{GethExecStep, ToAddress, Word};
+use crate::{
+    evm::{gas::call_gas_cost, GasCost, MemoryAddress, Precompile},
+    operation::{MemoryOp, StackOp, RW},
+    Error,
+};
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the [`OpcodeId::CALL`](crate::evm::OpcodeId::CALL),
+/// [`OpcodeId::CALLCODE`](crate::evm::OpcodeId::CALLCODE),
+/// [`OpcodeId::DELEGATECALL`](crate::evm::OpcodeId::DELEGATECALL) and
+/// [`OpcodeId::STATICCALL`](crate::evm::OpcodeId::STATICCALL) `OpcodeId`s.
+/// This is responsible of generating all of the associated [`StackOp`]s,
+/// [`MemoryOp`]s,
+/// [`TxAccessListAccountOp`](crate::operation::TxAccessListAccountOp)s and
+/// [`AccountOp`](crate::operation::AccountOp)s of the value transfer and place
+/// them inside the trace's
+/// [`OperationContainer`](crate::operation::OperationContainer), and of
+/// pushing the new [`Call`](circuit_input_builder::Call) into the call stack
+/// when the callee code is executed, or when the callee is a [`Precompile`]
+/// which is executed natively.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Call;
+
+impl Opcode for Call {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        steps: &[GethExecStep],
+    ) -> Result<(), Error> {
+        let step = &steps[0];
+        let kind = CallKind::try_from(step.op)?;
+
+        // CALL and CALLCODE take a value argument in the third stack position
+        // which DELEGATECALL and STATICCALL don't have.
+        let has_value = matches!(kind, CallKind::Call | CallKind::CallCode);
+        let n_args = if has_value { 7 } else { 6 };
+
+        // Stack reads of all the arguments
+        for i in 0..n_args {
+            state.push_op(StackOp::new(
+                RW::READ,
+                step.stack.nth_last_filled(i),
+                step.stack.nth_last(i)?,
+            ));
+        }
+
+        // The call fails without reading the call data when the step fails,
+        // except on depth and insufficient balance errors, which fail the call
+        // before the callee is called and push a zero result.
+        let is_early_failure = matches!(
+            state.step.error,
+            Some(ExecError::Depth | ExecError::InsufficientBalance)
+        );
+        if state.step.error.is_some() && !is_early_failure {
+            return Ok(());
+        }
+
+        let code_address = step.stack.nth_last(1)?.to_address();
+        let value = match kind {
+            CallKind::Call | CallKind::CallCode => step.stack.nth_last(2)?,
+            CallKind::DelegateCall => state.call().value,
+            _ => Word::zero(),
+        };
+        let args_pos = if has_value { 3 } else { 2 };
+        let call_data_range = get_memory_range(
+            step.stack.nth_last(args_pos)?,
+            step.stack.nth_last(args_pos + 1)?,
+        )?;
+        let return_data_range = get_memory_range(
+            step.stack.nth_last(args_pos + 2)?,
+            step.stack.nth_last(args_pos + 3)?,
+        )?;
+        let call_data_offset = call_data_range.start as u64;
+        let call_data_length = call_data_range.len() as u64;
+        let return_data_offset = return_data_range.start as u64;
+        let return_data_length = return_data_range.len() as u64;
+
+        // Memory reads of the call data in the caller memory.  Bytes beyond
+        // the current memory size are read as zero since the memory is
+        // expanded by the call.
+        let call_data = get_memory_bytes(step, call_data_range.clone());
+        for (address, byte) in call_data_range.zip(call_data.iter()) {
+            state.push_op(MemoryOp::new(
+                RW::READ,
+                MemoryAddress::from(address),
+                *byte,
+            ));
+        }
+
+        // Stack write of the call result, which is found in the first step
+        // after the call in the caller context.
+        let result = get_call_result(steps);
+        if let Some(result) = result {
+            state.push_op(StackOp::new(
+                RW::WRITE,
+                step.stack.nth_last_filled(n_args - 1),
+                result,
+            ));
+        }
+
+        // Mark the callee as accessed in the transaction, after computing the
+        // gas sent to it, which depends on whether it was already accessed.
+        let (_, callee_gas) = call_gas_cost(state, step)?;
+        state.add_address_to_access_list(code_address)?;
+
+        // The return data of the previous sub-call is discarded, and it's
+        // replaced by the one of the callee when it returns.
+        state.call_ctx_mut().return_data = Vec::new();
+
+        // CALL transfers the value to the callee.  CALLCODE transfers it from
+        // the caller to itself, which doesn't change any balance.
+        let transfers_value = kind == CallKind::Call && !value.is_zero();
+        let is_executed = steps.get(1).map(|s| s.depth) == Some(step.depth + 1);
+        let is_success = result.map(|r| !r.is_zero()).unwrap_or(false);
+
+        // Precompiled contracts don't have any step, so they are executed
+        // natively in a new call, unless the call fails early on the depth or
+        // the balance checks before the callee is reached.
+        let precompile = Precompile::from_address(&code_address)
+            .filter(|_| !is_executed && !is_early_failure);
+
+        // Push the new call only when the callee code is executed.  Accounts
+        // without code and early failures (depth, insufficient balance)
+        // return to the caller immediately, and the value is only transferred
+        // when they succeed.
+        if !is_executed && precompile.is_none() {
+            if transfers_value && is_success {
+                let caller_address = state.call().address;
+                state.transfer(caller_address, code_address, value)?;
+            }
+            return Ok(());
+        }
+
+        // Save the context of the caller, which is restored when the callee
+        // returns.
+        state.save_call_context(steps);
+
+        let caller = state.call();
+        let (caller_address, address) = match kind {
+            CallKind::Call | CallKind::StaticCall => {
+                (caller.address, code_address)
+            }
+            CallKind::CallCode => (caller.address, caller.address),
+            CallKind::DelegateCall => (caller.caller_address, caller.address),
+            _ => return Err(Error::OpcodeIdNotCallType),
+        };
+        let is_static = kind == CallKind::StaticCall || caller.is_static;
+        let is_persistent = caller.is_persistent && is_success;
+        let (_, code_account) = state.sdb.get_account(&code_address);
+        let code_hash = code_account.code_hash();
+
+        let call_id = state.next_call_id();
+        state.push_call(circuit_input_builder::Call {
+            call_id,
+            kind,
+            is_static,
+            is_root: false,
+            caller_address,
+            address,
+            code_address,
+            code_hash,
+            value,
+            call_data_offset,
+            call_data_length,
+            call_data: call_data.clone(),
+            return_data_offset,
+            return_data_length,
+            is_success,
+            is_persistent,
+            rw_counter_end_of_reversion: 0,
+        })?;
+        state.init_call_context()?;
+
+        // The value is transferred in the new call, so it's reverted when the
+        // callee fails.
+        if transfers_value {
+            state.transfer(caller_address, address, value)?;
+        }
+
+        if let Some(precompile) = precompile {
+            let output = if is_success {
+                // The callee also gets the stipend of a value transfer
+                let callee_gas = if has_value && !value.is_zero() {
+                    callee_gas + GasCost::CALL_STIPEND.as_u64()
+                } else {
+                    callee_gas
+                };
+                Some(execute_precompile(
+                    steps, precompile, &call_data, callee_gas,
+                )?)
+            } else {
+                None
+            };
+            state.handle_precompile_return(output.clone())?;
+
+            // Memory writes of the output in the caller memory, which must
+            // match the memory found after the call.
+            let output = output.unwrap_or_default();
+            let length = output.len().min(return_data_length as usize);
+            for (i, byte) in output[..length].iter().enumerate() {
+                let address = return_data_offset as usize + i;
+                if steps[1].memory.0.get(address) != Some(byte) {
+                    return Err(Error::InvalidGethExecStep(
+                        "*CALL*: precompile output doesn't match memory",
+                        Box::new(steps[1].clone()),
+                    ));
+                }
+                state.push_op(MemoryOp::new(
+                    RW::WRITE,
+                    MemoryAddress::from(address),
+                    *byte,
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Execute the `precompile` called by the *CALL* at `steps[0]` with `input`
+/// and `callee_gas`, which succeeds, and return its output.  The gas returned
+/// to the caller, found in `steps[1]`, must be the gas left after the gas cost
+/// of the precompiled contract.
+fn execute_precompile(
+    steps: &[GethExecStep],
+    precompile: Precompile,
+    input: &[u8],
+    callee_gas: u64,
+) -> Result<Vec<u8>, Error> {
+    let (step, next_step) = (&steps[0], &steps[1]);
+    let output = precompile.execute(input).ok_or_else(|| {
+        Error::InvalidGethExecStep(
+            "*CALL*: precompile succeeds with an invalid input",
+            Box::new(step.clone()),
+        )
+    })?;
+
+    let gas_left = step.gas.0 - step.gas_cost.as_u64();
+    let returned_gas = callee_gas.checked_sub(precompile.gas_cost(input));
+    if returned_gas.map(|gas| gas_left + gas) != Some(next_step.gas.0) {
+        return Err(Error::InvalidGethExecStep(
+            "*CALL*: precompile gas cost mismatch",
+            Box::new(next_step.clone()),
+        ));
+    }
+
+    Ok(output)
+}
+
+#[cfg(test)]
+mod call_tests {
+    use super::*;
+    use crate::{
+        address, bytecode,
+        circuit_input_builder::{
+            CircuitInputBuilder, ExecError, ExecState, ExecStep, Transaction,
+            TransactionContext,
+        },
+        eth_types::{Address, ToWord},
+        evm::{OpcodeId, StackAddress},
+        mock,
+        operation::{
+            AccountField, AccountOp, CallContextField, CallContextOp,
+            Operation, Target, TxAccessListAccountOp,
+        },
+        state_db::Account,
+    };
+    use pretty_assertions::assert_eq;
+    use std::collections::HashMap;
+
+    fn test_call_ops(
+        op: OpcodeId,
+        expected_caller: Address,
+        expected_address: Address,
+    ) -> Result<(), Error> {
+        let kind = CallKind::try_from(op)?;
+        let has_value = matches!(kind, CallKind::Call | CallKind::CallCode);
+        let addr_b = address!("0x0000000000000000000000000000000000000123");
+
+        let code_b = bytecode! {
+            STOP
+        };
+        let mut code_a = bytecode! {
+            // Store 0x11223344 in memory so that it's used as call data
+            PUSH4(0x11223344u64)
+            PUSH1(0x00u64)
+            MSTORE
+            PUSH1(0x00u64) // retLength
+            PUSH1(0x00u64) // retOffset
+            PUSH1(0x04u64) // argsLength
+            PUSH1(0x1cu64) // argsOffset
+        };
+        if has_value {
+            code_a.push(1, Word::zero()); // value
+        }
+        code_a.push(2, Word::from(0x123u64)); // addr
+        code_a.push(2, Word::from(0xffffu64)); // gas
+        code_a.add_marker("start".to_string());
+        code_a.write_op(op);
+        code_a.write_op(OpcodeId::STOP);
+
+        // Get the execution steps from the external tracer
+        let mut block =
+            mock::BlockData::new_single_tx_trace_code_2(&code_a, &code_b)
+                .unwrap();
+        block.geth_trace.struct_logs =
+            block.geth_trace.struct_logs[code_a.get_pos("start")..].to_vec();
+
+        let mut builder = CircuitInputBuilder::new(
+            block.eth_block.clone(),
+            block.block_ctants.clone(),
+        );
+        // The callee code is executed in the new call, with the same balance
+        // as in `mock::new_tracer_account`.
+        let code_hash_b = builder.sdb.set_code(code_b.to_bytes());
+        builder.sdb.set_account(
+            &addr_b,
+            Account {
+                nonce: Word::zero(),
+                balance: Word::from(555u64),
+                storage: HashMap::new(),
+                codeHash: code_hash_b,
+            },
+        );
+        builder.handle_tx(&block.eth_tx, &block.geth_trace).unwrap();
+
+        let mut test_builder = CircuitInputBuilder::new(
+            block.eth_block,
+            block.block_ctants.clone(),
+        );
+        let mut tx = Transaction::new(&block.eth_tx);
+        let mut tx_ctx = TransactionContext::new(&block.eth_tx);
+        test_builder
+            .begin_tx(&mut tx, &mut tx_ctx, &block.geth_trace)
+            .unwrap();
+
+        // Generate step corresponding to the *CALL*
+        let mut step = ExecStep::new(
+            &block.geth_trace.struct_logs[0],
+            0,
+            test_builder.block_ctx.gc,
+            0,
+        );
+        let mut state_ref =
+            test_builder.state_ref(&mut tx, &mut tx_ctx, &mut step);
+        let mut args = vec![
+            Word::from(0xffffu64),
+            Word::from(0x123u64),
+            Word::zero(),
+            Word::from(0x1cu64),
+            Word::from(0x04u64),
+            Word::zero(),
+            Word::zero(),
+        ];
+        if !has_value {
+            args.remove(2);
+        }
+        // Add StackOps associated to the stack pops.
+        for (i, arg) in args.iter().enumerate() {
+            state_ref.push_op(StackOp::new(
+                RW::READ,
+                StackAddress::from(1024 - args.len() + i),
+                *arg,
+            ));
+        }
+        // Add MemoryOps associated to the call data reads.
+        for (i, byte) in [0x11u8, 0x22, 0x33, 0x44].iter().enumerate() {
+            state_ref.push_op(MemoryOp::new(
+                RW::READ,
+                MemoryAddress::from(0x1c + i),
+                *byte,
+            ));
+        }
+        // Add StackOp associated to the result push.
+        state_ref.push_op(StackOp::new(
+            RW::WRITE,
+            StackAddress::from(1023),
+            Word::one(),
+        ));
+        // Add TxAccessListAccountOp associated to the cold callee access.
+        state_ref.push_op(TxAccessListAccountOp::new(
+            RW::WRITE,
+            1,
+            addr_b,
+            true,
+            false,
+        ));
+        // Add CallContextOps associated to the caller context save, which is
+        // the one found after the *CALL* except for the gas left.
+        let call_step = &block.geth_trace.struct_logs[0];
+        let next_step = block.geth_trace.struct_logs.last().unwrap();
+        let root_call_id = 1;
+        for (field, value) in [
+            (CallContextField::ProgramCounter, next_step.pc.0),
+            (CallContextField::StackPointer, 1023),
+            (
+                CallContextField::GasLeft,
+                (call_step.gas.0 - call_step.gas_cost.as_u64()) as usize,
+            ),
+            (CallContextField::MemorySize, 1),
+            // The value transfer of the transaction and the callee access
+            (CallContextField::StateWriteCounter, 3),
+        ] {
+            state_ref.push_op(CallContextOp::new(
+                RW::WRITE,
+                root_call_id,
+                field,
+                Word::from(value),
+            ));
+        }
+        // Add CallContextOps associated to the callee context.
+        let call_id = state_ref.next_call_id();
+        for (field, value) in [
+            (CallContextField::RwCounterEndOfReversion, Word::zero()),
+            (CallContextField::CallerCallId, Word::from(root_call_id)),
+            (CallContextField::TxId, Word::one()),
+            (CallContextField::Depth, Word::from(2)),
+            (CallContextField::CallerAddress, expected_caller.to_word()),
+            (CallContextField::CalleeAddress, expected_address.to_word()),
+            (CallContextField::CallDataOffset, Word::from(0x1c)),
+            (CallContextField::CallDataLength, Word::from(0x04)),
+            (CallContextField::ReturnDataOffset, Word::zero()),
+            (CallContextField::ReturnDataLength, Word::zero()),
+            (CallContextField::Value, Word::zero()),
+            (CallContextField::Result, Word::one()),
+            (CallContextField::IsPersistent, Word::one()),
+            (
+                CallContextField::IsStatic,
+                Word::from((kind == CallKind::StaticCall) as u64),
+            ),
+            (CallContextField::IsRoot, Word::zero()),
+            (CallContextField::IsCreate, Word::zero()),
+            (CallContextField::OpcodeSource, code_hash_b.to_word()),
+        ] {
+            state_ref.push_op(CallContextOp::new(
+                RW::READ,
+                call_id,
+                field,
+                value,
+            ));
+        }
+        tx.steps_mut().push(step);
+        test_builder.block.txs_mut().push(tx);
+
+        assert_eq!(
+            builder.block.txs()[0].steps()[1].bus_mapping_instance,
+            test_builder.block.txs()[0].steps()[1].bus_mapping_instance
+        );
+        assert_eq!(
+            builder.block.container.stack,
+            test_builder.block.container.stack
+        );
+        assert_eq!(
+            builder.block.container.memory,
+            test_builder.block.container.memory
+        );
+        assert_eq!(
+            builder.block.container.tx_access_list_account,
+            test_builder.block.container.tx_access_list_account
+        );
+        // The caller context is restored when the callee returns
+        let n_call_context_ops =
+            test_builder.block.container.call_context.len();
+        assert_eq!(
+            builder.block.container.call_context[..n_call_context_ops],
+            test_builder.block.container.call_context
+        );
+        let restored_ops: Vec<_> = builder.block.container.call_context
+            [n_call_context_ops..n_call_context_ops + 5]
+            .iter()
+            .map(|op| {
+                (
+                    op.op().rw(),
+                    op.op().call_id(),
+                    *op.op().field(),
+                    *op.op().value(),
+                )
+            })
+            .collect();
+        // The first 3 operations are the reads of the BeginTx step
+        let saved_ops: Vec<_> = test_builder.block.container.call_context[3..8]
+            .iter()
+            .map(|op| {
+                (
+                    RW::READ,
+                    op.op().call_id(),
+                    *op.op().field(),
+                    *op.op().value(),
+                )
+            })
+            .collect();
+        assert_eq!(restored_ops, saved_ops);
+
+        // Check the context of the new call
+        let call = &builder.block.txs()[0].calls()[1];
+        assert_eq!(call.call_id, call_id);
+        assert_eq!(call.kind, kind);
+        assert_eq!(call.caller_address, expected_caller);
+        assert_eq!(call.address, expected_address);
+        assert_eq!(call.code_address, addr_b);
+        assert_eq!(call.code_hash, code_hash_b);
+        assert_eq!(call.is_static, kind == CallKind::StaticCall);
+        assert_eq!(call.call_data_offset, 0x1c);
+        assert_eq!(call.call_data_length, 0x04);
+        assert_eq!(call.call_data, vec![0x11, 0x22, 0x33, 0x44]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn call_opcode_impl() -> Result<(), Error> {
+        let addr_a = Address::zero();
+        let addr_b = address!("0x0000000000000000000000000000000000000123");
+        let origin = mock::new_tracer_tx().origin;
+        test_call_ops(OpcodeId::CALL, addr_a, addr_b)?;
+        test_call_ops(OpcodeId::CALLCODE, addr_a, addr_a)?;
+        test_call_ops(OpcodeId::DELEGATECALL, origin, addr_a)?;
+        test_call_ops(OpcodeId::STATICCALL, addr_a, addr_b)
+    }
+
+    #[test]
+    fn call_value_transfer() -> Result<(), Error> {
+        let addr_a = Address::zero();
+        let addr_b = address!("0x0000000000000000000000000000000000000123");
+        let code_b = bytecode! {
+            STOP
+        };
+        let code_a = bytecode! {
+            PUSH1(0x00u64) // retLength
+            PUSH1(0x00u64) // retOffset
+            PUSH1(0x00u64) // argsLength
+            PUSH1(0x00u64) // argsOffset
+            PUSH1(0x10u64) // value
+            PUSH2(0x123u64) // addr
+            PUSH2(0xffffu64) // gas
+            CALL
+            STOP
+        };
+
+        // Get the execution steps from the external tracer
+        let block =
+            mock::BlockData::new_single_tx_trace_code_2(&code_a, &code_b)
+                .unwrap();
+
+        let mut builder = CircuitInputBuilder::new(
+            block.eth_block.clone(),
+            block.block_ctants.clone(),
+        );
+        // Same balance as in `mock::new_tracer_account`
+        for address in [addr_a, addr_b] {
+            builder.sdb.get_account_mut(&address).1.balance =
+                Word::from(555u64);
+        }
+        builder.handle_tx(&block.eth_tx, &block.geth_trace).unwrap();
+
+        let account_ops = [
+            AccountOp::new(
+                RW::WRITE,
+                addr_a,
+                AccountField::Balance,
+                Word::from(555u64 - 0x10),
+                Word::from(555u64),
+            ),
+            AccountOp::new(
+                RW::WRITE,
+                addr_b,
+                AccountField::Balance,
+                Word::from(555u64 + 0x10),
+                Word::from(555u64),
+            ),
+        ];
+        // The account operations of the CALL come after the ones of the
+        // BeginTx step.
+        assert_eq!(
+            builder.block.container.account[5..7]
+                .iter()
+                .map(Operation::op)
+                .collect::<Vec<_>>(),
+            account_ops.iter().collect::<Vec<_>>()
+        );
+        assert_eq!(
+            builder.sdb.get_account(&addr_b).1.balance,
+            Word::from(555u64 + 0x10)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn call_precompile() -> Result<(), Error> {
+        let code = bytecode! {
+            // Store 0x11223344 in memory so that it's used as input
+            PUSH4(0x11223344u64)
+            PUSH1(0x00u64)
+            MSTORE
+            PUSH1(0x20u64) // retLength
+            PUSH1(0x20u64) // retOffset
+            PUSH1(0x04u64) // argsLength
+            PUSH1(0x1cu64) // argsOffset
+            PUSH1(0x00u64) // value
+            PUSH1(0x02u64) // addr
+            PUSH2(0xffffu64) // gas
+            CALL
+            RETURNDATASIZE
+            // Call the identity precompile without enough gas
+            PUSH1(0x20u64) // retLength
+            PUSH1(0x40u64) // retOffset
+            PUSH1(0x04u64) // argsLength
+            PUSH1(0x1cu64) // argsOffset
+            PUSH1(0x00u64) // value
+            PUSH1(0x04u64) // addr
+            PUSH1(0x00u64) // gas
+            CALL
+            RETURNDATASIZE
+            STOP
+        };
+
+        // Get the execution steps from the external tracer
+        let block = mock::BlockData::new_single_tx_trace_code(&code).unwrap();
+
+        let mut builder = CircuitInputBuilder::new(
+            block.eth_block.clone(),
+            block.block_ctants.clone(),
+        );
+        builder.handle_tx(&block.eth_tx, &block.geth_trace).unwrap();
+
+        // The precompiles are executed in new calls, and the second one fails
+        let calls = builder.block.txs()[0].calls();
+        assert_eq!(calls.len(), 3);
+        assert_eq!(calls[1].code_address, Precompile::Sha256.address());
+        assert_eq!(calls[1].call_data, vec![0x11, 0x22, 0x33, 0x44]);
+        assert!(calls[1].is_success);
+        assert_eq!(calls[2].code_address, Precompile::Identity.address());
+        assert!(!calls[2].is_success);
+
+        // The output of SHA-256 is written into the caller memory
+        let output = Precompile::Sha256
+            .execute(&[0x11, 0x22, 0x33, 0x44])
+            .unwrap();
+        let memory_ops: Vec<_> = output
+            .iter()
+            .enumerate()
+            .map(|(i, byte)| {
+                MemoryOp::new(RW::WRITE, MemoryAddress::from(0x20 + i), *byte)
+            })
+            .collect();
+        let n_memory_ops = builder.block.container.memory.len();
+        assert_eq!(
+            builder.block.container.memory
+                [n_memory_ops - 32 - 4..n_memory_ops - 4]
+                .iter()
+                .map(Operation::op)
+                .collect::<Vec<_>>(),
+            memory_ops.iter().collect::<Vec<_>>()
+        );
+
+        // The return data is the output of the successful call, and it's
+        // cleared by the failed one.
+        let return_data_sizes: Vec<_> = builder.block.txs()[0]
+            .steps()
+            .iter()
+            .filter(|step| {
+                step.exec_state == ExecState::Op(OpcodeId::RETURNDATASIZE)
+            })
+            .map(|step| {
+                let op_ref = step.bus_mapping_instance.last().unwrap();
+                *builder.block.container.stack[op_ref.as_usize() - 1]
+                    .op()
+                    .value()
+            })
+            .collect();
+        assert_eq!(return_data_sizes, vec![Word::from(32), Word::zero()]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn call_precompile_insufficient_balance() -> Result<(), Error> {
+        let code = bytecode! {
+            // Call the identity precompile with more value than the balance
+            PUSH1(0x20u64) // retLength
+            PUSH1(0x00u64) // retOffset
+            PUSH1(0x04u64) // argsLength
+            PUSH1(0x1cu64) // argsOffset
+            PUSH2(0x1000u64) // value
+            PUSH1(0x04u64) // addr
+            PUSH2(0xffffu64) // gas
+            CALL
+            STOP
+        };
+
+        // Get the execution steps from the external tracer
+        let block = mock::BlockData::new_single_tx_trace_code(&code).unwrap();
+
+        let mut builder = CircuitInputBuilder::new(
+            block.eth_block.clone(),
+            block.block_ctants.clone(),
+        );
+        // Same balance as in `mock::new_tracer_account`
+        builder.sdb.get_account_mut(&Address::zero()).1.balance =
+            Word::from(555u64);
+        builder.handle_tx(&block.eth_tx, &block.geth_trace).unwrap();
+
+        // The call fails before the precompile is reached, so no call is
+        // pushed and no value is transferred.
+        assert_eq!(builder.block.txs()[0].calls().len(), 1);
+        let step = builder.block.txs()[0]
+            .steps()
+            .iter()
+            .find(|step| step.exec_state == ExecState::Op(OpcodeId::CALL))
+            .unwrap();
+        assert_eq!(step.error, Some(ExecError::InsufficientBalance));
+        assert!(step
+            .bus_mapping_instance
+            .iter()
+            .all(|op_ref| op_ref.target() != Target::Account));
+        assert_eq!(
+            builder
+                .sdb
+                .get_account(&Precompile::Identity.address())
+                .1
+                .balance,
+            Word::zero()
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,400 @@
+use super::Opcode;
+use crate::circuit_input_builder::{get_memory_range, CircuitInputStateRef};
+use crate::eth_types::{GethExecStep, Word};
+use crate::{
+    evm::MemoryAddress,
+    operation::{CallContextField, MemoryOp, StackOp, RW},
+    Error,
+};
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the
+/// [`OpcodeId::CALLDATALOAD`](crate::evm::OpcodeId::CALLDATALOAD)
+/// `OpcodeId`.  This is responsible of generating all of the associated
+/// [`StackOp`]s and [`MemoryOp`]s and place them inside the trace's
+/// [`OperationContainer`](crate::operation::OperationContainer).
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Calldataload;
+
+impl Opcode for Calldataload {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        steps: &[GethExecStep],
+    ) -> Result<(), Error> {
+        let step = &steps[0];
+
+        // Stack read of the call data offset
+        let offset = step.stack.last()?;
+        state.push_op(StackOp::new(RW::READ, step.stack.last_filled(), offset));
+
+        // Nothing is pushed when the step fails
+        if state.step.error.is_some() {
+            return Ok(());
+        }
+
+        // Call data read of the 32 bytes word
+        let bytes = read_call_data(state, offset, 32);
+        let value = Word::from_big_endian(&bytes);
+        if steps[1].stack.last()? != value {
+            return Err(Error::InvalidGethExecStep(
+                "CALLDATALOAD: loaded value doesn't match the call data",
+                Box::new(step.clone()),
+            ));
+        }
+
+        // Stack write of the word
+        state.push_op(StackOp::new(RW::WRITE, step.stack.last_filled(), value));
+
+        Ok(())
+    }
+}
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the
+/// [`OpcodeId::CALLDATASIZE`](crate::evm::OpcodeId::CALLDATASIZE)
+/// `OpcodeId`.  This is responsible of generating the associated
+/// [`CallContextOp`](crate::operation::CallContextOp) and [`StackOp`] and
+/// place them inside the trace's
+/// [`OperationContainer`](crate::operation::OperationContainer).
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Calldatasize;
+
+impl Opcode for Calldatasize {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        steps: &[GethExecStep],
+    ) -> Result<(), Error> {
+        let step = &steps[0];
+
+        // Nothing is pushed when the step fails
+        if state.step.error.is_some() {
+            return Ok(());
+        }
+
+        let value = Word::from(state.call().call_data_length);
+        if steps[1].stack.last()? != value {
+            return Err(Error::InvalidGethExecStep(
+                "CALLDATASIZE: pushed value doesn't match the call data length",
+                Box::new(step.clone()),
+            ));
+        }
+
+        // Call context read of the call data length
+        state.push_call_context_op(
+            RW::READ,
+            CallContextField::CallDataLength,
+            value,
+        );
+
+        // Stack write of the call data length
+        state.push_op(StackOp::new(
+            RW::WRITE,
+            step.stack.last_filled().map(|a| a - 1),
+            value,
+        ));
+
+        Ok(())
+    }
+}
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the
+/// [`OpcodeId::CALLDATACOPY`](crate::evm::OpcodeId::CALLDATACOPY)
+/// `OpcodeId`.  This is responsible of generating all of the associated
+/// [`StackOp`]s and [`MemoryOp`]s and place them inside the trace's
+/// [`OperationContainer`](crate::operation::OperationContainer).
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Calldatacopy;
+
+impl Opcode for Calldatacopy {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        steps: &[GethExecStep],
+    ) -> Result<(), Error> {
+        let step = &steps[0];
+
+        // Stack reads of the memory offset, the call data offset and the
+        // length
+        for i in 0..3 {
+            state.push_op(StackOp::new(
+                RW::READ,
+                step.stack.nth_last_filled(i),
+                step.stack.nth_last(i)?,
+            ));
+        }
+
+        // The memory is not written when the step fails
+        if state.step.error.is_some() {
+            return Ok(());
+        }
+
+        // The memory offset is ignored when the length is zero
+        let range =
+            get_memory_range(step.stack.nth_last(0)?, step.stack.nth_last(2)?)?;
+        let data_offset = step.stack.nth_last(1)?;
+
+        // Memory writes of the copied call data
+        let bytes = read_call_data(state, data_offset, range.len());
+        for (address, byte) in range.zip(bytes.iter()) {
+            state.push_op(MemoryOp::new(
+                RW::WRITE,
+                MemoryAddress::from(address),
+                *byte,
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// Read `length` bytes of the call data of the current call starting at
+/// `offset`, padded with zeros beyond its end.  The root call data is the
+/// transaction input, while for internal calls the bytes are read from the
+/// caller memory at the call data region, generating the corresponding
+/// [`MemoryOp`]s.  The call data region is read from the call context.
+fn read_call_data(
+    state: &mut CircuitInputStateRef,
+    offset: Word,
+    length: usize,
+) -> Vec<u8> {
+    let call = state.call();
+    let is_root = call.is_root;
+    let call_data_offset = call.call_data_offset as usize;
+    let call_data_length = call.call_data_length;
+    let call_data = call.call_data.clone();
+
+    // Call context reads of the call data region
+    state.push_call_context_op(
+        RW::READ,
+        CallContextField::CallDataLength,
+        Word::from(call_data_length),
+    );
+    if !is_root {
+        state.push_call_context_op(
+            RW::READ,
+            CallContextField::CallDataOffset,
+            Word::from(call_data_offset),
+        );
+    }
+
+    let offset = if offset.bits() > 64 {
+        usize::MAX
+    } else {
+        offset.low_u64() as usize
+    };
+
+    let mut bytes = vec![0u8; length];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        let index = match offset.checked_add(i) {
+            Some(index) if index < call_data.len() => index,
+            _ => break,
+        };
+        *byte = call_data[index];
+        if !is_root {
+            state.push_op(MemoryOp::new(
+                RW::READ,
+                MemoryAddress::from(call_data_offset + index),
+                *byte,
+            ));
+        }
+    }
+    bytes
+}
+
+#[cfg(test)]
+mod calldata_tests {
+    use super::*;
+    use crate::{
+        bytecode,
+        circuit_input_builder::CircuitInputBuilder,
+        eth_types::Bytes,
+        evm::StackAddress,
+        mock,
+        operation::{CallContextOp, Operation},
+    };
+    use pretty_assertions::assert_eq;
+
+    fn input() -> Bytes {
+        Bytes::from((1..=36).collect::<Vec<u8>>())
+    }
+
+    #[test]
+    fn calldataload_opcode_impl() -> Result<(), Error> {
+        let code = bytecode! {
+            PUSH1(0x10u64)
+            CALLDATALOAD
+            STOP
+        };
+
+        // Get the execution steps from the external tracer
+        let block =
+            mock::BlockData::new_single_tx_trace_code_input(&code, input())
+                .unwrap();
+
+        let mut builder = CircuitInputBuilder::new(
+            block.eth_block.clone(),
+            block.block_ctants.clone(),
+        );
+        builder.handle_tx(&block.eth_tx, &block.geth_trace).unwrap();
+
+        // The bytes beyond the input length are read as zero
+        let mut bytes = [0u8; 32];
+        bytes[..20].copy_from_slice(&input()[0x10..]);
+        let stack_ops = [
+            StackOp::new(RW::READ, StackAddress::from(1023), Word::from(0x10)),
+            StackOp::new(
+                RW::WRITE,
+                StackAddress::from(1023),
+                Word::from_big_endian(&bytes),
+            ),
+        ];
+        assert_eq!(
+            builder.block.container.stack[1..]
+                .iter()
+                .map(Operation::op)
+                .collect::<Vec<_>>(),
+            stack_ops.iter().collect::<Vec<_>>()
+        );
+        // The root call data is not read from memory
+        assert!(builder.block.container.memory.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn calldatasize_opcode_impl() -> Result<(), Error> {
+        let code = bytecode! {
+            CALLDATASIZE
+            STOP
+        };
+
+        // Get the execution steps from the external tracer
+        let block =
+            mock::BlockData::new_single_tx_trace_code_input(&code, input())
+                .unwrap();
+
+        let mut builder = CircuitInputBuilder::new(
+            block.eth_block.clone(),
+            block.block_ctants.clone(),
+        );
+        builder.handle_tx(&block.eth_tx, &block.geth_trace).unwrap();
+
+        assert_eq!(
+            builder
+                .block
+                .container
+                .stack
+                .iter()
+                .map(Operation::op)
+                .collect::<Vec<_>>(),
+            vec![&StackOp::new(
+                RW::WRITE,
+                StackAddress::from(1023),
+                Word::from(36)
+            )]
+        );
+        // The call context is read after the 3 reads of the BeginTx step
+        assert_eq!(
+            builder.block.container.call_context[3..4]
+                .iter()
+                .map(Operation::op)
+                .collect::<Vec<_>>(),
+            vec![&CallContextOp::new(
+                RW::READ,
+                1,
+                CallContextField::CallDataLength,
+                Word::from(36)
+            )]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn calldatacopy_opcode_impl() -> Result<(), Error> {
+        // Code that copies 8 bytes of its call data from offset 2
+        let code_b = bytecode! {
+            PUSH1(0x08u64) // length
+            PUSH1(0x02u64) // dataOffset
+            PUSH1(0x00u64) // memOffset
+            CALLDATACOPY
+            STOP
+        };
+        // Code that calls the code at 0x123 with 0x11223344 as call data
+        let code_a = bytecode! {
+            PUSH4(0x11223344u64)
+            PUSH1(0x00u64)
+            MSTORE
+            PUSH1(0x00u64) // retLength
+            PUSH1(0x00u64) // retOffset
+            PUSH1(0x04u64) // argsLength
+            PUSH1(0x1cu64) // argsOffset
+            PUSH1(0x00u64) // value
+            PUSH2(0x123u64) // addr
+            PUSH2(0xffffu64) // gas
+            CALL
+            STOP
+        };
+
+        // Get the execution steps from the external tracer
+        let block =
+            mock::BlockData::new_single_tx_trace_code_2(&code_a, &code_b)
+                .unwrap();
+
+        let mut builder = CircuitInputBuilder::new(
+            block.eth_block.clone(),
+            block.block_ctants.clone(),
+        );
+        builder.handle_tx(&block.eth_tx, &block.geth_trace).unwrap();
+
+        // The call data in bounds is read from the caller memory, and the
+        // copied bytes beyond it are zero.  The preceding memory ops are the
+        // ones of the MSTORE and the CALL.
+        let memory_ops = [
+            MemoryOp::new(RW::READ, MemoryAddress::from(0x1e), 0x33),
+            MemoryOp::new(RW::READ, MemoryAddress::from(0x1f), 0x44),
+            MemoryOp::new(RW::WRITE, MemoryAddress::from(0x00), 0x33),
+            MemoryOp::new(RW::WRITE, MemoryAddress::from(0x01), 0x44),
+            MemoryOp::new(RW::WRITE, MemoryAddress::from(0x02), 0x00),
+            MemoryOp::new(RW::WRITE, MemoryAddress::from(0x03), 0x00),
+            MemoryOp::new(RW::WRITE, MemoryAddress::from(0x04), 0x00),
+            MemoryOp::new(RW::WRITE, MemoryAddress::from(0x05), 0x00),
+            MemoryOp::new(RW::WRITE, MemoryAddress::from(0x06), 0x00),
+            MemoryOp::new(RW::WRITE, MemoryAddress::from(0x07), 0x00),
+        ];
+        assert_eq!(
+            builder.block.container.memory[36..]
+                .iter()
+                .map(Operation::op)
+                .collect::<Vec<_>>(),
+            memory_ops.iter().collect::<Vec<_>>()
+        );
+        // The call data region is read from the callee context, whose ops
+        // come after the 3 ops of the BeginTx step, the 5 ops saving the
+        // caller context and the 17 ops initializing the callee context.
+        let call_id = builder.block.txs()[0].calls()[1].call_id;
+        let call_context_ops = [
+            CallContextOp::new(
+                RW::READ,
+                call_id,
+                CallContextField::CallDataLength,
+                Word::from(0x04),
+            ),
+            CallContextOp::new(
+                RW::READ,
+                call_id,
+                CallContextField::CallDataOffset,
+                Word::from(0x1c),
+            ),
+        ];
+        assert_eq!(
+            builder.block.container.call_context[25..27]
+                .iter()
+                .map(Operation::op)
+                .collect::<Vec<_>>(),
+            call_context_ops.iter().collect::<Vec<_>>()
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,505 @@
+use super::Opcode;
+use crate::circuit_input_builder::{get_memory_range, CircuitInputStateRef};
+use crate::eth_types::{Address, GethExecStep, ToAddress, ToWord, Word};
+use crate::{
+    evm::MemoryAddress,
+    operation::{AccountField, AccountOp, MemoryOp, StackOp, RW},
+    Error,
+};
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the [`OpcodeId::CODESIZE`](crate::evm::OpcodeId::CODESIZE)
+/// `OpcodeId`.  This is responsible of generating the associated [`StackOp`]
+/// and place it inside the trace's
+/// [`OperationContainer`](crate::operation::OperationContainer).
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Codesize;
+
+impl Opcode for Codesize {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        steps: &[GethExecStep],
+    ) -> Result<(), Error> {
+        let step = &steps[0];
+
+        // Nothing is pushed when the step fails
+        if state.step.error.is_some() {
+            return Ok(());
+        }
+
+        let code = call_code(state, step)?;
+        let value = Word::from(code.len());
+        check_pushed_value(steps, value)?;
+
+        // Stack write of the code size
+        state.push_op(StackOp::new(
+            RW::WRITE,
+            step.stack.last_filled().map(|a| a - 1),
+            value,
+        ));
+
+        Ok(())
+    }
+}
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the [`OpcodeId::CODECOPY`](crate::evm::OpcodeId::CODECOPY)
+/// `OpcodeId`.  This is responsible of generating all of the associated
+/// [`StackOp`]s and [`MemoryOp`]s and place them inside the trace's
+/// [`OperationContainer`](crate::operation::OperationContainer).
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Codecopy;
+
+impl Opcode for Codecopy {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        steps: &[GethExecStep],
+    ) -> Result<(), Error> {
+        let step = &steps[0];
+
+        // Stack reads of the memory offset, the code offset and the length
+        for i in 0..3 {
+            state.push_op(StackOp::new(
+                RW::READ,
+                step.stack.nth_last_filled(i),
+                step.stack.nth_last(i)?,
+            ));
+        }
+
+        // The memory is not written when the step fails
+        if state.step.error.is_some() {
+            return Ok(());
+        }
+
+        let code = call_code(state, step)?;
+        copy_code_to_memory(state, step, &code, 0)
+    }
+}
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the
+/// [`OpcodeId::EXTCODESIZE`](crate::evm::OpcodeId::EXTCODESIZE) `OpcodeId`.
+/// This is responsible of generating all of the associated [`StackOp`]s,
+/// [`TxAccessListAccountOp`](crate::operation::TxAccessListAccountOp)s and
+/// [`AccountOp`]s and place them inside the trace's
+/// [`OperationContainer`](crate::operation::OperationContainer).
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Extcodesize;
+
+impl Opcode for Extcodesize {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        steps: &[GethExecStep],
+    ) -> Result<(), Error> {
+        let step = &steps[0];
+
+        // Stack read of the address
+        let address = step.stack.last()?;
+        state.push_op(StackOp::new(
+            RW::READ,
+            step.stack.last_filled(),
+            address,
+        ));
+
+        // The account is not accessed when the step fails
+        if state.step.error.is_some() {
+            return Ok(());
+        }
+
+        let address = address.to_address();
+        state.add_address_to_access_list(address)?;
+        let code = account_code(state, step, address)?;
+        let value = Word::from(code.len());
+        check_pushed_value(steps, value)?;
+
+        // Stack write of the code size
+        state.push_op(StackOp::new(RW::WRITE, step.stack.last_filled(), value));
+
+        Ok(())
+    }
+}
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the
+/// [`OpcodeId::EXTCODECOPY`](crate::evm::OpcodeId::EXTCODECOPY) `OpcodeId`.
+/// This is responsible of generating all of the associated [`StackOp`]s,
+/// [`TxAccessListAccountOp`](crate::operation::TxAccessListAccountOp)s,
+/// [`AccountOp`]s and [`MemoryOp`]s and place them inside the trace's
+/// [`OperationContainer`](crate::operation::OperationContainer).
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Extcodecopy;
+
+impl Opcode for Extcodecopy {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        steps: &[GethExecStep],
+    ) -> Result<(), Error> {
+        let step = &steps[0];
+
+        // Stack reads of the address, the memory offset, the code offset and
+        // the length
+        for i in 0..4 {
+            state.push_op(StackOp::new(
+                RW::READ,
+                step.stack.nth_last_filled(i),
+                step.stack.nth_last(i)?,
+            ));
+        }
+
+        // The memory is not written when the step fails
+        if state.step.error.is_some() {
+            return Ok(());
+        }
+
+        let address = step.stack.nth_last(0)?.to_address();
+        state.add_address_to_access_list(address)?;
+        let code = account_code(state, step, address)?;
+        copy_code_to_memory(state, step, &code, 1)
+    }
+}
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the
+/// [`OpcodeId::EXTCODEHASH`](crate::evm::OpcodeId::EXTCODEHASH) `OpcodeId`.
+/// This is responsible of generating all of the associated [`StackOp`]s,
+/// [`TxAccessListAccountOp`](crate::operation::TxAccessListAccountOp)s and
+/// [`AccountOp`]s and place them inside the trace's
+/// [`OperationContainer`](crate::operation::OperationContainer).
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Extcodehash;
+
+impl Opcode for Extcodehash {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        steps: &[GethExecStep],
+    ) -> Result<(), Error> {
+        let step = &steps[0];
+
+        // Stack read of the address
+        let address = step.stack.last()?;
+        state.push_op(StackOp::new(
+            RW::READ,
+            step.stack.last_filled(),
+            address,
+        ));
+
+        // The account is not accessed when the step fails
+        if state.step.error.is_some() {
+            return Ok(());
+        }
+
+        // Account read of the code hash.  The code hash of an empty account
+        // is zero (EIP-1052).
+        let address = address.to_address();
+        state.add_address_to_access_list(address)?;
+        read_code_hash(state, address);
+        let (_, account) = state.sdb.get_account(&address);
+        let value = if account.is_empty() {
+            Word::zero()
+        } else {
+            account.code_hash().to_word()
+        };
+        check_pushed_value(steps, value)?;
+
+        // Stack write of the code hash
+        state.push_op(StackOp::new(RW::WRITE, step.stack.last_filled(), value));
+
+        Ok(())
+    }
+}
+
+/// Return the code executed by the current call.
+fn call_code(
+    state: &CircuitInputStateRef,
+    step: &GethExecStep,
+) -> Result<Vec<u8>, Error> {
+    match state.sdb.get_code(&state.call().code_hash) {
+        (true, code) => Ok(code.to_vec()),
+        (false, _) => Err(Error::InvalidGethExecStep(
+            "code of the current call not found in the StateDB",
+            Box::new(step.clone()),
+        )),
+    }
+}
+
+/// Push the [`AccountOp`] read of the code hash of the account at `address`.
+fn read_code_hash(state: &mut CircuitInputStateRef, address: Address) {
+    let (_, account) = state.sdb.get_account(&address);
+    let code_hash = account.codeHash.to_word();
+    state.push_op(AccountOp::new(
+        RW::READ,
+        address,
+        AccountField::CodeHash,
+        code_hash,
+        code_hash,
+    ));
+}
+
+/// Return the code of the account at `address`, pushing the [`AccountOp`]
+/// read of its code hash.  Accounts that don't exist have an empty code.
+fn account_code(
+    state: &mut CircuitInputStateRef,
+    step: &GethExecStep,
+    address: Address,
+) -> Result<Vec<u8>, Error> {
+    read_code_hash(state, address);
+    match state.sdb.get_account_code(&address) {
+        (true, code) => Ok(code.to_vec()),
+        (false, _) => Err(Error::InvalidGethExecStep(
+            "code of the account not found in the StateDB",
+            Box::new(step.clone()),
+        )),
+    }
+}
+
+/// Push the [`MemoryOp`] writes of the `code` copied into memory, taking the
+/// memory offset, the code offset and the length from the stack starting at
+/// position `arg` from the top.  Bytes beyond the code end are copied as zero.
+fn copy_code_to_memory(
+    state: &mut CircuitInputStateRef,
+    step: &GethExecStep,
+    code: &[u8],
+    arg: usize,
+) -> Result<(), Error> {
+    let range = get_memory_range(
+        step.stack.nth_last(arg)?,
+        step.stack.nth_last(arg + 2)?,
+    )?;
+    let code_offset = step.stack.nth_last(arg + 1)?;
+    let code_offset = if code_offset.bits() > 64 {
+        usize::MAX
+    } else {
+        code_offset.low_u64() as usize
+    };
+
+    for (i, address) in range.enumerate() {
+        let byte = code_offset
+            .checked_add(i)
+            .and_then(|index| code.get(index))
+            .copied()
+            .unwrap_or(0);
+        state.push_op(MemoryOp::new(
+            RW::WRITE,
+            MemoryAddress::from(address),
+            byte,
+        ));
+    }
+
+    Ok(())
+}
+
+/// Check that the value pushed in the trace matches the computed one.
+fn check_pushed_value(
+    steps: &[GethExecStep],
+    value: Word,
+) -> Result<(), Error> {
+    if steps[1].stack.last()? != value {
+        return Err(Error::InvalidGethExecStep(
+            "pushed value doesn't match the code or account",
+            Box::new(steps[0].clone()),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod code_tests {
+    use super::*;
+    use crate::{
+        address, bytecode, bytecode::Bytecode,
+        circuit_input_builder::CircuitInputBuilder, eth_types::H256,
+        evm::StackAddress, mock, operation::Operation,
+        operation::TxAccessListAccountOp, state_db::Account,
+    };
+    use ethers_core::utils::keccak256;
+    use pretty_assertions::assert_eq;
+    use std::collections::HashMap;
+
+    // Set the account at `address` with the `code` in the StateDB, with the
+    // same balance as in `mock::new_tracer_account`.
+    fn set_account_code(
+        builder: &mut CircuitInputBuilder,
+        address: Address,
+        code: &Bytecode,
+    ) {
+        let code_hash = builder.sdb.set_code(code.to_bytes());
+        builder.sdb.set_account(
+            &address,
+            Account {
+                nonce: Word::zero(),
+                balance: Word::from(555u64),
+                storage: HashMap::new(),
+                codeHash: code_hash,
+            },
+        );
+    }
+
+    #[test]
+    fn codesize_codecopy_opcode_impl() -> Result<(), Error> {
+        let code = bytecode! {
+            CODESIZE
+            PUSH1(0x04u64) // length
+            PUSH1(0x07u64) // codeOffset
+            PUSH1(0x00u64) // memOffset
+            CODECOPY
+            STOP
+        };
+
+        // Get the execution steps from the external tracer
+        let block = mock::BlockData::new_single_tx_trace_code(&code).unwrap();
+
+        let mut builder = CircuitInputBuilder::new(
+            block.eth_block.clone(),
+            block.block_ctants.clone(),
+        );
+        set_account_code(&mut builder, Address::zero(), &code);
+        builder.handle_tx(&block.eth_tx, &block.geth_trace).unwrap();
+
+        assert_eq!(
+            builder.block.container.stack[0].op(),
+            &StackOp::new(RW::WRITE, StackAddress::from(1023), Word::from(9))
+        );
+        // The last 2 bytes of the copied range are beyond the code end
+        let memory_ops = [
+            MemoryOp::new(RW::WRITE, MemoryAddress::from(0x00), 0x39),
+            MemoryOp::new(RW::WRITE, MemoryAddress::from(0x01), 0x00),
+            MemoryOp::new(RW::WRITE, MemoryAddress::from(0x02), 0x00),
+            MemoryOp::new(RW::WRITE, MemoryAddress::from(0x03), 0x00),
+        ];
+        assert_eq!(
+            builder
+                .block
+                .container
+                .memory
+                .iter()
+                .map(Operation::op)
+                .collect::<Vec<_>>(),
+            memory_ops.iter().collect::<Vec<_>>()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn extcode_opcodes_impl() -> Result<(), Error> {
+        let addr_b = address!("0x0000000000000000000000000000000000000123");
+        let addr_empty = address!("0x0000000000000000000000000000000000000456");
+        let code_b = bytecode! {
+            PUSH1(0x01u64)
+            STOP
+        };
+        let code_a = bytecode! {
+            PUSH2(0x123u64)
+            EXTCODESIZE
+            PUSH2(0x123u64)
+            EXTCODEHASH
+            PUSH2(0x456u64)
+            EXTCODEHASH
+            PUSH1(0x03u64) // length
+            PUSH1(0x00u64) // codeOffset
+            PUSH1(0x00u64) // memOffset
+            PUSH2(0x123u64) // address
+            EXTCODECOPY
+            STOP
+        };
+
+        // Get the execution steps from the external tracer
+        let block =
+            mock::BlockData::new_single_tx_trace_code_2(&code_a, &code_b)
+                .unwrap();
+
+        let mut builder = CircuitInputBuilder::new(
+            block.eth_block.clone(),
+            block.block_ctants.clone(),
+        );
+        set_account_code(&mut builder, Address::zero(), &code_a);
+        set_account_code(&mut builder, addr_b, &code_b);
+        builder.handle_tx(&block.eth_tx, &block.geth_trace).unwrap();
+
+        let code_hash_b = H256(keccak256(&code_b.to_bytes())).to_word();
+        let account_ops = [
+            (addr_b, code_hash_b),
+            (addr_b, code_hash_b),
+            (addr_empty, Word::zero()),
+            (addr_b, code_hash_b),
+        ]
+        .map(|(address, code_hash)| {
+            AccountOp::new(
+                RW::READ,
+                address,
+                AccountField::CodeHash,
+                code_hash,
+                code_hash,
+            )
+        });
+        // The account operations of the BeginTx step come first
+        assert_eq!(
+            builder.block.container.account[5..9]
+                .iter()
+                .map(Operation::op)
+                .collect::<Vec<_>>(),
+            account_ops.iter().collect::<Vec<_>>()
+        );
+
+        // Only the first access to each account is cold, after the sender,
+        // the callee and the precompiles are warmed up in the BeginTx step
+        let access_list_ops = [
+            (addr_b, false),
+            (addr_b, true),
+            (addr_empty, false),
+            (addr_b, true),
+        ]
+        .map(|(address, is_warm)| {
+            TxAccessListAccountOp::new(RW::WRITE, 1, address, true, is_warm)
+        });
+        assert_eq!(
+            builder.block.container.tx_access_list_account[11..]
+                .iter()
+                .map(Operation::op)
+                .collect::<Vec<_>>(),
+            access_list_ops.iter().collect::<Vec<_>>()
+        );
+
+        // Stack writes of the code size, the code hash and the zero hash of
+        // the empty account
+        let stack_writes = builder
+            .block
+            .container
+            .stack
+            .iter()
+            .map(Operation::op)
+            .filter(|op| op.rw() == RW::WRITE)
+            .map(|op| *op.value())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            stack_writes[..6],
+            [
+                Word::from(0x123),
+                Word::from(3),
+                Word::from(0x123),
+                code_hash_b,
+                Word::from(0x456),
+                Word::zero(),
+            ]
+        );
+
+        // Memory writes of the copied code
+        let memory_ops = [
+            MemoryOp::new(RW::WRITE, MemoryAddress::from(0x00), 0x60),
+            MemoryOp::new(RW::WRITE, MemoryAddress::from(0x01), 0x01),
+            MemoryOp::new(RW::WRITE, MemoryAddress::from(0x02), 0x00),
+        ];
+        assert_eq!(
+            builder
+                .block
+                .container
+                .memory
+                .iter()
+                .map(Operation::op)
+                .collect::<Vec<_>>(),
+            memory_ops.iter().collect::<Vec<_>>()
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,450 @@
+use super::Opcode;
+use crate::circuit_input_builder::{
+    self, get_call_result, get_memory_bytes, get_memory_range, CallKind,
+    CircuitInputStateRef, ExecError,
+};
+use crate::eth_types::{GethExecStep, ToWord, Word};
+use crate::{
+    evm::MemoryAddress,
+    operation::{AccountField, AccountOp, MemoryOp, StackOp, RW},
+    Error,
+};
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the [`OpcodeId::CREATE`](crate::evm::OpcodeId::CREATE) and
+/// [`OpcodeId::CREATE2`](crate::evm::OpcodeId::CREATE2) `OpcodeId`s.
+/// This is responsible of generating all of the associated [`StackOp`]s,
+/// [`MemoryOp`]s,
+/// [`TxAccessListAccountOp`](crate::operation::TxAccessListAccountOp)s and
+/// [`AccountOp`]s and place them inside the trace's
+/// [`OperationContainer`](crate::operation::OperationContainer), and of
+/// pushing the new [`Call`](circuit_input_builder::Call) that executes the
+/// init code into the call stack.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Create;
+
+impl Opcode for Create {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        steps: &[GethExecStep],
+    ) -> Result<(), Error> {
+        let step = &steps[0];
+        let kind = CallKind::try_from(step.op)?;
+        let n_args = if kind == CallKind::Create2 { 4 } else { 3 };
+
+        // Stack reads of all the arguments
+        for i in 0..n_args {
+            state.push_op(StackOp::new(
+                RW::READ,
+                step.stack.nth_last_filled(i),
+                step.stack.nth_last(i)?,
+            ));
+        }
+
+        // The creation fails without reading the init code when the step
+        // fails, except on the errors that fail the creation before the init
+        // code is executed and push a zero result.
+        if !matches!(
+            state.step.error,
+            None | Some(
+                ExecError::Depth
+                    | ExecError::InsufficientBalance
+                    | ExecError::ContractAddressCollision
+            )
+        ) {
+            return Ok(());
+        }
+
+        let value = step.stack.nth_last(0)?;
+        let range =
+            get_memory_range(step.stack.nth_last(1)?, step.stack.nth_last(2)?)?;
+
+        // Memory reads of the init code.  Bytes beyond the current memory
+        // size are read as zero since the memory is expanded by the create.
+        let init_code = get_memory_bytes(step, range.clone());
+        for (address, byte) in range.zip(init_code.iter()) {
+            state.push_op(MemoryOp::new(
+                RW::READ,
+                MemoryAddress::from(address),
+                *byte,
+            ));
+        }
+
+        // The created address depends on the creator nonce, so it must be
+        // derived before the nonce is increased.
+        let address = match kind {
+            CallKind::Create => state.create_address()?,
+            _ => state.create2_address(step)?,
+        };
+
+        // Stack write of the created address, which is found in the first
+        // step after the create in the caller context.
+        let result = get_call_result(steps);
+        if let Some(result) = result {
+            state.push_op(StackOp::new(
+                RW::WRITE,
+                step.stack.nth_last_filled(n_args - 1),
+                result,
+            ));
+        }
+
+        // The return data of the previous sub-call is discarded, and it's
+        // replaced by the one of the init code when it reverts.
+        state.call_ctx_mut().return_data = Vec::new();
+
+        // The creation is aborted without increasing the creator nonce on
+        // depth or insufficient balance errors.
+        if matches!(
+            state.step.error,
+            Some(ExecError::Depth | ExecError::InsufficientBalance)
+        ) {
+            return Ok(());
+        }
+
+        // Increase the creator nonce
+        let creator_address = state.call().address;
+        let (_, creator) = state.sdb.get_account_mut(&creator_address);
+        let nonce_prev = creator.nonce;
+        creator.nonce = nonce_prev + 1;
+        state.push_op_reversible(AccountOp::new(
+            RW::WRITE,
+            creator_address,
+            AccountField::Nonce,
+            nonce_prev + 1,
+            nonce_prev,
+        ))?;
+
+        // Mark the created address as accessed in the transaction, which
+        // happens even on an address collision.
+        state.add_address_to_access_list(address)?;
+
+        // On an address collision the creation fails after increasing the
+        // creator nonce.
+        if state.step.error == Some(ExecError::ContractAddressCollision) {
+            return Ok(());
+        }
+
+        let is_executed = steps.get(1).map(|s| s.depth) == Some(step.depth + 1);
+        let is_success = result.map(|r| !r.is_zero()).unwrap_or(false);
+        if is_executed {
+            // Save the context of the creator, which is restored when the init
+            // code returns.
+            state.save_call_context(steps);
+
+            // Push the new call that executes the init code.  The deployed
+            // code is recorded when it returns successfully.
+            let code_hash = state.sdb.set_code(init_code);
+            let is_persistent = state.call().is_persistent && is_success;
+            let call_id = state.next_call_id();
+            state.push_call(circuit_input_builder::Call {
+                call_id,
+                kind,
+                is_static: false,
+                is_root: false,
+                caller_address: creator_address,
+                address,
+                code_address: address,
+                code_hash,
+                value,
+                call_data_offset: 0,
+                call_data_length: 0,
+                call_data: Vec::new(),
+                return_data_offset: 0,
+                return_data_length: 0,
+                is_success,
+                is_persistent,
+                rw_counter_end_of_reversion: 0,
+            })?;
+            state.init_call_context()?;
+        } else if !is_success {
+            return Ok(());
+        }
+
+        // The nonce of the created account starts at 1 (EIP-161) and it
+        // receives the endowment.  These writes belong to the new call, so
+        // they are reverted when the init code fails.
+        let (_, account) = state.sdb.get_account_mut(&address);
+        account.nonce = Word::one();
+        state.push_op_reversible(AccountOp::new(
+            RW::WRITE,
+            address,
+            AccountField::Nonce,
+            Word::one(),
+            Word::zero(),
+        ))?;
+        if !value.is_zero() {
+            state.transfer(creator_address, address, value)?;
+        }
+
+        // An empty init code is not executed and deploys an empty code.
+        if !is_executed {
+            state.deploy_code(address, Vec::new())?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod create_tests {
+    use super::*;
+    use crate::{
+        bytecode,
+        circuit_input_builder::{
+            CircuitInputBuilder, ExecStep, Transaction, TransactionContext,
+        },
+        eth_types::{Address, H256},
+        evm::{OpcodeId, StackAddress},
+        mock,
+        operation::{CallContextField, CallContextOp, TxAccessListAccountOp},
+        state_db::Account,
+    };
+    use ethers_core::utils::keccak256;
+    use pretty_assertions::assert_eq;
+    use std::collections::HashMap;
+
+    fn test_create_ops(op: OpcodeId) -> Result<(), Error> {
+        // Init code that returns without deploying any code
+        let init_code = bytecode! {
+            PUSH1(0x01u64)
+            STOP
+        };
+        let init_code_bytes = init_code.to_bytes();
+        let init_code_word = Word::from_big_endian(&init_code_bytes);
+        let init_code_offset = 32 - init_code_bytes.len();
+
+        let mut code = bytecode! {
+            PUSH32(init_code_word)
+            PUSH1(0x00u64)
+            MSTORE
+        };
+        if op == OpcodeId::CREATE2 {
+            code.push(1, Word::from(0x07u64)); // salt
+        }
+        code.push(1, Word::from(init_code_bytes.len())); // length
+        code.push(1, Word::from(init_code_offset)); // offset
+        code.push(1, Word::zero()); // value
+        code.add_marker("start".to_string());
+        code.write_op(op);
+        code.write_op(OpcodeId::STOP);
+
+        // Get the execution steps from the external tracer
+        let block =
+            mock::BlockData::new_single_tx_trace_code_at_start(&code).unwrap();
+        let creator = Address::zero();
+        // Same balance as in `mock::new_tracer_account`
+        let creator_account = || Account {
+            nonce: Word::zero(),
+            balance: Word::from(555u64),
+            storage: HashMap::new(),
+            codeHash: H256::zero(),
+        };
+
+        let mut builder = CircuitInputBuilder::new(
+            block.eth_block.clone(),
+            block.block_ctants.clone(),
+        );
+        builder.sdb.set_account(&creator, creator_account());
+        builder.handle_tx(&block.eth_tx, &block.geth_trace).unwrap();
+
+        let mut test_builder = CircuitInputBuilder::new(
+            block.eth_block,
+            block.block_ctants.clone(),
+        );
+        test_builder.sdb.set_account(&creator, creator_account());
+        let mut tx = Transaction::new(&block.eth_tx);
+        let mut tx_ctx = TransactionContext::new(&block.eth_tx);
+        test_builder
+            .begin_tx(&mut tx, &mut tx_ctx, &block.geth_trace)
+            .unwrap();
+
+        // Generate step corresponding to the CREATE/CREATE2
+        let mut step = ExecStep::new(
+            &block.geth_trace.struct_logs[0],
+            0,
+            test_builder.block_ctx.gc,
+            0,
+        );
+        let mut state_ref =
+            test_builder.state_ref(&mut tx, &mut tx_ctx, &mut step);
+        let address = match op {
+            OpcodeId::CREATE => state_ref.create_address()?,
+            _ => state_ref.create2_address(&block.geth_trace.struct_logs[0])?,
+        };
+        let mut args = vec![
+            Word::zero(),
+            Word::from(init_code_offset),
+            Word::from(init_code_bytes.len()),
+        ];
+        if op == OpcodeId::CREATE2 {
+            args.push(Word::from(0x07u64));
+        }
+        // Add StackOps associated to the stack pops.
+        for (i, arg) in args.iter().enumerate() {
+            state_ref.push_op(StackOp::new(
+                RW::READ,
+                StackAddress::from(1024 - args.len() + i),
+                *arg,
+            ));
+        }
+        // Add MemoryOps associated to the init code reads.
+        for (i, byte) in init_code_bytes.iter().enumerate() {
+            state_ref.push_op(MemoryOp::new(
+                RW::READ,
+                MemoryAddress::from(init_code_offset + i),
+                *byte,
+            ));
+        }
+        // Add StackOp associated to the created address push.
+        state_ref.push_op(StackOp::new(
+            RW::WRITE,
+            StackAddress::from(1023),
+            address.to_word(),
+        ));
+        // Add AccountOp associated to the creator nonce increase.
+        state_ref.push_op(AccountOp::new(
+            RW::WRITE,
+            creator,
+            AccountField::Nonce,
+            Word::one(),
+            Word::zero(),
+        ));
+        // Add TxAccessListAccountOp associated to the created address access.
+        state_ref.push_op(TxAccessListAccountOp::new(
+            RW::WRITE,
+            1,
+            address,
+            true,
+            false,
+        ));
+        // Add CallContextOps associated to the creator context save, which is
+        // the one found after the CREATE/CREATE2 except for the gas left.
+        let create_step = &block.geth_trace.struct_logs[0];
+        let next_step = block.geth_trace.struct_logs.last().unwrap();
+        let root_call_id = 1;
+        for (field, value) in [
+            (CallContextField::ProgramCounter, next_step.pc.0),
+            (CallContextField::StackPointer, 1023),
+            (
+                CallContextField::GasLeft,
+                (create_step.gas.0 - create_step.gas_cost.as_u64()) as usize,
+            ),
+            (CallContextField::MemorySize, 1),
+            // The value transfer of the transaction, the creator nonce and the
+            // created address access
+            (CallContextField::StateWriteCounter, 4),
+        ] {
+            state_ref.push_op(CallContextOp::new(
+                RW::WRITE,
+                root_call_id,
+                field,
+                Word::from(value),
+            ));
+        }
+        // Add CallContextOps associated to the init code call context.
+        let call_id = state_ref.next_call_id();
+        for (field, value) in [
+            (CallContextField::RwCounterEndOfReversion, Word::zero()),
+            (CallContextField::CallerCallId, Word::from(root_call_id)),
+            (CallContextField::TxId, Word::one()),
+            (CallContextField::Depth, Word::from(2)),
+            (CallContextField::CallerAddress, creator.to_word()),
+            (CallContextField::CalleeAddress, address.to_word()),
+            (CallContextField::CallDataOffset, Word::zero()),
+            (CallContextField::CallDataLength, Word::zero()),
+            (CallContextField::ReturnDataOffset, Word::zero()),
+            (CallContextField::ReturnDataLength, Word::zero()),
+            (CallContextField::Value, Word::zero()),
+            (CallContextField::Result, Word::one()),
+            (CallContextField::IsPersistent, Word::one()),
+            (CallContextField::IsStatic, Word::zero()),
+            (CallContextField::IsRoot, Word::zero()),
+            (CallContextField::IsCreate, Word::one()),
+            (
+                CallContextField::OpcodeSource,
+                H256(keccak256(&init_code_bytes)).to_word(),
+            ),
+        ] {
+            state_ref.push_op(CallContextOp::new(
+                RW::READ,
+                call_id,
+                field,
+                value,
+            ));
+        }
+        // Add AccountOp associated to the nonce of the created account, which
+        // is written in the new call.
+        state_ref.push_op(AccountOp::new(
+            RW::WRITE,
+            address,
+            AccountField::Nonce,
+            Word::one(),
+            Word::zero(),
+        ));
+        tx.steps_mut().push(step);
+        test_builder.block.txs_mut().push(tx);
+
+        assert_eq!(
+            builder.block.txs()[0].steps()[1].bus_mapping_instance,
+            test_builder.block.txs()[0].steps()[1].bus_mapping_instance
+        );
+        let n_stack_ops = test_builder.block.container.stack.len();
+        assert_eq!(
+            builder.block.container.stack[..n_stack_ops],
+            test_builder.block.container.stack
+        );
+        assert_eq!(
+            builder.block.container.memory,
+            test_builder.block.container.memory
+        );
+        let n_call_context_ops =
+            test_builder.block.container.call_context.len();
+        assert_eq!(
+            builder.block.container.call_context[..n_call_context_ops],
+            test_builder.block.container.call_context
+        );
+        // The created address matches the one returned by geth
+        assert_eq!(
+            block.geth_trace.struct_logs.last().unwrap().stack.last()?,
+            address.to_word()
+        );
+
+        // Check the context of the new call
+        let call = &builder.block.txs()[0].calls()[1];
+        assert_eq!(call.call_id, call_id);
+        assert_eq!(call.kind, CallKind::try_from(op)?);
+        assert_eq!(call.caller_address, creator);
+        assert_eq!(call.address, address);
+        assert_eq!(call.code_hash, H256(keccak256(&init_code_bytes)));
+
+        // Check the deployed empty code
+        let code_hash = H256(keccak256(&[0u8; 0]));
+        let (_, account) = builder.sdb.get_account(&address);
+        assert_eq!(account.nonce, Word::one());
+        assert_eq!(account.codeHash, code_hash);
+        let (_, creator_account) = builder.sdb.get_account(&creator);
+        assert_eq!(creator_account.nonce, Word::one());
+        // The first 5 account operations are the ones of the BeginTx step
+        assert_eq!(
+            builder.block.container.account[7].op(),
+            &AccountOp::new(
+                RW::WRITE,
+                address,
+                AccountField::CodeHash,
+                code_hash.to_word(),
+                Word::zero(),
+            )
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn create_opcode_impl() -> Result<(), Error> {
+        test_create_ops(OpcodeId::CREATE)
+    }
+
+    #[test]
+    fn create2_opcode_impl() -> Result<(), Error> {
+        test_create_ops(OpcodeId::CREATE2)
+    }
+}
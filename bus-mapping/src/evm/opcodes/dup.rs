@@ -18,6 +18,11 @@ impl<const N: usize> Opcode for Dup<N> {
     ) -> Result<(), Error> {
         let step = &steps[0];
 
+        // Nothing is pushed when the step fails
+        if state.step.error.is_some() {
+            return Ok(());
+        }
+
         let stack_value_read = step.stack.nth_last(N - 1)?;
         let stack_position = step.stack.nth_last_filled(N - 1);
         state.push_op(StackOp::new(RW::READ, stack_position, stack_value_read));
@@ -74,6 +79,9 @@ mod dup_tests {
         );
         let mut tx = Transaction::new(&block.eth_tx);
         let mut tx_ctx = TransactionContext::new(&block.eth_tx);
+        test_builder
+            .begin_tx(&mut tx, &mut tx_ctx, &block.geth_trace)
+            .unwrap();
 
         // Generate steps corresponding to DUP1, DUP3, DUP5
         for (i, word) in [word!("0x3"), word!("0x2"), word!("0x1")]
@@ -104,13 +112,16 @@ mod dup_tests {
             tx.steps_mut().push(step);
         }
 
+        test_builder
+            .end_tx(&mut tx, &mut tx_ctx, &block.geth_trace)
+            .unwrap();
         test_builder.block.txs_mut().push(tx);
 
         // Compare first 3 steps bus mapping instance
         for i in 0..3 {
             assert_eq!(
-                builder.block.txs()[0].steps()[i].bus_mapping_instance,
-                test_builder.block.txs()[0].steps()[i].bus_mapping_instance
+                builder.block.txs()[0].steps()[i + 1].bus_mapping_instance,
+                test_builder.block.txs()[0].steps()[i + 1].bus_mapping_instance
             );
         }
         // Compare containers
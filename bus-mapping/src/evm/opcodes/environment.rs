@@ -0,0 +1,261 @@
+use super::Opcode;
+use crate::circuit_input_builder::CircuitInputStateRef;
+use crate::eth_types::{GethExecStep, ToWord, Word};
+use crate::{
+    evm::OpcodeId,
+    operation::{CallContextField, StackOp, RW},
+    Error,
+};
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the opcodes that push a value of the call context
+/// ([`OpcodeId::ADDRESS`](crate::evm::OpcodeId::ADDRESS),
+/// [`OpcodeId::CALLER`](crate::evm::OpcodeId::CALLER),
+/// [`OpcodeId::CALLVALUE`](crate::evm::OpcodeId::CALLVALUE) and
+/// [`OpcodeId::SELFBALANCE`](crate::evm::OpcodeId::SELFBALANCE)), of the
+/// transaction context
+/// ([`OpcodeId::ORIGIN`](crate::evm::OpcodeId::ORIGIN) and
+/// [`OpcodeId::GASPRICE`](crate::evm::OpcodeId::GASPRICE)) and of the block
+/// context ([`OpcodeId::COINBASE`](crate::evm::OpcodeId::COINBASE),
+/// [`OpcodeId::TIMESTAMP`](crate::evm::OpcodeId::TIMESTAMP),
+/// [`OpcodeId::NUMBER`](crate::evm::OpcodeId::NUMBER),
+/// [`OpcodeId::DIFFICULTY`](crate::evm::OpcodeId::DIFFICULTY),
+/// [`OpcodeId::GASLIMIT`](crate::evm::OpcodeId::GASLIMIT),
+/// [`OpcodeId::CHAINID`](crate::evm::OpcodeId::CHAINID) and
+/// [`OpcodeId::BASEFEE`](crate::evm::OpcodeId::BASEFEE)).  The pushed value
+/// is taken from the context and checked against the one found in the trace,
+/// and the call context field it depends on is read with a
+/// [`CallContextOp`](crate::operation::CallContextOp).
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Environment;
+
+impl Opcode for Environment {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        steps: &[GethExecStep],
+    ) -> Result<(), Error> {
+        let step = &steps[0];
+
+        // Nothing is pushed when the step fails
+        if state.step.error.is_some() {
+            return Ok(());
+        }
+
+        let value = context_value(state, step)?;
+
+        // The value pushed in the trace must match the context one
+        if steps[1].stack.last()? != value {
+            return Err(Error::InvalidGethExecStep(
+                "Environment: pushed value doesn't match the context",
+                Box::new(step.clone()),
+            ));
+        }
+
+        // Call context read of the field the value depends on
+        if let Some((field, field_value)) = call_context_field(state, step.op) {
+            state.push_call_context_op(RW::READ, field, field_value);
+        }
+
+        // Stack write of the value
+        state.push_op(StackOp::new(
+            RW::WRITE,
+            step.stack.last_filled().map(|a| a - 1),
+            value,
+        ));
+
+        Ok(())
+    }
+}
+
+/// Return the field of the call context, with its value, that the value pushed
+/// by `op` depends on.
+fn call_context_field(
+    state: &CircuitInputStateRef,
+    op: OpcodeId,
+) -> Option<(CallContextField, Word)> {
+    let call = state.call();
+    Some(match op {
+        OpcodeId::ADDRESS | OpcodeId::SELFBALANCE => {
+            (CallContextField::CalleeAddress, call.address.to_word())
+        }
+        OpcodeId::CALLER => (
+            CallContextField::CallerAddress,
+            call.caller_address.to_word(),
+        ),
+        OpcodeId::CALLVALUE => (CallContextField::Value, call.value),
+        _ => return None,
+    })
+}
+
+/// Return the value of the call, transaction or block context that `step`
+/// pushes into the stack.
+fn context_value(
+    state: &CircuitInputStateRef,
+    step: &GethExecStep,
+) -> Result<Word, Error> {
+    let constants = &state.block.constants;
+    Ok(match step.op {
+        // Call context
+        OpcodeId::ADDRESS => state.call().address.to_word(),
+        OpcodeId::CALLER => state.call().caller_address.to_word(),
+        OpcodeId::CALLVALUE => state.call().value,
+        OpcodeId::SELFBALANCE => {
+            let address = state.call().address;
+            let (_, account) = state.sdb.get_account(&address);
+            account.balance
+        }
+        // Transaction context
+        OpcodeId::ORIGIN => state.tx.from.to_word(),
+        OpcodeId::GASPRICE => state.tx.gas_price,
+        // Block context
+        OpcodeId::COINBASE => constants.coinbase().to_word(),
+        OpcodeId::TIMESTAMP => *constants.timestamp(),
+        OpcodeId::NUMBER => Word::from(constants.number().as_u64()),
+        OpcodeId::DIFFICULTY => *constants.difficulty(),
+        OpcodeId::GASLIMIT => *constants.gas_limit(),
+        OpcodeId::CHAINID => *constants.chain_id(),
+        OpcodeId::BASEFEE => *constants.base_fee(),
+        _ => {
+            return Err(Error::InvalidGethExecStep(
+                "Environment: opcode doesn't push a context value",
+                Box::new(step.clone()),
+            ))
+        }
+    })
+}
+
+#[cfg(test)]
+mod environment_tests {
+    use super::*;
+    use crate::{
+        bytecode,
+        circuit_input_builder::CircuitInputBuilder,
+        eth_types::{Address, H256},
+        evm::StackAddress,
+        mock,
+        operation::{CallContextOp, Operation},
+        state_db::Account,
+        BlockConstants,
+    };
+    use pretty_assertions::assert_eq;
+    use std::collections::HashMap;
+
+    #[test]
+    fn environment_opcodes_impl() -> Result<(), Error> {
+        let code = bytecode! {
+            ADDRESS
+            ORIGIN
+            CALLER
+            CALLVALUE
+            GASPRICE
+            COINBASE
+            TIMESTAMP
+            NUMBER
+            DIFFICULTY
+            GASLIMIT
+            CHAINID
+            BASEFEE
+            SELFBALANCE
+            STOP
+        };
+
+        // Get the execution steps from the external tracer
+        let block = mock::BlockData::new_single_tx_trace_code(&code).unwrap();
+        let ctants = &block.block_ctants;
+        // The geth runtime uses the tx gas limit as the block gas limit
+        let gas_limit = block.eth_tx.gas;
+        let block_ctants = BlockConstants::new(
+            *ctants.hash(),
+            *ctants.coinbase(),
+            *ctants.timestamp(),
+            *ctants.number(),
+            *ctants.difficulty(),
+            gas_limit,
+            *ctants.chain_id(),
+            *ctants.base_fee(),
+            ctants.history_hashes().to_vec(),
+        );
+        let origin = block.eth_tx.from;
+
+        let mut builder =
+            CircuitInputBuilder::new(block.eth_block.clone(), block_ctants);
+        // Same balance as in `mock::new_tracer_account`
+        builder.sdb.set_account(
+            &Address::zero(),
+            Account {
+                nonce: Word::zero(),
+                balance: Word::from(555u64),
+                storage: HashMap::new(),
+                codeHash: H256::zero(),
+            },
+        );
+        builder.handle_tx(&block.eth_tx, &block.geth_trace).unwrap();
+
+        let values = [
+            Address::zero().to_word(),
+            origin.to_word(),
+            origin.to_word(),
+            Word::zero(),
+            Word::zero(),
+            ctants.coinbase().to_word(),
+            *ctants.timestamp(),
+            Word::from(ctants.number().as_u64()),
+            *ctants.difficulty(),
+            gas_limit,
+            *ctants.chain_id(),
+            *ctants.base_fee(),
+            Word::from(555u64),
+        ];
+        let stack_ops: Vec<StackOp> = values
+            .iter()
+            .enumerate()
+            .map(|(i, value)| {
+                StackOp::new(RW::WRITE, StackAddress::from(1023 - i), *value)
+            })
+            .collect();
+        assert_eq!(
+            builder
+                .block
+                .container
+                .stack
+                .iter()
+                .map(Operation::op)
+                .collect::<Vec<_>>(),
+            stack_ops.iter().collect::<Vec<_>>()
+        );
+        // The root call is the first one of the block, and its context is
+        // read after the 3 reads of the BeginTx step.
+        let call_context_ops = [
+            (CallContextField::CalleeAddress, Address::zero().to_word()),
+            (CallContextField::CallerAddress, origin.to_word()),
+            (CallContextField::Value, Word::zero()),
+            (CallContextField::CalleeAddress, Address::zero().to_word()),
+        ]
+        .map(|(field, value)| CallContextOp::new(RW::READ, 1, field, value));
+        assert_eq!(
+            builder.block.container.call_context[3..7]
+                .iter()
+                .map(Operation::op)
+                .collect::<Vec<_>>(),
+            call_context_ops.iter().collect::<Vec<_>>()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn environment_opcode_value_mismatch() {
+        let code = bytecode! {
+            GASLIMIT
+            STOP
+        };
+
+        // The block gas limit doesn't match the one used by the geth runtime
+        let block = mock::BlockData::new_single_tx_trace_code(&code).unwrap();
+        let mut builder = CircuitInputBuilder::new(
+            block.eth_block.clone(),
+            block.block_ctants.clone(),
+        );
+        assert!(builder.handle_tx(&block.eth_tx, &block.geth_trace).is_err());
+    }
+}
@@ -0,0 +1,85 @@
+use super::Opcode;
+use crate::circuit_input_builder::CircuitInputStateRef;
+use crate::eth_types::{GethExecStep, Word};
+use crate::{
+    operation::{StackOp, RW},
+    Error,
+};
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the [`OpcodeId::GAS`](crate::evm::OpcodeId::GAS)
+/// `OpcodeId`.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Gas;
+
+impl Opcode for Gas {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        steps: &[GethExecStep],
+    ) -> Result<(), Error> {
+        let step = &steps[0];
+
+        // Nothing is pushed when the step fails
+        if state.step.error.is_some() {
+            return Ok(());
+        }
+
+        // The pushed value is the gas left after paying for the GAS opcode
+        let value = Word::from(step.gas.0 - step.gas_cost.as_u64());
+        if value != steps[1].stack.last()? {
+            return Err(Error::InvalidGethExecStep(
+                "GAS: gas left mismatch",
+                Box::new(step.clone()),
+            ));
+        }
+        state.push_op(StackOp::new(
+            RW::WRITE,
+            step.stack.last_filled().map(|a| a - 1),
+            value,
+        ));
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod gas_tests {
+    use super::*;
+    use crate::{
+        bytecode, circuit_input_builder::CircuitInputBuilder,
+        evm::StackAddress, mock,
+    };
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn gas_opcode_impl() -> Result<(), Error> {
+        let code = bytecode! {
+            #[start]
+            GAS
+            STOP
+        };
+
+        // Get the execution steps from the external tracer
+        let block =
+            mock::BlockData::new_single_tx_trace_code_at_start(&code).unwrap();
+
+        let mut builder = CircuitInputBuilder::new(
+            block.eth_block.clone(),
+            block.block_ctants.clone(),
+        );
+        builder.handle_tx(&block.eth_tx, &block.geth_trace).unwrap();
+
+        // The gas left is pushed after paying the 2 gas of the GAS opcode
+        let step = &block.geth_trace.struct_logs[0];
+        assert_eq!(
+            builder.block.container.stack[0].op(),
+            &StackOp::new(
+                RW::WRITE,
+                StackAddress::from(1023),
+                Word::from(step.gas.0 - 2),
+            )
+        );
+
+        Ok(())
+    }
+}
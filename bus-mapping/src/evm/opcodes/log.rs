@@ -0,0 +1,244 @@
+use super::Opcode;
+use crate::circuit_input_builder::{
+    get_memory_bytes, get_memory_range, CircuitInputStateRef,
+};
+use crate::eth_types::GethExecStep;
+use crate::{
+    evm::MemoryAddress,
+    operation::{LogOp, MemoryOp, StackOp, RW},
+    Error,
+};
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the [`OpcodeId::LOG0`](crate::evm::OpcodeId::LOG0),
+/// [`OpcodeId::LOG1`](crate::evm::OpcodeId::LOG1),
+/// [`OpcodeId::LOG2`](crate::evm::OpcodeId::LOG2),
+/// [`OpcodeId::LOG3`](crate::evm::OpcodeId::LOG3) and
+/// [`OpcodeId::LOG4`](crate::evm::OpcodeId::LOG4) `OpcodeId`s, where `N` is
+/// the number of topics.  This is responsible of generating all of the
+/// associated [`StackOp`]s, [`MemoryOp`]s and the [`LogOp`] and place them
+/// inside the trace's
+/// [`OperationContainer`](crate::operation::OperationContainer).
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Log<const N: usize>;
+
+impl<const N: usize> Opcode for Log<N> {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        steps: &[GethExecStep],
+    ) -> Result<(), Error> {
+        let step = &steps[0];
+
+        // Stack reads of the memory range and the N topics
+        for i in 0..N + 2 {
+            state.push_op(StackOp::new(
+                RW::READ,
+                step.stack.nth_last_filled(i),
+                step.stack.nth_last(i)?,
+            ));
+        }
+
+        // The log is not emitted when the step fails
+        if state.step.error.is_some() {
+            return Ok(());
+        }
+
+        let range =
+            get_memory_range(step.stack.nth_last(0)?, step.stack.nth_last(1)?)?;
+        let topics = (0..N)
+            .map(|i| step.stack.nth_last(i + 2))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // Memory reads of the data.  Bytes beyond the current memory size are
+        // read as zero since the memory is expanded by the log.
+        let data = get_memory_bytes(step, range.clone());
+        for (address, byte) in range.zip(data.iter()) {
+            state.push_op(MemoryOp::new(
+                RW::READ,
+                MemoryAddress::from(address),
+                *byte,
+            ));
+        }
+
+        // Log write.  The log is not written to the state, so it's not
+        // reverted like a state write, but it's dropped from the receipt when
+        // the call is not persistent.
+        let index = state.block.container.log.len();
+        let call = state.call();
+        let (address, is_persistent) = (call.address, call.is_persistent);
+        state.push_op(LogOp::new(
+            RW::WRITE,
+            index,
+            address,
+            topics,
+            data,
+            is_persistent,
+        ));
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod log_tests {
+    use super::*;
+    use crate::{
+        address, bytecode,
+        circuit_input_builder::{
+            CircuitInputBuilder, ExecStep, Transaction, TransactionContext,
+        },
+        eth_types::{Address, Word},
+        evm::StackAddress,
+        mock,
+    };
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn log2_opcode_impl() -> Result<(), Error> {
+        let code = bytecode! {
+            // Store 0x1234 in memory so that it's used as log data
+            PUSH2(0x1234u64)
+            PUSH1(0x00u64)
+            MSTORE
+            PUSH1(0xbbu64) // topic 1
+            PUSH1(0xaau64) // topic 0
+            PUSH1(0x02u64) // length
+            PUSH1(0x1eu64) // offset
+            #[start]
+            LOG2
+            STOP
+        };
+
+        // Get the execution steps from the external tracer
+        let block =
+            mock::BlockData::new_single_tx_trace_code_at_start(&code).unwrap();
+
+        let mut builder = CircuitInputBuilder::new(
+            block.eth_block.clone(),
+            block.block_ctants.clone(),
+        );
+        builder.handle_tx(&block.eth_tx, &block.geth_trace).unwrap();
+
+        let mut test_builder = CircuitInputBuilder::new(
+            block.eth_block,
+            block.block_ctants.clone(),
+        );
+        let mut tx = Transaction::new(&block.eth_tx);
+        let mut tx_ctx = TransactionContext::new(&block.eth_tx);
+        test_builder
+            .begin_tx(&mut tx, &mut tx_ctx, &block.geth_trace)
+            .unwrap();
+
+        // Generate step corresponding to LOG2
+        let mut step = ExecStep::new(
+            &block.geth_trace.struct_logs[0],
+            0,
+            test_builder.block_ctx.gc,
+            0,
+        );
+        let mut state_ref =
+            test_builder.state_ref(&mut tx, &mut tx_ctx, &mut step);
+        // Add StackOps associated to the stack pops.
+        for (i, value) in [0x1eu64, 0x02, 0xaa, 0xbb].iter().enumerate() {
+            state_ref.push_op(StackOp::new(
+                RW::READ,
+                StackAddress::from(1020 + i),
+                Word::from(*value),
+            ));
+        }
+        // Add MemoryOps associated to the data reads.
+        state_ref.push_op(MemoryOp::new(
+            RW::READ,
+            MemoryAddress::from(0x1e),
+            0x12,
+        ));
+        state_ref.push_op(MemoryOp::new(
+            RW::READ,
+            MemoryAddress::from(0x1f),
+            0x34,
+        ));
+        // Add LogOp associated to the log write.
+        state_ref.push_op(LogOp::new(
+            RW::WRITE,
+            0,
+            Address::zero(),
+            vec![Word::from(0xaau64), Word::from(0xbbu64)],
+            vec![0x12, 0x34],
+            true,
+        ));
+        tx.steps_mut().push(step);
+        test_builder
+            .end_tx(&mut tx, &mut tx_ctx, &block.geth_trace)
+            .unwrap();
+        test_builder.block.txs_mut().push(tx);
+
+        assert_eq!(
+            builder.block.txs()[0].steps()[1].bus_mapping_instance,
+            test_builder.block.txs()[0].steps()[1].bus_mapping_instance
+        );
+        assert_eq!(builder.block.container, test_builder.block.container);
+
+        Ok(())
+    }
+
+    #[test]
+    fn log0_reverted_call() -> Result<(), Error> {
+        // Code that emits a log and reverts
+        let code_b = bytecode! {
+            PUSH1(0x00u64) // length
+            PUSH1(0x00u64) // offset
+            LOG0
+            PUSH1(0x00u64) // length
+            PUSH1(0x00u64) // offset
+            REVERT
+        };
+        // Code that calls the code at 0x123 and emits a log afterwards
+        let code_a = bytecode! {
+            PUSH1(0x00u64) // retLength
+            PUSH1(0x00u64) // retOffset
+            PUSH1(0x00u64) // argsLength
+            PUSH1(0x00u64) // argsOffset
+            PUSH1(0x00u64) // value
+            PUSH2(0x123u64) // addr
+            PUSH2(0xffffu64) // gas
+            CALL
+            PUSH1(0x00u64) // length
+            PUSH1(0x00u64) // offset
+            LOG0
+            STOP
+        };
+
+        // Get the execution steps from the external tracer
+        let block =
+            mock::BlockData::new_single_tx_trace_code_2(&code_a, &code_b)
+                .unwrap();
+
+        let mut builder = CircuitInputBuilder::new(
+            block.eth_block.clone(),
+            block.block_ctants.clone(),
+        );
+        builder.handle_tx(&block.eth_tx, &block.geth_trace).unwrap();
+
+        // The log of the reverted call is kept in the container, but it's
+        // marked as not persistent.
+        let logs = builder
+            .block
+            .container
+            .log
+            .iter()
+            .map(|log| (*log.op().address(), log.op().is_persistent()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            logs,
+            vec![
+                (
+                    address!("0x0000000000000000000000000000000000000123"),
+                    false
+                ),
+                (Address::zero(), true),
+            ]
+        );
+
+        Ok(())
+    }
+}
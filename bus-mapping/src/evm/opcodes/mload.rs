@@ -31,6 +31,11 @@ impl Opcode for Mload {
         // Manage first stack read at latest stack position
         state.push_op(StackOp::new(RW::READ, stack_position, stack_value_read));
 
+        // The memory is not read when the step fails
+        if state.step.error.is_some() {
+            return Ok(());
+        }
+
         // Read the memory
         let mut mem_read_addr: MemoryAddress = stack_value_read.try_into()?;
         let mem_read_value = steps[1].memory.read_word(mem_read_addr)?;
@@ -96,6 +101,9 @@ mod mload_tests {
         );
         let mut tx = Transaction::new(&block.eth_tx);
         let mut tx_ctx = TransactionContext::new(&block.eth_tx);
+        test_builder
+            .begin_tx(&mut tx, &mut tx_ctx, &block.geth_trace)
+            .unwrap();
 
         // Generate step corresponding to MLOAD
         let mut step = ExecStep::new(
@@ -133,12 +141,15 @@ mod mload_tests {
             });
 
         tx.steps_mut().push(step);
+        test_builder
+            .end_tx(&mut tx, &mut tx_ctx, &block.geth_trace)
+            .unwrap();
         test_builder.block.txs_mut().push(tx);
 
         // Compare first step bus mapping instance
         assert_eq!(
-            builder.block.txs()[0].steps()[0].bus_mapping_instance,
-            test_builder.block.txs()[0].steps()[0].bus_mapping_instance,
+            builder.block.txs()[0].steps()[1].bus_mapping_instance,
+            test_builder.block.txs()[0].steps()[1].bus_mapping_instance,
         );
 
         // Compare containers
@@ -10,11 +10,11 @@ use core::convert::TryInto;
 
 /// Placeholder structure used to implement [`Opcode`] trait over it
 /// corresponding to the [`OpcodeId::MSTORE`](crate::evm::OpcodeId::MSTORE)
-/// `OpcodeId`.
+/// and [`OpcodeId::MSTORE8`](crate::evm::OpcodeId::MSTORE8) `OpcodeId`s.
 #[derive(Debug, Copy, Clone)]
-pub(crate) struct Mstore;
+pub(crate) struct Mstore<const IS_MSTORE8: bool>;
 
-impl Opcode for Mstore {
+impl<const IS_MSTORE8: bool> Opcode for Mstore<IS_MSTORE8> {
     fn gen_associated_ops(
         state: &mut CircuitInputStateRef,
         steps: &[GethExecStep],
@@ -30,15 +30,29 @@ impl Opcode for Mstore {
         let value_pos = step.stack.nth_last_filled(1);
         state.push_op(StackOp::new(RW::READ, value_pos, value));
 
-        // First mem write -> 32 MemoryOp generated.
+        // The memory is not written when the step fails
+        if state.step.error.is_some() {
+            return Ok(());
+        }
+
         let offset_addr: MemoryAddress = offset.try_into()?;
-        let bytes = value.to_be_bytes();
-        for (i, byte) in bytes.iter().enumerate() {
+        if IS_MSTORE8 {
+            // Mem write of the least significant byte -> 1 MemoryOp generated.
             state.push_op(MemoryOp::new(
                 RW::WRITE,
-                offset_addr.map(|a| a + i),
-                *byte,
+                offset_addr,
+                value.low_u64() as u8,
             ));
+        } else {
+            // First mem write -> 32 MemoryOp generated.
+            let bytes = value.to_be_bytes();
+            for (i, byte) in bytes.iter().enumerate() {
+                state.push_op(MemoryOp::new(
+                    RW::WRITE,
+                    offset_addr.map(|a| a + i),
+                    *byte,
+                ));
+            }
         }
 
         Ok(())
@@ -86,6 +100,9 @@ mod mstore_tests {
         );
         let mut tx = Transaction::new(&block.eth_tx);
         let mut tx_ctx = TransactionContext::new(&block.eth_tx);
+        test_builder
+            .begin_tx(&mut tx, &mut tx_ctx, &block.geth_trace)
+            .unwrap();
 
         // Generate step corresponding to MSTORE
         let mut step = ExecStep::new(
@@ -121,12 +138,91 @@ mod mstore_tests {
         }
 
         tx.steps_mut().push(step);
+        test_builder
+            .end_tx(&mut tx, &mut tx_ctx, &block.geth_trace)
+            .unwrap();
+        test_builder.block.txs_mut().push(tx);
+
+        // Compare first step bus mapping instance
+        assert_eq!(
+            builder.block.txs()[0].steps()[1].bus_mapping_instance,
+            test_builder.block.txs()[0].steps()[1].bus_mapping_instance,
+        );
+
+        // Compare containers
+        assert_eq!(builder.block.container, test_builder.block.container);
+
+        Ok(())
+    }
+
+    #[test]
+    fn mstore8_opcode_impl() -> Result<(), Error> {
+        let code = bytecode! {
+            .setup_state()
+            PUSH2(0x1234)
+            PUSH2(0x100)
+            #[start]
+            MSTORE8
+            STOP
+        };
+
+        // Get the execution steps from the external tracer
+        let block =
+            mock::BlockData::new_single_tx_trace_code_at_start(&code).unwrap();
+
+        let mut builder = CircuitInputBuilder::new(
+            block.eth_block.clone(),
+            block.block_ctants.clone(),
+        );
+        builder.handle_tx(&block.eth_tx, &block.geth_trace).unwrap();
+
+        let mut test_builder = CircuitInputBuilder::new(
+            block.eth_block,
+            block.block_ctants.clone(),
+        );
+        let mut tx = Transaction::new(&block.eth_tx);
+        let mut tx_ctx = TransactionContext::new(&block.eth_tx);
+        test_builder
+            .begin_tx(&mut tx, &mut tx_ctx, &block.geth_trace)
+            .unwrap();
+
+        // Generate step corresponding to MSTORE8
+        let mut step = ExecStep::new(
+            &block.geth_trace.struct_logs[0],
+            0,
+            test_builder.block_ctx.gc,
+            0,
+        );
+        let mut state_ref =
+            test_builder.state_ref(&mut tx, &mut tx_ctx, &mut step);
+
+        // Add StackOps associated to the 0x100, 0x1234 reads starting from last
+        // stack position.
+        state_ref.push_op(StackOp::new(
+            RW::READ,
+            StackAddress::from(1022),
+            Word::from(0x100),
+        ));
+        state_ref.push_op(StackOp::new(
+            RW::READ,
+            StackAddress::from(1023),
+            Word::from(0x1234),
+        ));
+
+        // Add the MemoryOp generated from the Memory write of the least
+        // significant byte at addr 0x100.
+        state_ref.push_op(MemoryOp::new(RW::WRITE, MemoryAddress(0x100), 0x34));
+
+        tx.steps_mut().push(step);
+        test_builder
+            .end_tx(&mut tx, &mut tx_ctx, &block.geth_trace)
+            .unwrap();
         test_builder.block.txs_mut().push(tx);
 
         // Compare first step bus mapping instance
         assert_eq!(
-            builder.block.txs()[0].steps()[0].bus_mapping_instance,
-            test_builder.block.txs()[0].steps()[0].bus_mapping_instance,
+            builder.block.txs()[0].steps()[1].bus_mapping_instance,
+            test_builder.block.txs()[0].steps()[1].bus_mapping_instance,
         );
 
         // Compare containers
@@ -17,6 +17,12 @@ impl Opcode for Pc {
         steps: &[GethExecStep],
     ) -> Result<(), Error> {
         let step = &steps[0];
+
+        // Nothing is pushed when the step fails
+        if state.step.error.is_some() {
+            return Ok(());
+        }
+
         // Get value result from next step and do stack write
         let value = steps[1].stack.last()?;
         state.push_op(StackOp::new(
@@ -69,6 +75,9 @@ mod pc_tests {
         );
         let mut tx = Transaction::new(&block.eth_tx);
         let mut tx_ctx = TransactionContext::new(&block.eth_tx);
+        test_builder
+            .begin_tx(&mut tx, &mut tx_ctx, &block.geth_trace)
+            .unwrap();
 
         // Generate step corresponding to MLOAD
         let mut step = ExecStep::new(
@@ -88,12 +97,15 @@ mod pc_tests {
         ));
 
         tx.steps_mut().push(step);
+        test_builder
+            .end_tx(&mut tx, &mut tx_ctx, &block.geth_trace)
+            .unwrap();
         test_builder.block.txs_mut().push(tx);
 
         // Compare first step bus mapping instance
         assert_eq!(
-            builder.block.txs()[0].steps()[0].bus_mapping_instance,
-            test_builder.block.txs()[0].steps()[0].bus_mapping_instance,
+            builder.block.txs()[0].steps()[1].bus_mapping_instance,
+            test_builder.block.txs()[0].steps()[1].bus_mapping_instance,
         );
 
         // Compare containers
@@ -20,6 +20,12 @@ impl<const N: usize> Opcode for Push<N> {
         steps: &[GethExecStep],
     ) -> Result<(), Error> {
         let step = &steps[0];
+
+        // Nothing is pushed when the step fails
+        if state.step.error.is_some() {
+            return Ok(());
+        }
+
         state.push_op(StackOp::new(
             RW::WRITE,
             // Get the value and addr from the next step. Being the last
@@ -71,6 +77,9 @@ mod push_tests {
         );
         let mut tx = Transaction::new(&block.eth_tx);
         let mut tx_ctx = TransactionContext::new(&block.eth_tx);
+        test_builder
+            .begin_tx(&mut tx, &mut tx_ctx, &block.geth_trace)
+            .unwrap();
 
         // Generate steps corresponding to PUSH1 80, PUSH2 1234,
         // PUSH16 0x00112233445566778899aabbccddeeff
@@ -100,13 +109,16 @@ mod push_tests {
             tx.steps_mut().push(step);
         }
 
+        test_builder
+            .end_tx(&mut tx, &mut tx_ctx, &block.geth_trace)
+            .unwrap();
         test_builder.block.txs_mut().push(tx);
 
         // Compare first 3 steps bus mapping instance
         for i in 0..3 {
             assert_eq!(
-                builder.block.txs()[0].steps()[i].bus_mapping_instance,
-                test_builder.block.txs()[0].steps()[i].bus_mapping_instance
+                builder.block.txs()[0].steps()[i + 1].bus_mapping_instance,
+                test_builder.block.txs()[0].steps()[i + 1].bus_mapping_instance
             );
         }
         // Compare containers
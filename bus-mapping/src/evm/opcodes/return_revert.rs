@@ -0,0 +1,218 @@
+use super::Opcode;
+use crate::circuit_input_builder::{
+    get_return_data, CircuitInputStateRef, ExecError,
+};
+use crate::eth_types::{GethExecStep, Word};
+use crate::{
+    evm::MemoryAddress,
+    operation::{CallContextField, MemoryOp, StackOp, RW},
+    Error,
+};
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the [`OpcodeId::RETURN`](crate::evm::OpcodeId::RETURN) and
+/// [`OpcodeId::REVERT`](crate::evm::OpcodeId::REVERT) `OpcodeId`s.  This is
+/// responsible of generating all of the associated [`StackOp`]s and
+/// [`MemoryOp`]s and place them inside the trace's
+/// [`OperationContainer`](crate::operation::OperationContainer): the returned
+/// memory range is read and, when returning from a *CALL*, copied into the
+/// output memory region of the caller, which is read from the call context.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct ReturnRevert;
+
+impl Opcode for ReturnRevert {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        steps: &[GethExecStep],
+    ) -> Result<(), Error> {
+        let step = &steps[0];
+
+        // Stack reads of the memory range
+        for i in 0..2 {
+            state.push_op(StackOp::new(
+                RW::READ,
+                step.stack.nth_last_filled(i),
+                step.stack.nth_last(i)?,
+            ));
+        }
+
+        // REVERT fails the call after reading the returned data like RETURN,
+        // but nothing else is read when the step itself fails.
+        if !matches!(
+            state.step.error,
+            None | Some(ExecError::ExecutionReverted)
+        ) {
+            return Ok(());
+        }
+
+        // Memory reads of the returned data
+        let offset = step.stack.nth_last(0)?.low_u64() as usize;
+        let data = get_return_data(step)?;
+        for (i, byte) in data.iter().enumerate() {
+            state.push_op(MemoryOp::new(
+                RW::READ,
+                MemoryAddress::from(offset + i),
+                *byte,
+            ));
+        }
+
+        // Memory writes of the returned data into the output memory region of
+        // the caller, truncated to its length.  The return data buffer of the
+        // caller is set when the call returns.
+        let call = state.call();
+        if call.is_root || call.is_create() {
+            return Ok(());
+        }
+        let return_data_offset = call.return_data_offset;
+        let return_data_length = call.return_data_length;
+        for (field, value) in [
+            (CallContextField::ReturnDataOffset, return_data_offset),
+            (CallContextField::ReturnDataLength, return_data_length),
+        ] {
+            state.push_call_context_op(RW::READ, field, Word::from(value));
+        }
+        let return_data_offset = return_data_offset as usize;
+        let length = data.len().min(return_data_length as usize);
+        for (i, byte) in data[..length].iter().enumerate() {
+            state.push_op(MemoryOp::new(
+                RW::WRITE,
+                MemoryAddress::from(return_data_offset + i),
+                *byte,
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod return_revert_tests {
+    use super::*;
+    use crate::{
+        bytecode,
+        bytecode::Bytecode,
+        circuit_input_builder::CircuitInputBuilder,
+        evm::{OpcodeId, StackAddress},
+        mock,
+        operation::{CallContextOp, Operation},
+    };
+    use pretty_assertions::assert_eq;
+
+    // Code that calls the code at 0x123 with an output memory region of 2
+    // bytes at 0x00.
+    fn code_caller() -> Bytecode {
+        bytecode! {
+            PUSH1(0x02u64) // retLength
+            PUSH1(0x00u64) // retOffset
+            PUSH1(0x00u64) // argsLength
+            PUSH1(0x00u64) // argsOffset
+            PUSH1(0x00u64) // value
+            PUSH2(0x123u64) // addr
+            PUSH2(0xffffu64) // gas
+            CALL
+            STOP
+        }
+    }
+
+    fn test_return_revert_ops(op: OpcodeId) -> Result<(), Error> {
+        // Code that stores a value, and returns or reverts with 0xdeadbeef
+        let mut code_b = bytecode! {
+            PUSH1(0x01u64)
+            PUSH1(0x00u64)
+            SSTORE
+            PUSH4(0xdeadbeefu64)
+            PUSH1(0x00u64)
+            MSTORE
+            PUSH1(0x04u64) // length
+            PUSH1(0x1cu64) // offset
+        };
+        code_b.write_op(op);
+
+        // Get the execution steps from the external tracer
+        let block = mock::BlockData::new_single_tx_trace_code_2(
+            &code_caller(),
+            &code_b,
+        )
+        .unwrap();
+
+        let mut builder = CircuitInputBuilder::new(
+            block.eth_block.clone(),
+            block.block_ctants.clone(),
+        );
+        builder.handle_tx(&block.eth_tx, &block.geth_trace).unwrap();
+
+        // The returned data is read and copied to the caller memory.  The
+        // preceding memory ops are the MSTORE ones.
+        let memory_ops = [
+            MemoryOp::new(RW::READ, MemoryAddress::from(0x1c), 0xde),
+            MemoryOp::new(RW::READ, MemoryAddress::from(0x1d), 0xad),
+            MemoryOp::new(RW::READ, MemoryAddress::from(0x1e), 0xbe),
+            MemoryOp::new(RW::READ, MemoryAddress::from(0x1f), 0xef),
+            MemoryOp::new(RW::WRITE, MemoryAddress::from(0x00), 0xde),
+            MemoryOp::new(RW::WRITE, MemoryAddress::from(0x01), 0xad),
+        ];
+        assert_eq!(
+            builder.block.container.memory[32..]
+                .iter()
+                .map(Operation::op)
+                .collect::<Vec<_>>(),
+            memory_ops.iter().collect::<Vec<_>>()
+        );
+        // The stack reads of the memory range are the last stack ops, since the
+        // result of the CALL is written when the CALL is handled.
+        let stack_ops = [
+            StackOp::new(RW::READ, StackAddress::from(1023), Word::from(0x1c)),
+            StackOp::new(RW::READ, StackAddress::from(1022), Word::from(0x04)),
+        ];
+        let n_stack_ops = builder.block.container.stack.len();
+        assert_eq!(
+            builder.block.container.stack[n_stack_ops - 2..]
+                .iter()
+                .map(Operation::op)
+                .collect::<Vec<_>>(),
+            stack_ops.iter().collect::<Vec<_>>()
+        );
+        // The output memory region is read from the callee context, whose ops
+        // come after the 3 ops of the BeginTx step, the 5 ops saving the
+        // caller context and the 17 ops initializing the callee context.
+        let call_id = builder.block.txs()[0].calls()[1].call_id;
+        let call_context_ops = [
+            (CallContextField::ReturnDataOffset, Word::zero()),
+            (CallContextField::ReturnDataLength, Word::from(0x02)),
+        ]
+        .map(|(field, value)| {
+            CallContextOp::new(RW::READ, call_id, field, value)
+        });
+        assert_eq!(
+            builder.block.container.call_context[25..27]
+                .iter()
+                .map(Operation::op)
+                .collect::<Vec<_>>(),
+            call_context_ops.iter().collect::<Vec<_>>()
+        );
+
+        // Only the steps of the callee are reverted on REVERT
+        let steps = builder.block.txs()[0].steps();
+        for step in steps.iter() {
+            let in_callee = step.call_index == 1;
+            assert_eq!(
+                step.reverted,
+                in_callee && op == OpcodeId::REVERT,
+                "{:?}",
+                step
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn return_opcode_impl() -> Result<(), Error> {
+        test_return_revert_ops(OpcodeId::RETURN)
+    }
+
+    #[test]
+    fn revert_opcode_impl() -> Result<(), Error> {
+        test_return_revert_ops(OpcodeId::REVERT)
+    }
+}
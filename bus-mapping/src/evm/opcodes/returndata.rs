@@ -0,0 +1,246 @@
+use super::Opcode;
+use crate::circuit_input_builder::{get_memory_range, CircuitInputStateRef};
+use crate::eth_types::{GethExecStep, Word};
+use crate::{
+    evm::MemoryAddress,
+    operation::{MemoryOp, StackOp, RW},
+    Error,
+};
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the
+/// [`OpcodeId::RETURNDATASIZE`](crate::evm::OpcodeId::RETURNDATASIZE)
+/// `OpcodeId`.  This is responsible of generating the associated [`StackOp`]
+/// and place it inside the trace's
+/// [`OperationContainer`](crate::operation::OperationContainer).
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Returndatasize;
+
+impl Opcode for Returndatasize {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        steps: &[GethExecStep],
+    ) -> Result<(), Error> {
+        let step = &steps[0];
+
+        // Nothing is pushed when the step fails
+        if state.step.error.is_some() {
+            return Ok(());
+        }
+
+        let value = Word::from(state.call_ctx().return_data.len());
+        if steps[1].stack.last()? != value {
+            return Err(Error::InvalidGethExecStep(
+                "RETURNDATASIZE: pushed value doesn't match the return data",
+                Box::new(step.clone()),
+            ));
+        }
+
+        // Stack write of the return data length
+        state.push_op(StackOp::new(
+            RW::WRITE,
+            step.stack.last_filled().map(|a| a - 1),
+            value,
+        ));
+
+        Ok(())
+    }
+}
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the
+/// [`OpcodeId::RETURNDATACOPY`](crate::evm::OpcodeId::RETURNDATACOPY)
+/// `OpcodeId`.  This is responsible of generating all of the associated
+/// [`StackOp`]s and [`MemoryOp`]s and place them inside the trace's
+/// [`OperationContainer`](crate::operation::OperationContainer).
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Returndatacopy;
+
+impl Opcode for Returndatacopy {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        steps: &[GethExecStep],
+    ) -> Result<(), Error> {
+        let step = &steps[0];
+
+        // Stack reads of the memory offset, the return data offset and the
+        // length
+        for i in 0..3 {
+            state.push_op(StackOp::new(
+                RW::READ,
+                step.stack.nth_last_filled(i),
+                step.stack.nth_last(i)?,
+            ));
+        }
+
+        // The memory is not written when the step fails, including when the
+        // copied range is out of the return data bounds.
+        if state.step.error.is_some() {
+            return Ok(());
+        }
+
+        // The return data range is within bounds, but the memory offset can be
+        // arbitrarily large when nothing is copied.
+        let range =
+            get_memory_range(step.stack.nth_last(0)?, step.stack.nth_last(2)?)?;
+        let data_offset = step.stack.nth_last(1)?.as_usize();
+
+        // Memory writes of the copied return data
+        let data = state.call_ctx().return_data
+            [data_offset..data_offset + range.len()]
+            .to_vec();
+        for (address, byte) in range.zip(data.iter()) {
+            state.push_op(MemoryOp::new(
+                RW::WRITE,
+                MemoryAddress::from(address),
+                *byte,
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod returndata_tests {
+    use super::*;
+    use crate::{
+        bytecode,
+        circuit_input_builder::{CircuitInputBuilder, ExecError, ExecState},
+        evm::{OpcodeId, StackAddress},
+        mock,
+        operation::{Operation, Target},
+    };
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn returndatasize_returndatacopy_opcode_impl() -> Result<(), Error> {
+        // Code that returns 0xdeadbeef
+        let code_b = bytecode! {
+            PUSH4(0xdeadbeefu64)
+            PUSH1(0x00u64)
+            MSTORE
+            PUSH1(0x04u64) // length
+            PUSH1(0x1cu64) // offset
+            RETURN
+        };
+        // Code that calls the code at 0x123 and copies 2 bytes of its return
+        // data from offset 1.
+        let code_a = bytecode! {
+            PUSH1(0x00u64) // retLength
+            PUSH1(0x00u64) // retOffset
+            PUSH1(0x00u64) // argsLength
+            PUSH1(0x00u64) // argsOffset
+            PUSH1(0x00u64) // value
+            PUSH2(0x123u64) // addr
+            PUSH2(0xffffu64) // gas
+            CALL
+            RETURNDATASIZE
+            PUSH1(0x02u64) // length
+            PUSH1(0x01u64) // dataOffset
+            PUSH1(0x00u64) // memOffset
+            RETURNDATACOPY
+            STOP
+        };
+
+        // Get the execution steps from the external tracer
+        let block =
+            mock::BlockData::new_single_tx_trace_code_2(&code_a, &code_b)
+                .unwrap();
+
+        let mut builder = CircuitInputBuilder::new(
+            block.eth_block.clone(),
+            block.block_ctants.clone(),
+        );
+        builder.handle_tx(&block.eth_tx, &block.geth_trace).unwrap();
+
+        let steps = builder.block.txs()[0].steps();
+        let step = steps
+            .iter()
+            .find(|step| {
+                step.exec_state == ExecState::Op(OpcodeId::RETURNDATASIZE)
+            })
+            .unwrap();
+        // The index of an `OperationRef` is the length of the container after
+        // inserting the operation.
+        let op_ref = step.bus_mapping_instance[0];
+        assert_eq!(
+            builder.block.container.stack[op_ref.as_usize() - 1].op(),
+            &StackOp::new(RW::WRITE, StackAddress::from(1022), Word::from(4))
+        );
+
+        // The preceding memory ops are the ones of the MSTORE and the RETURN
+        // in the sub-call.
+        let memory_ops = [
+            MemoryOp::new(RW::WRITE, MemoryAddress::from(0x00), 0xad),
+            MemoryOp::new(RW::WRITE, MemoryAddress::from(0x01), 0xbe),
+        ];
+        assert_eq!(
+            builder.block.container.memory[36..]
+                .iter()
+                .map(Operation::op)
+                .collect::<Vec<_>>(),
+            memory_ops.iter().collect::<Vec<_>>()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn returndatacopy_out_of_bounds() -> Result<(), Error> {
+        // Code that returns 1 byte
+        let code_b = bytecode! {
+            PUSH1(0x01u64) // length
+            PUSH1(0x00u64) // offset
+            RETURN
+        };
+        // Code that calls the code at 0x123 and copies 2 bytes of its return
+        // data, which is out of bounds.
+        let code_a = bytecode! {
+            PUSH1(0x00u64) // retLength
+            PUSH1(0x00u64) // retOffset
+            PUSH1(0x00u64) // argsLength
+            PUSH1(0x00u64) // argsOffset
+            PUSH1(0x00u64) // value
+            PUSH2(0x123u64) // addr
+            PUSH2(0xffffu64) // gas
+            CALL
+            PUSH1(0x02u64) // length
+            PUSH1(0x00u64) // dataOffset
+            PUSH1(0x00u64) // memOffset
+            RETURNDATACOPY
+            STOP
+        };
+
+        // Get the execution steps from the external tracer
+        let block =
+            mock::BlockData::new_single_tx_trace_code_2(&code_a, &code_b)
+                .unwrap();
+
+        let mut builder = CircuitInputBuilder::new(
+            block.eth_block.clone(),
+            block.block_ctants.clone(),
+        );
+        builder.handle_tx(&block.eth_tx, &block.geth_trace).unwrap();
+
+        // The error isn't reported by geth but found by the builder, and only
+        // the stack reads are done, before the reversion of the call.
+        let step = builder.block.txs()[0]
+            .steps()
+            .iter()
+            .find(|step| {
+                step.exec_state == ExecState::Op(OpcodeId::RETURNDATACOPY)
+            })
+            .unwrap();
+        assert_eq!(step.error, Some(ExecError::ReturnDataOutOfBounds));
+        assert!(step.bus_mapping_instance[..3]
+            .iter()
+            .all(|op_ref| op_ref.target() == Target::Stack));
+        assert!(step
+            .bus_mapping_instance
+            .iter()
+            .all(|op_ref| op_ref.target() != Target::Memory));
+
+        Ok(())
+    }
+}
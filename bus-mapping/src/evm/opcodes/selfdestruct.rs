@@ -0,0 +1,177 @@
+use super::Opcode;
+use crate::circuit_input_builder::CircuitInputStateRef;
+use crate::eth_types::{GethExecStep, ToAddress, Word};
+use crate::{
+    operation::{AccountDestructedOp, AccountField, AccountOp, StackOp, RW},
+    Error,
+};
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the
+/// [`OpcodeId::SELFDESTRUCT`](crate::evm::OpcodeId::SELFDESTRUCT) `OpcodeId`.
+/// This is responsible of generating all of the associated [`StackOp`]s,
+/// [`TxAccessListAccountOp`](crate::operation::TxAccessListAccountOp)s,
+/// [`AccountOp`]s and [`AccountDestructedOp`]s and place them inside the
+/// trace's [`OperationContainer`](crate::operation::OperationContainer), and
+/// of moving the balance to the beneficiary and marking the account as
+/// destructed in the [`StateDB`](crate::state_db::StateDB).
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Selfdestruct;
+
+impl Opcode for Selfdestruct {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        steps: &[GethExecStep],
+    ) -> Result<(), Error> {
+        let step = &steps[0];
+
+        // Stack read of the beneficiary
+        let beneficiary = step.stack.last()?;
+        state.push_op(StackOp::new(
+            RW::READ,
+            step.stack.last_filled(),
+            beneficiary,
+        ));
+
+        if state.step.error.is_some() {
+            return Ok(());
+        }
+
+        // Mark the beneficiary as accessed in the transaction
+        let beneficiary = beneficiary.to_address();
+        state.add_address_to_access_list(beneficiary)?;
+
+        let address = state.call().address;
+        let (_, account) = state.sdb.get_account(&address);
+        let balance = account.balance;
+
+        // The balance is added to the beneficiary and then the balance of the
+        // destructed account is set to zero, so it's burnt when the
+        // beneficiary is the destructed account itself.
+        let (_, beneficiary_account) = state.sdb.get_account_mut(&beneficiary);
+        let beneficiary_balance_prev = beneficiary_account.balance;
+        beneficiary_account.balance = beneficiary_balance_prev + balance;
+        state.push_op_reversible(AccountOp::new(
+            RW::WRITE,
+            beneficiary,
+            AccountField::Balance,
+            beneficiary_balance_prev + balance,
+            beneficiary_balance_prev,
+        ))?;
+        let (_, account) = state.sdb.get_account_mut(&address);
+        let balance_prev = account.balance;
+        account.balance = Word::zero();
+        state.push_op_reversible(AccountOp::new(
+            RW::WRITE,
+            address,
+            AccountField::Balance,
+            Word::zero(),
+            balance_prev,
+        ))?;
+
+        // Mark the account as destructed.  EIP-3529 removed the SELFDESTRUCT
+        // refund, so the refund counter is not changed.
+        let destructed_prev = state.sdb.destruct_account(&address);
+        state.push_op_reversible(AccountDestructedOp::new(
+            RW::WRITE,
+            address,
+            true,
+            destructed_prev,
+        ))?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod selfdestruct_tests {
+    use super::*;
+    use crate::{
+        address, bytecode,
+        circuit_input_builder::CircuitInputBuilder,
+        eth_types::{Address, H256},
+        mock,
+        operation::Operation,
+        state_db::Account,
+    };
+    use pretty_assertions::assert_eq;
+    use std::collections::HashMap;
+
+    #[test]
+    fn selfdestruct_opcode_impl() -> Result<(), Error> {
+        let beneficiary =
+            address!("0x0000000000000000000000000000000000000123");
+        let code = bytecode! {
+            PUSH2(0x123u64)
+            SELFDESTRUCT
+        };
+
+        // Get the execution steps from the external tracer
+        let block = mock::BlockData::new_single_tx_trace_code(&code).unwrap();
+
+        let mut builder = CircuitInputBuilder::new(
+            block.eth_block.clone(),
+            block.block_ctants.clone(),
+        );
+        // Same balance as in `mock::new_tracer_account`
+        builder.sdb.set_account(
+            &Address::zero(),
+            Account {
+                nonce: Word::zero(),
+                balance: Word::from(555u64),
+                storage: HashMap::new(),
+                codeHash: H256::zero(),
+            },
+        );
+        builder.handle_tx(&block.eth_tx, &block.geth_trace).unwrap();
+
+        let account_ops = [
+            AccountOp::new(
+                RW::WRITE,
+                beneficiary,
+                AccountField::Balance,
+                Word::from(555u64),
+                Word::zero(),
+            ),
+            AccountOp::new(
+                RW::WRITE,
+                Address::zero(),
+                AccountField::Balance,
+                Word::zero(),
+                Word::from(555u64),
+            ),
+        ];
+        // The account operations of the BeginTx step come first
+        assert_eq!(
+            builder.block.container.account[5..7]
+                .iter()
+                .map(Operation::op)
+                .collect::<Vec<_>>(),
+            account_ops.iter().collect::<Vec<_>>()
+        );
+        assert_eq!(
+            builder
+                .block
+                .container
+                .account_destructed
+                .iter()
+                .map(Operation::op)
+                .collect::<Vec<_>>(),
+            vec![&AccountDestructedOp::new(
+                RW::WRITE,
+                Address::zero(),
+                true,
+                false
+            )]
+        );
+
+        // The destructed account is removed at the end of the transaction
+        let (found, _) = builder.sdb.get_account(&Address::zero());
+        assert!(!found);
+        let (_, account) = builder.sdb.get_account(&beneficiary);
+        assert_eq!(account.balance, Word::from(555u64));
+        assert_eq!(builder.sdb.refund(), 0);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,169 @@
+use super::Opcode;
+use crate::circuit_input_builder::{
+    get_memory_bytes, get_memory_range, CircuitInputStateRef,
+};
+use crate::eth_types::{GethExecStep, ToWord, H256};
+use crate::{
+    evm::MemoryAddress,
+    operation::{MemoryOp, StackOp, RW},
+    Error,
+};
+use ethers_core::utils::keccak256;
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the [`OpcodeId::SHA3`](crate::evm::OpcodeId::SHA3)
+/// `OpcodeId`.  This is responsible of generating all of the associated
+/// [`StackOp`]s and [`MemoryOp`]s and place them inside the trace's
+/// [`OperationContainer`](crate::operation::OperationContainer), and of
+/// recording the hashed input in the keccak inputs of the
+/// [`Block`](crate::circuit_input_builder::Block).
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Sha3;
+
+impl Opcode for Sha3 {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        steps: &[GethExecStep],
+    ) -> Result<(), Error> {
+        let step = &steps[0];
+
+        // Stack reads of the memory offset and the length
+        for i in 0..2 {
+            state.push_op(StackOp::new(
+                RW::READ,
+                step.stack.nth_last_filled(i),
+                step.stack.nth_last(i)?,
+            ));
+        }
+
+        if state.step.error.is_some() {
+            return Ok(());
+        }
+
+        let range =
+            get_memory_range(step.stack.nth_last(0)?, step.stack.nth_last(1)?)?;
+
+        // Memory reads of the hashed input.  Bytes beyond the current memory
+        // size are read as zero since the memory is expanded by the SHA3.
+        let input = get_memory_bytes(step, range.clone());
+        for (address, byte) in range.zip(input.iter()) {
+            state.push_op(MemoryOp::new(
+                RW::READ,
+                MemoryAddress::from(address),
+                *byte,
+            ));
+        }
+
+        let digest = H256(keccak256(&input));
+        if steps[1].stack.last()? != digest.to_word() {
+            return Err(Error::InvalidGethExecStep(
+                "SHA3: pushed value doesn't match the digest",
+                Box::new(step.clone()),
+            ));
+        }
+        state.block.keccak_inputs.push((input, digest));
+
+        // Stack write of the digest
+        state.push_op(StackOp::new(
+            RW::WRITE,
+            step.stack.nth_last_filled(1),
+            digest.to_word(),
+        ));
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod sha3_tests {
+    use super::*;
+    use crate::{
+        bytecode, circuit_input_builder::CircuitInputBuilder, eth_types::Word,
+        evm::StackAddress, mock, operation::Operation,
+    };
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn sha3_opcode_impl() -> Result<(), Error> {
+        let code = bytecode! {
+            PUSH4(0xdeadbeefu64)
+            PUSH1(0x00u64)
+            MSTORE
+            PUSH1(0x04u64) // length
+            PUSH1(0x1cu64) // offset
+            SHA3
+            STOP
+        };
+
+        // Get the execution steps from the external tracer
+        let block = mock::BlockData::new_single_tx_trace_code(&code).unwrap();
+
+        let mut builder = CircuitInputBuilder::new(
+            block.eth_block.clone(),
+            block.block_ctants.clone(),
+        );
+        builder.handle_tx(&block.eth_tx, &block.geth_trace).unwrap();
+
+        let input = vec![0xde, 0xad, 0xbe, 0xef];
+        let digest = H256(keccak256(&input));
+        assert_eq!(builder.block.keccak_inputs, vec![(input.clone(), digest)]);
+
+        // The preceding stack ops are the ones of the pushes and the MSTORE
+        let stack_ops = [
+            StackOp::new(RW::READ, StackAddress::from(1023), Word::from(0x1c)),
+            StackOp::new(RW::READ, StackAddress::from(1022), Word::from(0x04)),
+            StackOp::new(RW::WRITE, StackAddress::from(1022), digest.to_word()),
+        ];
+        assert_eq!(
+            builder.block.container.stack[6..]
+                .iter()
+                .map(Operation::op)
+                .collect::<Vec<_>>(),
+            stack_ops.iter().collect::<Vec<_>>()
+        );
+        // The preceding memory ops are the ones of the MSTORE
+        let memory_ops: Vec<MemoryOp> = input
+            .iter()
+            .enumerate()
+            .map(|(i, byte)| {
+                MemoryOp::new(RW::READ, MemoryAddress::from(0x1c + i), *byte)
+            })
+            .collect();
+        assert_eq!(
+            builder.block.container.memory[32..]
+                .iter()
+                .map(Operation::op)
+                .collect::<Vec<_>>(),
+            memory_ops.iter().collect::<Vec<_>>()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn sha3_empty_input_any_offset() -> Result<(), Error> {
+        let code = bytecode! {
+            PUSH1(0x00u64) // length
+            PUSH32(Word::one() << 255) // offset
+            SHA3
+            STOP
+        };
+
+        // Get the execution steps from the external tracer
+        let block = mock::BlockData::new_single_tx_trace_code(&code).unwrap();
+
+        let mut builder = CircuitInputBuilder::new(
+            block.eth_block.clone(),
+            block.block_ctants.clone(),
+        );
+        builder.handle_tx(&block.eth_tx, &block.geth_trace).unwrap();
+
+        // Nothing is read from memory when the input is empty
+        let input: Vec<u8> = vec![];
+        let digest = H256(keccak256(&input));
+        assert_eq!(builder.block.keccak_inputs, vec![(input, digest)]);
+        assert!(builder.block.container.memory.is_empty());
+
+        Ok(())
+    }
+}
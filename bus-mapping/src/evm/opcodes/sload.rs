@@ -8,7 +8,11 @@ use crate::{
 
 /// Placeholder structure used to implement [`Opcode`] trait over it
 /// corresponding to the [`OpcodeId::SLOAD`](crate::evm::OpcodeId::SLOAD)
-/// `OpcodeId`.
+/// `OpcodeId`.  This is responsible of generating all of the associated
+/// [`StackOp`]s, [`StorageOp`]s and
+/// [`TxAccessListStorageSlotOp`](crate::operation::TxAccessListStorageSlotOp)s
+/// and place them inside the trace's
+/// [`OperationContainer`](crate::operation::OperationContainer).
 #[derive(Debug, Copy, Clone)]
 pub(crate) struct Sload;
 
@@ -26,16 +30,25 @@ impl Opcode for Sload {
         // Manage first stack read at latest stack position
         state.push_op(StackOp::new(RW::READ, stack_position, stack_value_read));
 
+        // The storage is not read when the step fails
+        if state.step.error.is_some() {
+            return Ok(());
+        }
+
         // Storage read
+        let address = state.call().address;
         let storage_value_read = step.storage.get_or_err(&stack_value_read)?;
         state.push_op(StorageOp::new(
             RW::READ,
-            state.call().address,
+            address,
             stack_value_read,
             storage_value_read,
             storage_value_read,
         ));
 
+        // Mark the storage slot as accessed in the transaction
+        state.add_storage_slot_to_access_list(address, stack_value_read)?;
+
         // First stack write
         state.push_op(StackOp::new(
             RW::WRITE,
@@ -53,11 +66,13 @@ mod sload_tests {
     use crate::{
         bytecode,
         circuit_input_builder::{
-            CircuitInputBuilder, ExecStep, Transaction, TransactionContext,
+            CircuitInputBuilder, ExecError, ExecState, ExecStep, Transaction,
+            TransactionContext,
         },
         eth_types::{Address, Word},
-        evm::StackAddress,
+        evm::{Gas, OpcodeId, StackAddress},
         mock,
+        operation::{Target, TxAccessListStorageSlotOp},
     };
     use pretty_assertions::assert_eq;
 
@@ -92,6 +107,9 @@ mod sload_tests {
         );
         let mut tx = Transaction::new(&block.eth_tx);
         let mut tx_ctx = TransactionContext::new(&block.eth_tx);
+        test_builder
+            .begin_tx(&mut tx, &mut tx_ctx, &block.geth_trace)
+            .unwrap();
 
         // Generate step corresponding to SLOAD
         let mut step = ExecStep::new(
@@ -116,6 +134,15 @@ mod sload_tests {
             Word::from(0x6fu32),
             Word::from(0x6fu32),
         ));
+        // Add TxAccessListStorageSlotOp associated to the cold slot access.
+        state_ref.push_op(TxAccessListStorageSlotOp::new(
+            RW::WRITE,
+            1,
+            Address::from([0u8; 20]),
+            Word::from(0x0u32),
+            true,
+            false,
+        ));
         // Add StackOp associated to the stack push.
         state_ref.push_op(StackOp::new(
             RW::WRITE,
@@ -123,14 +150,53 @@ mod sload_tests {
             Word::from(0x6fu32),
         ));
         tx.steps_mut().push(step);
+        test_builder
+            .end_tx(&mut tx, &mut tx_ctx, &block.geth_trace)
+            .unwrap();
         test_builder.block.txs_mut().push(tx);
 
         assert_eq!(
-            builder.block.txs()[0].steps()[0].bus_mapping_instance,
-            test_builder.block.txs()[0].steps()[0].bus_mapping_instance
+            builder.block.txs()[0].steps()[1].bus_mapping_instance,
+            test_builder.block.txs()[0].steps()[1].bus_mapping_instance
         );
         assert_eq!(builder.block.container, test_builder.block.container);
 
         Ok(())
     }
+
+    #[test]
+    fn sload_out_of_gas() -> Result<(), Error> {
+        let code = bytecode! {
+            PUSH1(0x00u64)
+            SLOAD
+            STOP
+        };
+
+        // The gas left after the PUSH1 is lower than the cold SLOAD cost
+        let block =
+            mock::BlockData::new_single_tx_trace_code_gas(&code, Gas(103))
+                .unwrap();
+
+        let mut builder = CircuitInputBuilder::new(
+            block.eth_block.clone(),
+            block.block_ctants.clone(),
+        );
+        builder.handle_tx(&block.eth_tx, &block.geth_trace).unwrap();
+
+        // Only the stack read is done, and the slot isn't marked as accessed.
+        let step = builder.block.txs()[0]
+            .steps()
+            .iter()
+            .find(|step| step.exec_state == ExecState::Op(OpcodeId::SLOAD))
+            .unwrap();
+        assert!(matches!(step.error, Some(ExecError::OutOfGas(_))));
+        assert_eq!(step.bus_mapping_instance[0].target(), Target::Stack);
+        assert!(step.bus_mapping_instance.iter().all(|op_ref| !matches!(
+            op_ref.target(),
+            Target::Storage | Target::TxAccessListStorageSlot
+        )));
+        assert!(builder.block.container.storage.is_empty());
+
+        Ok(())
+    }
 }
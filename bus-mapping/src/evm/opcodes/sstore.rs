@@ -0,0 +1,208 @@
+use super::Opcode;
+use crate::circuit_input_builder::CircuitInputStateRef;
+use crate::eth_types::GethExecStep;
+use crate::{
+    evm::gas::sstore_gas_cost_and_refund,
+    operation::{StackOp, StorageOp, RW},
+    Error,
+};
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the [`OpcodeId::SSTORE`](crate::evm::OpcodeId::SSTORE)
+/// `OpcodeId`.  This is responsible of generating all of the associated
+/// [`StackOp`]s, [`StorageOp`]s,
+/// [`TxAccessListStorageSlotOp`](crate::operation::TxAccessListStorageSlotOp)s
+/// and [`TxRefundOp`](crate::operation::TxRefundOp)s and place them inside
+/// the trace's [`OperationContainer`](crate::operation::OperationContainer),
+/// and of
+/// updating the storage and the refund counter of the
+/// [`StateDB`](crate::state_db::StateDB).
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Sstore;
+
+impl Opcode for Sstore {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        steps: &[GethExecStep],
+    ) -> Result<(), Error> {
+        let step = &steps[0];
+
+        // Stack reads of the key and the value
+        let key = step.stack.nth_last(0)?;
+        let value = step.stack.nth_last(1)?;
+        state.push_op(StackOp::new(
+            RW::READ,
+            step.stack.nth_last_filled(0),
+            key,
+        ));
+        state.push_op(StackOp::new(
+            RW::READ,
+            step.stack.nth_last_filled(1),
+            value,
+        ));
+
+        // The storage is not written when the step fails
+        if state.step.error.is_some() {
+            return Ok(());
+        }
+
+        // Mark the storage slot as accessed in the transaction
+        let address = state.call().address;
+        state.add_storage_slot_to_access_list(address, key)?;
+
+        // Storage write
+        let (_, committed_value) =
+            state.sdb.get_committed_storage(&address, &key);
+        let committed_value = *committed_value;
+        let value_prev = state.sdb.set_storage(&address, &key, value);
+        state.push_op_reversible(StorageOp::new(
+            RW::WRITE,
+            address,
+            key,
+            value,
+            value_prev,
+        ))?;
+
+        // Refund accounting
+        let (_, refund) =
+            sstore_gas_cost_and_refund(committed_value, value_prev, value);
+        let refund_prev = state.sdb.refund();
+        if refund >= 0 {
+            state.sdb.add_refund(refund as u64);
+        } else {
+            state.sdb.sub_refund(-refund as u64)?;
+        }
+        state.push_refund_op(refund_prev)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod sstore_tests {
+    use super::*;
+    use crate::{
+        bytecode,
+        circuit_input_builder::{CircuitInputBuilder, ExecState},
+        eth_types::{Address, Word},
+        evm::{OpcodeId, StackAddress},
+        mock,
+        operation::{Operation, TxAccessListStorageSlotOp, TxRefundOp},
+    };
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn sstore_opcode_impl() -> Result<(), Error> {
+        let code = bytecode! {
+            // Write 0x6f to storage slot 0
+            PUSH1(0x6fu64)
+            PUSH1(0x00u64)
+            SSTORE
+
+            // Restore storage slot 0 to its original value
+            PUSH1(0x00u64)
+            PUSH1(0x00u64)
+            SSTORE
+            STOP
+        };
+
+        // Get the execution steps from the external tracer
+        let block = mock::BlockData::new_single_tx_trace_code(&code).unwrap();
+
+        let mut builder = CircuitInputBuilder::new(
+            block.eth_block.clone(),
+            block.block_ctants.clone(),
+        );
+        builder.handle_tx(&block.eth_tx, &block.geth_trace).unwrap();
+
+        let steps = builder.block.txs()[0].steps();
+        assert_eq!(steps[3].exec_state, ExecState::Op(OpcodeId::SSTORE));
+        assert_eq!(steps[6].exec_state, ExecState::Op(OpcodeId::SSTORE));
+        // The first SSTORE sees the value transfer of the BeginTx step, and
+        // the second one also sees the state writes of the first one: the
+        // slot access, the storage write and the refund update.
+        assert_eq!(steps[3].swc, 2);
+        assert_eq!(steps[6].swc, 5);
+
+        let storage_ops = [
+            StorageOp::new(
+                RW::WRITE,
+                Address::zero(),
+                Word::zero(),
+                Word::from(0x6fu64),
+                Word::zero(),
+            ),
+            StorageOp::new(
+                RW::WRITE,
+                Address::zero(),
+                Word::zero(),
+                Word::zero(),
+                Word::from(0x6fu64),
+            ),
+        ];
+        assert_eq!(
+            builder
+                .block
+                .container
+                .storage
+                .iter()
+                .map(Operation::op)
+                .collect::<Vec<_>>(),
+            storage_ops.iter().collect::<Vec<_>>()
+        );
+        let stack_ops = [
+            StackOp::new(RW::READ, StackAddress::from(1022), Word::zero()),
+            StackOp::new(RW::READ, StackAddress::from(1023), Word::from(0x6f)),
+        ];
+        assert_eq!(
+            builder.block.container.stack[2..4]
+                .iter()
+                .map(Operation::op)
+                .collect::<Vec<_>>(),
+            stack_ops.iter().collect::<Vec<_>>()
+        );
+
+        // The slot is cold in the first SSTORE and warm in the second one
+        let access_list_ops = [false, true].map(|is_warm| {
+            TxAccessListStorageSlotOp::new(
+                RW::WRITE,
+                1,
+                Address::zero(),
+                Word::zero(),
+                true,
+                is_warm,
+            )
+        });
+        assert_eq!(
+            builder
+                .block
+                .container
+                .tx_access_list_storage_slot
+                .iter()
+                .map(Operation::op)
+                .collect::<Vec<_>>(),
+            access_list_ops.iter().collect::<Vec<_>>()
+        );
+
+        // Restoring the original zero value refunds the slot set
+        assert_eq!(builder.sdb.refund(), 19900);
+        // The refund counter is read at the EndTx step
+        let refund_ops = [
+            TxRefundOp::new(RW::WRITE, 1, 0, 0),
+            TxRefundOp::new(RW::WRITE, 1, 19900, 0),
+            TxRefundOp::new(RW::READ, 1, 19900, 19900),
+        ];
+        assert_eq!(
+            builder
+                .block
+                .container
+                .tx_refund
+                .iter()
+                .map(Operation::op)
+                .collect::<Vec<_>>(),
+            refund_ops.iter().collect::<Vec<_>>()
+        );
+
+        Ok(())
+    }
+}
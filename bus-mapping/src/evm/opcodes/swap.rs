@@ -34,6 +34,11 @@ impl<const N: usize> Opcode for Swap<N> {
             stack_a_value_read,
         ));
 
+        // The stack is not written when the step fails
+        if state.step.error.is_some() {
+            return Ok(());
+        }
+
         // Write a into b_position, write b into a_position
         state.push_op(StackOp::new(
             RW::WRITE,
@@ -96,6 +101,9 @@ mod swap_tests {
         );
         let mut tx = Transaction::new(&block.eth_tx);
         let mut tx_ctx = TransactionContext::new(&block.eth_tx);
+        test_builder
+            .begin_tx(&mut tx, &mut tx_ctx, &block.geth_trace)
+            .unwrap();
 
         // Generate steps corresponding to DUP1, DUP3, DUP5
         for (i, (a, b)) in [(6, 5), (5, 3), (3, 1)].iter().enumerate() {
@@ -121,13 +129,16 @@ mod swap_tests {
             tx.steps_mut().push(step);
         }
 
+        test_builder
+            .end_tx(&mut tx, &mut tx_ctx, &block.geth_trace)
+            .unwrap();
         test_builder.block.txs_mut().push(tx);
 
         // Compare first 3 steps bus mapping instance
         for i in 0..3 {
             assert_eq!(
-                builder.block.txs()[0].steps()[i].bus_mapping_instance,
-                test_builder.block.txs()[0].steps()[i].bus_mapping_instance
+                builder.block.txs()[0].steps()[i + 1].bus_mapping_instance,
+                test_builder.block.txs()[0].steps()[i + 1].bus_mapping_instance
             );
         }
         // Compare containers
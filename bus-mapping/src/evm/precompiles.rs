@@ -0,0 +1,490 @@
+//! Native implementation of the precompiled contracts of the EVM, which are
+//! executed without any step in the geth traces.
+use crate::eth_types::{Address, Word, H256};
+use bn::{AffineG1, AffineG2, Fq, Fq2, Fr, Group, Gt, G1, G2};
+use ethers_core::types::Signature;
+use num_bigint::BigUint;
+use ripemd160::Ripemd160;
+use sha2::{Digest, Sha256};
+
+/// Precompiled contract deployed at an address from 0x01 to 0x09.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Precompile {
+    /// Recovery of the address of the signer of a hash (0x01)
+    EcRecover = 0x01,
+    /// SHA-256 hash (0x02)
+    Sha256 = 0x02,
+    /// RIPEMD-160 hash (0x03)
+    Ripemd160 = 0x03,
+    /// Identity function, which copies the input to the output (0x04)
+    Identity = 0x04,
+    /// Modular exponentiation (0x05, EIP-198)
+    ModExp = 0x05,
+    /// Addition on the alt_bn128 curve (0x06, EIP-196)
+    Bn256Add = 0x06,
+    /// Scalar multiplication on the alt_bn128 curve (0x07, EIP-196)
+    Bn256ScalarMul = 0x07,
+    /// Pairing check on the alt_bn128 curve (0x08, EIP-197)
+    Bn256Pairing = 0x08,
+    /// BLAKE2 compression function F (0x09, EIP-152)
+    Blake2F = 0x09,
+}
+
+impl Precompile {
+    /// All the precompiled contracts, sorted by address.
+    pub const ALL: [Precompile; 9] = [
+        Precompile::EcRecover,
+        Precompile::Sha256,
+        Precompile::Ripemd160,
+        Precompile::Identity,
+        Precompile::ModExp,
+        Precompile::Bn256Add,
+        Precompile::Bn256ScalarMul,
+        Precompile::Bn256Pairing,
+        Precompile::Blake2F,
+    ];
+
+    /// Return the precompiled contract deployed at `address`, if any.
+    pub fn from_address(address: &Address) -> Option<Self> {
+        let bytes = address.as_bytes();
+        if bytes[..19].iter().any(|byte| *byte != 0) {
+            return None;
+        }
+        Self::ALL
+            .iter()
+            .find(|precompile| **precompile as u8 == bytes[19])
+            .copied()
+    }
+
+    /// Return the address where this precompiled contract is deployed.
+    pub fn address(&self) -> Address {
+        Address::from_low_u64_be(*self as u64)
+    }
+
+    /// Return the gas cost of executing this precompiled contract with
+    /// `input`.  The execution fails when the call has less gas.
+    pub fn gas_cost(&self, input: &[u8]) -> u64 {
+        let words = (input.len() as u64 + 31) / 32;
+        match self {
+            Precompile::EcRecover => 3000,
+            Precompile::Sha256 => 60 + 12 * words,
+            Precompile::Ripemd160 => 600 + 120 * words,
+            Precompile::Identity => 15 + 3 * words,
+            Precompile::ModExp => modexp_gas_cost(input),
+            Precompile::Bn256Add => 150,
+            Precompile::Bn256ScalarMul => 6000,
+            Precompile::Bn256Pairing => {
+                45000 + 34000 * (input.len() as u64 / 192)
+            }
+            Precompile::Blake2F => {
+                if input.len() == 213 {
+                    u32::from_be_bytes(input[..4].try_into().unwrap()) as u64
+                } else {
+                    0
+                }
+            }
+        }
+    }
+
+    /// Execute this precompiled contract with `input`, returning its output,
+    /// or `None` when the execution fails due to an invalid input.
+    pub fn execute(&self, input: &[u8]) -> Option<Vec<u8>> {
+        match self {
+            Precompile::EcRecover => Some(ecrecover(input)),
+            Precompile::Sha256 => Some(Sha256::digest(input).to_vec()),
+            Precompile::Ripemd160 => {
+                let mut output = vec![0u8; 12];
+                output.extend_from_slice(&Ripemd160::digest(input));
+                Some(output)
+            }
+            Precompile::Identity => Some(input.to_vec()),
+            Precompile::ModExp => Some(modexp(input)),
+            Precompile::Bn256Add => bn256_add(input),
+            Precompile::Bn256ScalarMul => bn256_scalar_mul(input),
+            Precompile::Bn256Pairing => bn256_pairing(input),
+            Precompile::Blake2F => blake2f(input),
+        }
+    }
+}
+
+/// Return `len` bytes of `input` from `offset`, padded with zeros at the end
+/// when the input is shorter.
+fn padded_input(input: &[u8], offset: usize, len: usize) -> Vec<u8> {
+    let mut bytes = vec![0u8; len];
+    if offset < input.len() {
+        let end = input.len().min(offset.saturating_add(len));
+        bytes[..end - offset].copy_from_slice(&input[offset..end]);
+    }
+    bytes
+}
+
+/// Return the address that signed the hash, left padded to 32 bytes, or an
+/// empty output when the signature is invalid.
+fn ecrecover(input: &[u8]) -> Vec<u8> {
+    let input = padded_input(input, 0, 128);
+    let hash = H256::from_slice(&input[..32]);
+    let v = Word::from_big_endian(&input[32..64]);
+    let r = Word::from_big_endian(&input[64..96]);
+    let s = Word::from_big_endian(&input[96..128]);
+    if v != Word::from(27) && v != Word::from(28) {
+        return Vec::new();
+    }
+    let signature = Signature {
+        r,
+        s,
+        v: v.as_u64(),
+    };
+    match signature.recover(hash) {
+        Ok(address) => {
+            let mut output = vec![0u8; 12];
+            output.extend_from_slice(address.as_bytes());
+            output
+        }
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Return the lengths of the base, the exponent and the modulus of a modular
+/// exponentiation, which are saturated when they don't fit in a `usize`.
+fn modexp_lengths(input: &[u8]) -> (usize, usize, usize) {
+    let length = |offset| {
+        let length = Word::from_big_endian(&padded_input(input, offset, 32));
+        if length > Word::from(usize::MAX) {
+            usize::MAX
+        } else {
+            length.as_usize()
+        }
+    };
+    (length(0), length(32), length(64))
+}
+
+/// Return the gas cost of a modular exponentiation (EIP-2565).
+fn modexp_gas_cost(input: &[u8]) -> u64 {
+    let (base_len, exp_len, mod_len) = modexp_lengths(input);
+    let max_len = base_len.max(mod_len) as u128;
+    let words = (max_len + 7) / 8;
+    let multiplication_complexity = words.saturating_mul(words);
+
+    // The iteration count depends on the bit length of the first 32 bytes of
+    // the exponent.
+    let exp_head = Word::from_big_endian(&padded_input(
+        input,
+        96usize.saturating_add(base_len),
+        exp_len.min(32),
+    ));
+    let exp_head_bits = exp_head.bits() as u128;
+    let iteration_count = if exp_len <= 32 {
+        exp_head_bits.saturating_sub(1)
+    } else {
+        (8 * (exp_len as u128 - 32))
+            .saturating_add(exp_head_bits.saturating_sub(1))
+    }
+    .max(1);
+
+    let gas = multiplication_complexity.saturating_mul(iteration_count) / 3;
+    gas.max(200).min(u64::MAX as u128) as u64
+}
+
+/// Return `base ** exp % mod` with the length of the modulus.
+fn modexp(input: &[u8]) -> Vec<u8> {
+    let (base_len, exp_len, mod_len) = modexp_lengths(input);
+    let exp_offset = 96usize.saturating_add(base_len);
+    let mod_offset = exp_offset.saturating_add(exp_len);
+    let base = BigUint::from_bytes_be(&padded_input(input, 96, base_len));
+    let exp = BigUint::from_bytes_be(&padded_input(input, exp_offset, exp_len));
+    let modulus =
+        BigUint::from_bytes_be(&padded_input(input, mod_offset, mod_len));
+    let mut output = vec![0u8; mod_len];
+    if modulus == BigUint::from(0u8) {
+        return output;
+    }
+    let result = base.modpow(&exp, &modulus).to_bytes_be();
+    output[mod_len - result.len()..].copy_from_slice(&result);
+    output
+}
+
+/// Read a point of G1 from 64 bytes, where (0, 0) is the point at infinity.
+fn read_g1(input: &[u8]) -> Option<G1> {
+    let x = Fq::from_slice(&input[..32]).ok()?;
+    let y = Fq::from_slice(&input[32..64]).ok()?;
+    if x.is_zero() && y.is_zero() {
+        Some(G1::zero())
+    } else {
+        AffineG1::new(x, y).ok().map(Into::into)
+    }
+}
+
+/// Read a point of G2 from 128 bytes, with the imaginary part of each
+/// coordinate first, where (0, 0) is the point at infinity.
+fn read_g2(input: &[u8]) -> Option<G2> {
+    let x_imaginary = Fq::from_slice(&input[..32]).ok()?;
+    let x_real = Fq::from_slice(&input[32..64]).ok()?;
+    let y_imaginary = Fq::from_slice(&input[64..96]).ok()?;
+    let y_real = Fq::from_slice(&input[96..128]).ok()?;
+    let x = Fq2::new(x_real, x_imaginary);
+    let y = Fq2::new(y_real, y_imaginary);
+    if x.is_zero() && y.is_zero() {
+        Some(G2::zero())
+    } else {
+        AffineG2::new(x, y).ok().map(Into::into)
+    }
+}
+
+/// Write a point of G1 into 64 bytes, where (0, 0) is the point at infinity.
+fn write_g1(point: G1) -> Vec<u8> {
+    let mut output = vec![0u8; 64];
+    if let Some(point) = AffineG1::from_jacobian(point) {
+        point
+            .x()
+            .to_big_endian(&mut output[..32])
+            .expect("slice of 32 bytes");
+        point
+            .y()
+            .to_big_endian(&mut output[32..])
+            .expect("slice of 32 bytes");
+    }
+    output
+}
+
+fn bn256_add(input: &[u8]) -> Option<Vec<u8>> {
+    let input = padded_input(input, 0, 128);
+    let a = read_g1(&input[..64])?;
+    let b = read_g1(&input[64..])?;
+    Some(write_g1(a + b))
+}
+
+fn bn256_scalar_mul(input: &[u8]) -> Option<Vec<u8>> {
+    let input = padded_input(input, 0, 96);
+    let point = read_g1(&input[..64])?;
+    let scalar = Fr::from_slice(&input[64..]).ok()?;
+    Some(write_g1(point * scalar))
+}
+
+fn bn256_pairing(input: &[u8]) -> Option<Vec<u8>> {
+    if input.len() % 192 != 0 {
+        return None;
+    }
+    let pairs = input
+        .chunks(192)
+        .map(|pair| Some((read_g1(&pair[..64])?, read_g2(&pair[64..])?)))
+        .collect::<Option<Vec<_>>>()?;
+    let mut output = vec![0u8; 32];
+    if bn::pairing_batch(&pairs) == Gt::one() {
+        output[31] = 1;
+    }
+    Some(output)
+}
+
+const BLAKE2B_IV: [u64; 8] = [
+    0x6a09e667f3bcc908,
+    0xbb67ae8584caa73b,
+    0x3c6ef372fe94f82b,
+    0xa54ff53a5f1d36f1,
+    0x510e527fade682d1,
+    0x9b05688c2b3e6c1f,
+    0x1f83d9abfb41bd6b,
+    0x5be0cd19137e2179,
+];
+
+const BLAKE2B_SIGMA: [[usize; 16]; 10] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+    [11, 8, 12, 0, 5, 2, 15, 13, 10, 14, 3, 6, 7, 1, 9, 4],
+    [7, 9, 3, 1, 13, 12, 11, 14, 2, 6, 5, 10, 4, 0, 15, 8],
+    [9, 0, 5, 7, 2, 4, 10, 15, 14, 1, 11, 12, 6, 8, 3, 13],
+    [2, 12, 6, 10, 0, 11, 8, 3, 4, 13, 7, 5, 15, 14, 1, 9],
+    [12, 5, 1, 15, 14, 13, 4, 10, 0, 7, 6, 3, 9, 2, 8, 11],
+    [13, 11, 7, 14, 12, 1, 3, 9, 5, 0, 15, 4, 8, 6, 2, 10],
+    [6, 15, 14, 9, 11, 3, 0, 8, 12, 2, 13, 7, 1, 4, 10, 5],
+    [10, 2, 8, 4, 7, 6, 1, 5, 15, 11, 9, 14, 3, 12, 13, 0],
+];
+
+/// Mixing function G of BLAKE2b.
+#[allow(clippy::many_single_char_names)]
+fn blake2b_g(
+    v: &mut [u64; 16],
+    (a, b, c, d): (usize, usize, usize, usize),
+    x: u64,
+    y: u64,
+) {
+    v[a] = v[a].wrapping_add(v[b]).wrapping_add(x);
+    v[d] = (v[d] ^ v[a]).rotate_right(32);
+    v[c] = v[c].wrapping_add(v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(24);
+    v[a] = v[a].wrapping_add(v[b]).wrapping_add(y);
+    v[d] = (v[d] ^ v[a]).rotate_right(16);
+    v[c] = v[c].wrapping_add(v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(63);
+}
+
+/// Compression function F of BLAKE2b with the number of `rounds`, the state
+/// vector `h`, the message block `m`, the offset counters `t` and the final
+/// block indicator `f`.
+fn blake2b_f(
+    rounds: u32,
+    h: &mut [u64; 8],
+    m: &[u64; 16],
+    t: &[u64; 2],
+    f: bool,
+) {
+    let mut v = [0u64; 16];
+    v[..8].copy_from_slice(h);
+    v[8..].copy_from_slice(&BLAKE2B_IV);
+    v[12] ^= t[0];
+    v[13] ^= t[1];
+    if f {
+        v[14] = !v[14];
+    }
+    for round in 0..rounds as usize {
+        let s = &BLAKE2B_SIGMA[round % 10];
+        blake2b_g(&mut v, (0, 4, 8, 12), m[s[0]], m[s[1]]);
+        blake2b_g(&mut v, (1, 5, 9, 13), m[s[2]], m[s[3]]);
+        blake2b_g(&mut v, (2, 6, 10, 14), m[s[4]], m[s[5]]);
+        blake2b_g(&mut v, (3, 7, 11, 15), m[s[6]], m[s[7]]);
+        blake2b_g(&mut v, (0, 5, 10, 15), m[s[8]], m[s[9]]);
+        blake2b_g(&mut v, (1, 6, 11, 12), m[s[10]], m[s[11]]);
+        blake2b_g(&mut v, (2, 7, 8, 13), m[s[12]], m[s[13]]);
+        blake2b_g(&mut v, (3, 4, 9, 14), m[s[14]], m[s[15]]);
+    }
+    for i in 0..8 {
+        h[i] ^= v[i] ^ v[i + 8];
+    }
+}
+
+fn blake2f(input: &[u8]) -> Option<Vec<u8>> {
+    if input.len() != 213 || input[212] > 1 {
+        return None;
+    }
+    let word = |offset: usize| {
+        u64::from_le_bytes(input[offset..offset + 8].try_into().unwrap())
+    };
+    let rounds = u32::from_be_bytes(input[..4].try_into().unwrap());
+    let mut h = [0u64; 8];
+    for (i, h) in h.iter_mut().enumerate() {
+        *h = word(4 + 8 * i);
+    }
+    let mut m = [0u64; 16];
+    for (i, m) in m.iter_mut().enumerate() {
+        *m = word(68 + 8 * i);
+    }
+    let t = [word(196), word(204)];
+    blake2b_f(rounds, &mut h, &m, &t, input[212] == 1);
+    Some(h.iter().flat_map(|h| h.to_le_bytes()).collect())
+}
+
+#[cfg(test)]
+mod precompiles_tests {
+    use super::*;
+    use crate::address;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn precompile_addresses() {
+        for precompile in Precompile::ALL {
+            assert_eq!(
+                Precompile::from_address(&precompile.address()),
+                Some(precompile)
+            );
+        }
+        assert_eq!(Precompile::from_address(&Address::zero()), None);
+        assert_eq!(
+            Precompile::from_address(&address!(
+                "0x000000000000000000000000000000000000000a"
+            )),
+            None
+        );
+        assert_eq!(
+            Precompile::from_address(&address!(
+                "0x0100000000000000000000000000000000000001"
+            )),
+            None
+        );
+    }
+
+    #[test]
+    fn precompile_hashes() {
+        let input = b"abc";
+        assert_eq!(
+            hex::encode(Precompile::Sha256.execute(input).unwrap()),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(
+            hex::encode(Precompile::Ripemd160.execute(input).unwrap()),
+            "0000000000000000000000008eb208f7e05d987a9b044a8e98c6b087f15a0bfc"
+        );
+        assert_eq!(Precompile::Identity.execute(input).unwrap(), input);
+        assert_eq!(Precompile::Sha256.gas_cost(input), 72);
+        assert_eq!(Precompile::Ripemd160.gas_cost(input), 720);
+        assert_eq!(Precompile::Identity.gas_cost(input), 18);
+    }
+
+    #[test]
+    fn precompile_modexp() {
+        // 3 ** 0xffff % 5 with 1 byte long base and modulus
+        let mut input = vec![0u8; 96];
+        input[31] = 1;
+        input[63] = 2;
+        input[95] = 1;
+        input.extend_from_slice(&[0x03, 0xff, 0xff, 0x05]);
+        assert_eq!(Precompile::ModExp.execute(&input).unwrap(), vec![0x02]);
+        assert_eq!(Precompile::ModExp.gas_cost(&input), 200);
+
+        // A zero modulus returns zero
+        input[99] = 0;
+        assert_eq!(Precompile::ModExp.execute(&input).unwrap(), vec![0x00]);
+    }
+
+    #[test]
+    fn precompile_bn256() {
+        // 1 * G + 1 * G = 2 * G, with the generator G = (1, 2)
+        let mut generator = vec![0u8; 64];
+        generator[31] = 1;
+        generator[63] = 2;
+        let double = Precompile::Bn256Add
+            .execute(&[generator.clone(), generator.clone()].concat())
+            .unwrap();
+        let mut scalar = vec![0u8; 32];
+        scalar[31] = 2;
+        assert_eq!(
+            Precompile::Bn256ScalarMul
+                .execute(&[generator.clone(), scalar].concat())
+                .unwrap(),
+            double
+        );
+
+        // A point out of the curve is invalid
+        generator[63] = 3;
+        assert_eq!(
+            Precompile::Bn256Add
+                .execute(&[generator.clone(), generator].concat()),
+            None
+        );
+
+        // The pairing of an empty input succeeds, and the input must be made
+        // of pairs of points of G1 and G2.
+        let mut output = vec![0u8; 32];
+        output[31] = 1;
+        assert_eq!(Precompile::Bn256Pairing.execute(&[]), Some(output));
+        assert_eq!(Precompile::Bn256Pairing.execute(&[0u8; 191]), None);
+        assert_eq!(Precompile::Bn256Pairing.gas_cost(&[0u8; 384]), 113000);
+    }
+
+    #[test]
+    fn precompile_blake2f() {
+        // Test vector 5 of EIP-152
+        let input = hex::decode(
+            "0000000c48c9bdf267e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f3af54fa5d182e6ad7f520e511f6c3e2b8c68059b6bbd41fbabd9831f79217e1319cde05b61626300000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000300000000000000000000000000000001",
+        )
+        .unwrap();
+        assert_eq!(
+            hex::encode(Precompile::Blake2F.execute(&input).unwrap()),
+            "ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d17d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923"
+        );
+        assert_eq!(Precompile::Blake2F.gas_cost(&input), 12);
+
+        // The final block indicator must be 0 or 1
+        let mut input = input;
+        input[212] = 2;
+        assert_eq!(Precompile::Blake2F.execute(&input), None);
+    }
+}
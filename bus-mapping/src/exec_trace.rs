@@ -19,6 +19,9 @@ pub struct BlockConstants {
     gas_limit: Word,
     chain_id: Word,
     base_fee: Word,
+    /// Hashes of the previous blocks, up to 256, from the oldest to the most
+    /// recent one, which are the ones accessible with BLOCKHASH.
+    history_hashes: Vec<Word>,
 }
 
 impl BlockConstants {
@@ -37,6 +40,7 @@ impl BlockConstants {
             gas_limit: block.gas_limit,
             chain_id: *chain_id,
             base_fee: block.base_fee_per_gas.unwrap(),
+            history_hashes: Vec::new(),
         }
     }
 
@@ -54,6 +58,7 @@ impl BlockConstants {
             gas_limit: Word::from(15_000_000u64),
             chain_id: Word::one(),
             base_fee: Word::from(97u64),
+            history_hashes: Vec::new(),
         }
     }
 }
@@ -70,6 +75,7 @@ impl BlockConstants {
         gas_limit: Word,
         chain_id: Word,
         base_fee: Word,
+        history_hashes: Vec<Word>,
     ) -> BlockConstants {
         BlockConstants {
             hash,
@@ -80,6 +86,7 @@ impl BlockConstants {
             gas_limit,
             chain_id,
             base_fee,
+            history_hashes,
         }
     }
     #[inline]
@@ -129,6 +136,30 @@ impl BlockConstants {
     pub fn base_fee(&self) -> &Word {
         &self.base_fee
     }
+
+    #[inline]
+    /// Return the hashes of the previous blocks, from the oldest to the most
+    /// recent one.
+    pub fn history_hashes(&self) -> &[Word] {
+        &self.history_hashes
+    }
+
+    /// Return the hash of the block `number` as returned by BLOCKHASH, which
+    /// is zero when the block is not one of the 256 previous blocks or its
+    /// hash is not in the history.
+    pub fn block_hash(&self, number: Word) -> Word {
+        let current = Word::from(self.number.as_u64());
+        if number >= current || number < current.saturating_sub(Word::from(256))
+        {
+            return Word::zero();
+        }
+        let distance = (current - number).as_usize();
+        self.history_hashes
+            .len()
+            .checked_sub(distance)
+            .map(|index| self.history_hashes[index])
+            .unwrap_or_else(Word::zero)
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -142,6 +173,19 @@ impl From<(Target, usize)> for OperationRef {
             Target::Memory => Self(Target::Memory, op_ref_data.1),
             Target::Stack => Self(Target::Stack, op_ref_data.1),
             Target::Storage => Self(Target::Storage, op_ref_data.1),
+            Target::TxAccessListAccount => {
+                Self(Target::TxAccessListAccount, op_ref_data.1)
+            }
+            Target::TxAccessListStorageSlot => {
+                Self(Target::TxAccessListStorageSlot, op_ref_data.1)
+            }
+            Target::TxRefund => Self(Target::TxRefund, op_ref_data.1),
+            Target::Account => Self(Target::Account, op_ref_data.1),
+            Target::AccountDestructed => {
+                Self(Target::AccountDestructed, op_ref_data.1)
+            }
+            Target::CallContext => Self(Target::CallContext, op_ref_data.1),
+            Target::Log => Self(Target::Log, op_ref_data.1),
         }
     }
 }
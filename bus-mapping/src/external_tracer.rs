@@ -1,5 +1,5 @@
 //! This module generates traces by connecting to an external tracer
-use crate::eth_types::{self, Address, GethExecStep, Word};
+use crate::eth_types::{self, Address, Bytes, GethExecStep, Word};
 use crate::BlockConstants;
 use crate::Error;
 use geth_utils;
@@ -14,6 +14,8 @@ pub struct Transaction {
     pub gas_limit: Word,
     /// Target Address
     pub target: Address,
+    /// Input / Call Data
+    pub input: Bytes,
 }
 
 impl Transaction {
@@ -23,6 +25,7 @@ impl Transaction {
             origin: tx.from,
             gas_limit: tx.gas,
             target: tx.to.unwrap(),
+            input: tx.input.clone(),
         }
     }
 }
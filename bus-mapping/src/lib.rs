@@ -112,6 +112,7 @@
 //!     Word::zero(),
 //!     Word::zero(),
 //!     Word::zero(),
+//!     Vec::new(),
 //! );
 //!
 //! // We use some mock data as context for the trace
@@ -83,10 +83,27 @@ impl BlockData {
     fn new_single_tx_trace_accounts_gas(
         accounts: &[external_tracer::Account],
         gas: Gas,
+    ) -> Result<Self, Error> {
+        Self::new_single_tx_trace_accounts_gas_input(
+            accounts,
+            gas,
+            Bytes::default(),
+        )
+    }
+
+    /// Create a new block with a single tx with the given gas limit and input
+    /// that executes the code found in the account with address 0x0 (which
+    /// can call code in the other accounts).  The trace will be generated
+    /// automatically with the external_tracer from the accounts code.
+    fn new_single_tx_trace_accounts_gas_input(
+        accounts: &[external_tracer::Account],
+        gas: Gas,
+        input: Bytes,
     ) -> Result<Self, Error> {
         let eth_block = new_block();
         let mut eth_tx = new_tx(&eth_block);
         eth_tx.gas = Word::from(gas.0);
+        eth_tx.input = input;
         let block_ctants = BlockConstants::from_eth_block(
             &eth_block,
             &eth_types::Word::one(),
@@ -140,6 +157,21 @@ impl BlockData {
         Self::new_single_tx_trace_accounts_gas(&[tracer_account], gas)
     }
 
+    /// Create a new block with a single tx with the given input that executes
+    /// the code passed by argument.  The trace will be generated
+    /// automatically with the external_tracer from the code.
+    pub fn new_single_tx_trace_code_input(
+        code: &Bytecode,
+        input: Bytes,
+    ) -> Result<Self, Error> {
+        let tracer_account = new_tracer_account(code);
+        Self::new_single_tx_trace_accounts_gas_input(
+            &[tracer_account],
+            Gas(1_000_000u64),
+            input,
+        )
+    }
+
     /// Create a new block with a single tx that executes the code_a passed by
     /// argument, with code_b deployed at address 0x123.  The trace will be
     /// generated automatically with the external_tracer from the code.
@@ -203,6 +235,7 @@ pub fn new_tracer_tx() -> external_tracer::Transaction {
         origin: address!("0x00000000000000000000000000000000c014ba5e"),
         gas_limit: Word::from(1_000_000u64),
         target: Address::zero(),
+        input: Bytes::default(),
     }
 }
 
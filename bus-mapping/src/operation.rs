@@ -1,5 +1,7 @@
 //! Collection of structs and functions used to:
-//! - Define the internals of a [`MemoryOp`], [`StackOp`] and [`StorageOp`].
+//! - Define the internals of a [`MemoryOp`], [`StackOp`], [`StorageOp`],
+//!   [`TxAccessListAccountOp`], [`TxAccessListStorageSlotOp`], [`TxRefundOp`],
+//!   [`AccountOp`], [`AccountDestructedOp`], [`CallContextOp`] and [`LogOp`].
 //! - Define the actual operation types and a wrapper over them (the
 //!   [`Operation`] enum).
 //! - Define structures that interact with operations such as
@@ -36,7 +38,9 @@ impl RW {
     }
 }
 
-/// Enum used to differenciate between EVM Stack, Memory and Storage operations.
+/// Enum used to differenciate between EVM Stack, Memory, Storage,
+/// TxAccessListAccount, TxAccessListStorageSlot, TxRefund, Account,
+/// AccountDestructed, CallContext and Log operations.
 #[derive(Debug, Clone, PartialEq, Eq, Copy)]
 pub enum Target {
     /// Means the target of the operation is the Memory.
@@ -45,6 +49,24 @@ pub enum Target {
     Stack,
     /// Means the target of the operation is the Storage.
     Storage,
+    /// Means the target of the operation is the access list of accounts of a
+    /// Transaction.
+    TxAccessListAccount,
+    /// Means the target of the operation is the access list of storage slots
+    /// of a Transaction.
+    TxAccessListStorageSlot,
+    /// Means the target of the operation is the gas refund counter of a
+    /// Transaction.
+    TxRefund,
+    /// Means the target of the operation is an Account.
+    Account,
+    /// Means the target of the operation is the destructed flag of an
+    /// Account.
+    AccountDestructed,
+    /// Means the target of the operation is the context of a Call.
+    CallContext,
+    /// Means the target of the operation is a Log.
+    Log,
 }
 
 /// Trait used for Operation Kinds.
@@ -249,8 +271,658 @@ impl Ord for StorageOp {
     }
 }
 
+/// Represents a [`READ`](RW::READ)/[`WRITE`](RW::WRITE) into the access list
+/// of accounts of a transaction (EIP-2929) implied by an specific
+/// [`OpcodeId`](crate::evm::opcodes::ids::OpcodeId) of the
+/// [`ExecStep`](crate::circuit_input_builder::ExecStep).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TxAccessListAccountOp {
+    rw: RW,
+    tx_id: usize,
+    address: Address,
+    value: bool,
+    value_prev: bool,
+}
+
+impl TxAccessListAccountOp {
+    /// Create a new instance of a `TxAccessListAccountOp` from it's
+    /// components.
+    pub const fn new(
+        rw: RW,
+        tx_id: usize,
+        address: Address,
+        value: bool,
+        value_prev: bool,
+    ) -> TxAccessListAccountOp {
+        TxAccessListAccountOp {
+            rw,
+            tx_id,
+            address,
+            value,
+            value_prev,
+        }
+    }
+
+    /// Returns the internal [`RW`] which says whether the operation corresponds
+    /// to a Read or a Write into the access list.
+    pub const fn rw(&self) -> RW {
+        self.rw
+    }
+
+    /// Returns the [`Target`] (operation type) of this operation.
+    pub const fn target(&self) -> Target {
+        Target::TxAccessListAccount
+    }
+
+    /// Returns the id of the transaction of this operation.
+    pub const fn tx_id(&self) -> usize {
+        self.tx_id
+    }
+
+    /// Returns the [`Address`] of the account of this operation.
+    pub const fn address(&self) -> &Address {
+        &self.address
+    }
+
+    /// Returns whether the account is in the access list after this
+    /// operation.
+    pub const fn value(&self) -> bool {
+        self.value
+    }
+
+    /// Returns whether the account was in the access list previous to this
+    /// operation.
+    pub const fn value_prev(&self) -> bool {
+        self.value_prev
+    }
+}
+
+impl Op for TxAccessListAccountOp {
+    fn into_enum(self) -> OpEnum {
+        OpEnum::TxAccessListAccount(self)
+    }
+}
+
+impl PartialOrd for TxAccessListAccountOp {
+    fn partial_cmp(&self, other: &TxAccessListAccountOp) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for TxAccessListAccountOp {
+    fn cmp(&self, other: &TxAccessListAccountOp) -> Ordering {
+        match self.tx_id().cmp(&other.tx_id()) {
+            Ordering::Equal => self.address().cmp(other.address()),
+            ord => ord,
+        }
+    }
+}
+
+/// Represents a [`READ`](RW::READ)/[`WRITE`](RW::WRITE) into the access list
+/// of storage slots of a transaction (EIP-2929) implied by an specific
+/// [`OpcodeId`](crate::evm::opcodes::ids::OpcodeId) of the
+/// [`ExecStep`](crate::circuit_input_builder::ExecStep).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TxAccessListStorageSlotOp {
+    rw: RW,
+    tx_id: usize,
+    address: Address,
+    key: Word,
+    value: bool,
+    value_prev: bool,
+}
+
+impl TxAccessListStorageSlotOp {
+    /// Create a new instance of a `TxAccessListStorageSlotOp` from it's
+    /// components.
+    pub const fn new(
+        rw: RW,
+        tx_id: usize,
+        address: Address,
+        key: Word,
+        value: bool,
+        value_prev: bool,
+    ) -> TxAccessListStorageSlotOp {
+        TxAccessListStorageSlotOp {
+            rw,
+            tx_id,
+            address,
+            key,
+            value,
+            value_prev,
+        }
+    }
+
+    /// Returns the internal [`RW`] which says whether the operation corresponds
+    /// to a Read or a Write into the access list.
+    pub const fn rw(&self) -> RW {
+        self.rw
+    }
+
+    /// Returns the [`Target`] (operation type) of this operation.
+    pub const fn target(&self) -> Target {
+        Target::TxAccessListStorageSlot
+    }
+
+    /// Returns the id of the transaction of this operation.
+    pub const fn tx_id(&self) -> usize {
+        self.tx_id
+    }
+
+    /// Returns the [`Address`] of the account of the storage slot of this
+    /// operation.
+    pub const fn address(&self) -> &Address {
+        &self.address
+    }
+
+    /// Returns the [`Word`] used as key of the storage slot of this
+    /// operation.
+    pub const fn key(&self) -> &Word {
+        &self.key
+    }
+
+    /// Returns whether the storage slot is in the access list after this
+    /// operation.
+    pub const fn value(&self) -> bool {
+        self.value
+    }
+
+    /// Returns whether the storage slot was in the access list previous to
+    /// this operation.
+    pub const fn value_prev(&self) -> bool {
+        self.value_prev
+    }
+}
+
+impl Op for TxAccessListStorageSlotOp {
+    fn into_enum(self) -> OpEnum {
+        OpEnum::TxAccessListStorageSlot(self)
+    }
+}
+
+impl PartialOrd for TxAccessListStorageSlotOp {
+    fn partial_cmp(
+        &self,
+        other: &TxAccessListStorageSlotOp,
+    ) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for TxAccessListStorageSlotOp {
+    fn cmp(&self, other: &TxAccessListStorageSlotOp) -> Ordering {
+        match self.tx_id().cmp(&other.tx_id()) {
+            Ordering::Equal => match self.address().cmp(other.address()) {
+                Ordering::Equal => self.key().cmp(other.key()),
+                ord => ord,
+            },
+            ord => ord,
+        }
+    }
+}
+
+/// Represents a [`READ`](RW::READ)/[`WRITE`](RW::WRITE) into the gas refund
+/// counter of a transaction implied by an specific
+/// [`OpcodeId`](crate::evm::opcodes::ids::OpcodeId) of the
+/// [`ExecStep`](crate::circuit_input_builder::ExecStep).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TxRefundOp {
+    rw: RW,
+    tx_id: usize,
+    value: u64,
+    value_prev: u64,
+}
+
+impl TxRefundOp {
+    /// Create a new instance of a `TxRefundOp` from it's components.
+    pub const fn new(
+        rw: RW,
+        tx_id: usize,
+        value: u64,
+        value_prev: u64,
+    ) -> TxRefundOp {
+        TxRefundOp {
+            rw,
+            tx_id,
+            value,
+            value_prev,
+        }
+    }
+
+    /// Returns the internal [`RW`] which says whether the operation corresponds
+    /// to a Read or a Write into the refund counter.
+    pub const fn rw(&self) -> RW {
+        self.rw
+    }
+
+    /// Returns the [`Target`] (operation type) of this operation.
+    pub const fn target(&self) -> Target {
+        Target::TxRefund
+    }
+
+    /// Returns the id of the transaction of this operation.
+    pub const fn tx_id(&self) -> usize {
+        self.tx_id
+    }
+
+    /// Returns the refund counter read or written by this operation.
+    pub const fn value(&self) -> u64 {
+        self.value
+    }
+
+    /// Returns the refund counter found previous to this operation.
+    pub const fn value_prev(&self) -> u64 {
+        self.value_prev
+    }
+}
+
+impl Op for TxRefundOp {
+    fn into_enum(self) -> OpEnum {
+        OpEnum::TxRefund(self)
+    }
+}
+
+impl PartialOrd for TxRefundOp {
+    fn partial_cmp(&self, other: &TxRefundOp) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for TxRefundOp {
+    fn cmp(&self, other: &TxRefundOp) -> Ordering {
+        self.tx_id().cmp(&other.tx_id())
+    }
+}
+
+/// Field of an Account that can be read or written by an [`AccountOp`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum AccountField {
+    /// Account Nonce
+    Nonce,
+    /// Account Balance
+    Balance,
+    /// Account Code Hash
+    CodeHash,
+}
+
+/// Represents a [`READ`](RW::READ)/[`WRITE`](RW::WRITE) into an account field
+/// implied by an specific [`OpcodeId`](crate::evm::opcodes::ids::OpcodeId) of
+/// the [`ExecStep`](crate::circuit_input_builder::ExecStep).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountOp {
+    rw: RW,
+    address: Address,
+    field: AccountField,
+    value: Word,
+    value_prev: Word,
+}
+
+impl AccountOp {
+    /// Create a new instance of an `AccountOp` from it's components.
+    pub const fn new(
+        rw: RW,
+        address: Address,
+        field: AccountField,
+        value: Word,
+        value_prev: Word,
+    ) -> AccountOp {
+        AccountOp {
+            rw,
+            address,
+            field,
+            value,
+            value_prev,
+        }
+    }
+
+    /// Returns the internal [`RW`] which says whether the operation corresponds
+    /// to a Read or a Write into the account.
+    pub const fn rw(&self) -> RW {
+        self.rw
+    }
+
+    /// Returns the [`Target`] (operation type) of this operation.
+    pub const fn target(&self) -> Target {
+        Target::Account
+    }
+
+    /// Returns the [`Address`] of the account of this operation.
+    pub const fn address(&self) -> &Address {
+        &self.address
+    }
+
+    /// Returns the [`AccountField`] read or written by this operation.
+    pub const fn field(&self) -> &AccountField {
+        &self.field
+    }
+
+    /// Returns the [`Word`] read or written by this operation.
+    pub const fn value(&self) -> &Word {
+        &self.value
+    }
+
+    /// Returns the [`Word`] of the field found previous to this operation.
+    pub const fn value_prev(&self) -> &Word {
+        &self.value_prev
+    }
+}
+
+impl Op for AccountOp {
+    fn into_enum(self) -> OpEnum {
+        OpEnum::Account(self)
+    }
+}
+
+impl PartialOrd for AccountOp {
+    fn partial_cmp(&self, other: &AccountOp) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for AccountOp {
+    fn cmp(&self, other: &AccountOp) -> Ordering {
+        match self.address().cmp(other.address()) {
+            Ordering::Equal => self.field().cmp(other.field()),
+            ord => ord,
+        }
+    }
+}
+
+/// Represents a [`READ`](RW::READ)/[`WRITE`](RW::WRITE) into the destructed
+/// flag of an account implied by an specific
+/// [`OpcodeId`](crate::evm::opcodes::ids::OpcodeId) of the
+/// [`ExecStep`](crate::circuit_input_builder::ExecStep).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountDestructedOp {
+    rw: RW,
+    address: Address,
+    value: bool,
+    value_prev: bool,
+}
+
+impl AccountDestructedOp {
+    /// Create a new instance of an `AccountDestructedOp` from it's components.
+    pub const fn new(
+        rw: RW,
+        address: Address,
+        value: bool,
+        value_prev: bool,
+    ) -> AccountDestructedOp {
+        AccountDestructedOp {
+            rw,
+            address,
+            value,
+            value_prev,
+        }
+    }
+
+    /// Returns the internal [`RW`] which says whether the operation corresponds
+    /// to a Read or a Write into the destructed flag.
+    pub const fn rw(&self) -> RW {
+        self.rw
+    }
+
+    /// Returns the [`Target`] (operation type) of this operation.
+    pub const fn target(&self) -> Target {
+        Target::AccountDestructed
+    }
+
+    /// Returns the [`Address`] of the account of this operation.
+    pub const fn address(&self) -> &Address {
+        &self.address
+    }
+
+    /// Returns the destructed flag read or written by this operation.
+    pub const fn value(&self) -> bool {
+        self.value
+    }
+
+    /// Returns the destructed flag found previous to this operation.
+    pub const fn value_prev(&self) -> bool {
+        self.value_prev
+    }
+}
+
+impl Op for AccountDestructedOp {
+    fn into_enum(self) -> OpEnum {
+        OpEnum::AccountDestructed(self)
+    }
+}
+
+impl PartialOrd for AccountDestructedOp {
+    fn partial_cmp(&self, other: &AccountDestructedOp) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for AccountDestructedOp {
+    fn cmp(&self, other: &AccountDestructedOp) -> Ordering {
+        self.address().cmp(other.address())
+    }
+}
+
+/// Field of the context of a Call that can be read or written by a
+/// [`CallContextOp`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CallContextField {
+    /// Global counter at which the state writes of the call are reverted
+    RwCounterEndOfReversion,
+    /// Id of the caller call
+    CallerCallId,
+    /// Id of the transaction that contains the call
+    TxId,
+    /// Depth of the call, which is 1 for the root call
+    Depth,
+    /// Caller Address
+    CallerAddress,
+    /// Callee Address
+    CalleeAddress,
+    /// Offset of the call data in the caller memory
+    CallDataOffset,
+    /// Length of the call data
+    CallDataLength,
+    /// Offset of the return data region in the caller memory
+    ReturnDataOffset,
+    /// Length of the return data region in the caller memory
+    ReturnDataLength,
+    /// Value transferred by the call
+    Value,
+    /// Whether the call succeeds
+    Result,
+    /// Whether the state writes of the call are persistent, which happens
+    /// when the call and all its callers succeed
+    IsPersistent,
+    /// Whether the call is executed without write access
+    IsStatic,
+    /// Whether the call is the root call of the transaction
+    IsRoot,
+    /// Whether the call is a {CREATE, CREATE2} one
+    IsCreate,
+    /// Hash of the code executed by the call
+    OpcodeSource,
+    /// Program counter saved when the call does a sub-call
+    ProgramCounter,
+    /// Stack pointer saved when the call does a sub-call
+    StackPointer,
+    /// Gas left saved when the call does a sub-call
+    GasLeft,
+    /// Memory size in words saved when the call does a sub-call
+    MemorySize,
+    /// State write counter saved when the call does a sub-call
+    StateWriteCounter,
+}
+
+/// Represents a [`READ`](RW::READ)/[`WRITE`](RW::WRITE) into a field of the
+/// context of a Call implied by an specific
+/// [`OpcodeId`](crate::evm::opcodes::ids::OpcodeId) of the
+/// [`ExecStep`](crate::circuit_input_builder::ExecStep).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallContextOp {
+    rw: RW,
+    call_id: usize,
+    field: CallContextField,
+    value: Word,
+}
+
+impl CallContextOp {
+    /// Create a new instance of a `CallContextOp` from it's components.
+    pub const fn new(
+        rw: RW,
+        call_id: usize,
+        field: CallContextField,
+        value: Word,
+    ) -> CallContextOp {
+        CallContextOp {
+            rw,
+            call_id,
+            field,
+            value,
+        }
+    }
+
+    /// Returns the internal [`RW`] which says whether the operation corresponds
+    /// to a Read or a Write into the call context.
+    pub const fn rw(&self) -> RW {
+        self.rw
+    }
+
+    /// Returns the [`Target`] (operation type) of this operation.
+    pub const fn target(&self) -> Target {
+        Target::CallContext
+    }
+
+    /// Returns the id of the call of this operation.
+    pub const fn call_id(&self) -> usize {
+        self.call_id
+    }
+
+    /// Returns the [`CallContextField`] read or written by this operation.
+    pub const fn field(&self) -> &CallContextField {
+        &self.field
+    }
+
+    /// Returns the [`Word`] read or written by this operation.
+    pub const fn value(&self) -> &Word {
+        &self.value
+    }
+}
+
+impl Op for CallContextOp {
+    fn into_enum(self) -> OpEnum {
+        OpEnum::CallContext(self)
+    }
+}
+
+impl PartialOrd for CallContextOp {
+    fn partial_cmp(&self, other: &CallContextOp) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for CallContextOp {
+    fn cmp(&self, other: &CallContextOp) -> Ordering {
+        match self.call_id().cmp(&other.call_id()) {
+            Ordering::Equal => self.field().cmp(other.field()),
+            ord => ord,
+        }
+    }
+}
+
+/// Represents a [`WRITE`](RW::WRITE) of a log entry implied by a LOG
+/// [`OpcodeId`](crate::evm::opcodes::ids::OpcodeId) of the
+/// [`ExecStep`](crate::circuit_input_builder::ExecStep).  The log is dropped
+/// from the transaction receipt when the call that emits it is not
+/// persistent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogOp {
+    rw: RW,
+    index: usize,
+    address: Address,
+    topics: Vec<Word>,
+    data: Vec<u8>,
+    is_persistent: bool,
+}
+
+impl LogOp {
+    /// Create a new instance of a `LogOp` from it's components.
+    pub fn new(
+        rw: RW,
+        index: usize,
+        address: Address,
+        topics: Vec<Word>,
+        data: Vec<u8>,
+        is_persistent: bool,
+    ) -> LogOp {
+        LogOp {
+            rw,
+            index,
+            address,
+            topics,
+            data,
+            is_persistent,
+        }
+    }
+
+    /// Returns the internal [`RW`] which says whether the operation corresponds
+    /// to a Read or a Write of the log.
+    pub const fn rw(&self) -> RW {
+        self.rw
+    }
+
+    /// Returns the [`Target`] (operation type) of this operation.
+    pub const fn target(&self) -> Target {
+        Target::Log
+    }
+
+    /// Returns the index of the log within the block.
+    pub const fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns the [`Address`] of the account that emitted the log.
+    pub const fn address(&self) -> &Address {
+        &self.address
+    }
+
+    /// Returns the topics of the log.
+    pub fn topics(&self) -> &[Word] {
+        &self.topics
+    }
+
+    /// Returns the data bytes of the log.
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Returns whether the call that emitted the log is persistent, that is,
+    /// whether the log is kept in the transaction receipt.
+    pub const fn is_persistent(&self) -> bool {
+        self.is_persistent
+    }
+}
+
+impl Op for LogOp {
+    fn into_enum(self) -> OpEnum {
+        OpEnum::Log(self)
+    }
+}
+
+impl PartialOrd for LogOp {
+    fn partial_cmp(&self, other: &LogOp) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for LogOp {
+    fn cmp(&self, other: &LogOp) -> Ordering {
+        self.index().cmp(&other.index())
+    }
+}
+
 /// Generic enum that wraps over all the operation types possible.
-/// In particular [`StackOp`], [`MemoryOp`] and [`StorageOp`].
+/// In particular [`StackOp`], [`MemoryOp`], [`StorageOp`],
+/// [`TxAccessListAccountOp`], [`TxAccessListStorageSlotOp`], [`TxRefundOp`],
+/// [`AccountOp`], [`AccountDestructedOp`], [`CallContextOp`] and [`LogOp`].
 #[derive(Debug, Clone)]
 pub enum OpEnum {
     /// Doc
@@ -259,6 +931,20 @@ pub enum OpEnum {
     Memory(MemoryOp),
     /// Doc
     Storage(StorageOp),
+    /// Doc
+    TxAccessListAccount(TxAccessListAccountOp),
+    /// Doc
+    TxAccessListStorageSlot(TxAccessListStorageSlotOp),
+    /// Doc
+    TxRefund(TxRefundOp),
+    /// Doc
+    Account(AccountOp),
+    /// Doc
+    AccountDestructed(AccountDestructedOp),
+    /// Doc
+    CallContext(CallContextOp),
+    /// Doc
+    Log(LogOp),
 }
 
 /// Operation is a Wrapper over a type that implements Op with a GlobalCounter.
@@ -303,16 +989,36 @@ impl<T: Op> Operation<T> {
         }
     }
 
+    /// Create a new Operation from an `op` with a `gc` which reverts a
+    /// previous operation of a call that failed.
+    pub fn new_reversion(gc: GlobalCounter, op: T) -> Self {
+        Self {
+            gc,
+            revert: true,
+            op,
+        }
+    }
+
     /// Return this `Operation` `gc`
     pub fn gc(&self) -> GlobalCounter {
         self.gc
     }
 
+    /// Return whether this `Operation` reverts a previous operation.
+    pub fn revert(&self) -> bool {
+        self.revert
+    }
+
     /// Return this `Operation` `op`
     pub fn op(&self) -> &T {
         &self.op
     }
 
+    /// Return a mutable reference to this `Operation` `op`
+    pub(crate) fn op_mut(&mut self) -> &mut T {
+        &mut self.op
+    }
+
     // /// Matches over an `Operation` returning the [`Target`] of the iternal
     // op /// it stores inside.
     // pub const fn target(&self) -> Target {
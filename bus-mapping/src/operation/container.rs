@@ -1,4 +1,8 @@
-use super::{MemoryOp, Op, OpEnum, Operation, StackOp, StorageOp, Target};
+use super::{
+    AccountDestructedOp, AccountOp, CallContextOp, LogOp, MemoryOp, Op, OpEnum,
+    Operation, StackOp, StorageOp, Target, TxAccessListAccountOp,
+    TxAccessListStorageSlotOp, TxRefundOp,
+};
 use crate::exec_trace::OperationRef;
 use itertools::Itertools;
 
@@ -12,8 +16,10 @@ use itertools::Itertools;
 /// [`ExecStep`](crate::circuit_input_builder::ExecStep).
 ///
 /// Finally, the container also provides the capability of retrieving all of the
-/// `Stack`, `Memory` or `Storage` operations ordered according to the criterias
-/// they have specified.
+/// `Stack`, `Memory`, `Storage`, `TxAccessListAccount`,
+/// `TxAccessListStorageSlot`, `TxRefund`, `Account`, `AccountDestructed`,
+/// `CallContext` or `Log` operations ordered
+/// according to the criterias they have specified.
 /// That serves as a way to get an input with which is easy to work with in
 /// order to construct the State proof.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -21,6 +27,14 @@ pub struct OperationContainer {
     pub(crate) memory: Vec<Operation<MemoryOp>>,
     pub(crate) stack: Vec<Operation<StackOp>>,
     pub(crate) storage: Vec<Operation<StorageOp>>,
+    pub(crate) tx_access_list_account: Vec<Operation<TxAccessListAccountOp>>,
+    pub(crate) tx_access_list_storage_slot:
+        Vec<Operation<TxAccessListStorageSlotOp>>,
+    pub(crate) tx_refund: Vec<Operation<TxRefundOp>>,
+    pub(crate) account: Vec<Operation<AccountOp>>,
+    pub(crate) account_destructed: Vec<Operation<AccountDestructedOp>>,
+    pub(crate) call_context: Vec<Operation<CallContextOp>>,
+    pub(crate) log: Vec<Operation<LogOp>>,
 }
 
 impl Default for OperationContainer {
@@ -37,6 +51,13 @@ impl OperationContainer {
             memory: Vec::new(),
             stack: Vec::new(),
             storage: Vec::new(),
+            tx_access_list_account: Vec::new(),
+            tx_access_list_storage_slot: Vec::new(),
+            tx_refund: Vec::new(),
+            account: Vec::new(),
+            account_destructed: Vec::new(),
+            call_context: Vec::new(),
+            log: Vec::new(),
         }
     }
 
@@ -45,20 +66,65 @@ impl OperationContainer {
     /// location of the inserted operation inside the corresponding container
     /// vector.
     pub fn insert<T: Op>(&mut self, op: Operation<T>) -> OperationRef {
-        let gc = op.gc();
+        let (gc, revert) = (op.gc(), op.revert());
         match op.op.into_enum() {
             OpEnum::Memory(op) => {
-                self.memory.push(Operation::new(gc, op));
+                self.memory.push(Operation { gc, revert, op });
                 OperationRef::from((Target::Memory, self.memory.len()))
             }
             OpEnum::Stack(op) => {
-                self.stack.push(Operation::new(gc, op));
+                self.stack.push(Operation { gc, revert, op });
                 OperationRef::from((Target::Stack, self.stack.len()))
             }
             OpEnum::Storage(op) => {
-                self.storage.push(Operation::new(gc, op));
+                self.storage.push(Operation { gc, revert, op });
                 OperationRef::from((Target::Storage, self.storage.len()))
             }
+            OpEnum::TxAccessListAccount(op) => {
+                self.tx_access_list_account
+                    .push(Operation { gc, revert, op });
+                OperationRef::from((
+                    Target::TxAccessListAccount,
+                    self.tx_access_list_account.len(),
+                ))
+            }
+            OpEnum::TxAccessListStorageSlot(op) => {
+                self.tx_access_list_storage_slot.push(Operation {
+                    gc,
+                    revert,
+                    op,
+                });
+                OperationRef::from((
+                    Target::TxAccessListStorageSlot,
+                    self.tx_access_list_storage_slot.len(),
+                ))
+            }
+            OpEnum::TxRefund(op) => {
+                self.tx_refund.push(Operation { gc, revert, op });
+                OperationRef::from((Target::TxRefund, self.tx_refund.len()))
+            }
+            OpEnum::Account(op) => {
+                self.account.push(Operation { gc, revert, op });
+                OperationRef::from((Target::Account, self.account.len()))
+            }
+            OpEnum::AccountDestructed(op) => {
+                self.account_destructed.push(Operation { gc, revert, op });
+                OperationRef::from((
+                    Target::AccountDestructed,
+                    self.account_destructed.len(),
+                ))
+            }
+            OpEnum::CallContext(op) => {
+                self.call_context.push(Operation { gc, revert, op });
+                OperationRef::from((
+                    Target::CallContext,
+                    self.call_context.len(),
+                ))
+            }
+            OpEnum::Log(op) => {
+                self.log.push(Operation { gc, revert, op });
+                OperationRef::from((Target::Log, self.log.len()))
+            }
         }
     }
 
@@ -79,6 +145,62 @@ impl OperationContainer {
     pub fn sorted_storage(&self) -> Vec<Operation<StorageOp>> {
         self.storage.iter().sorted().cloned().collect()
     }
+
+    /// Returns a sorted vector of all of the [`TxAccessListAccountOp`]s
+    /// contained inside of the container.
+    pub fn sorted_tx_access_list_account(
+        &self,
+    ) -> Vec<Operation<TxAccessListAccountOp>> {
+        self.tx_access_list_account
+            .iter()
+            .sorted()
+            .cloned()
+            .collect()
+    }
+
+    /// Returns a sorted vector of all of the [`TxAccessListStorageSlotOp`]s
+    /// contained inside of the container.
+    pub fn sorted_tx_access_list_storage_slot(
+        &self,
+    ) -> Vec<Operation<TxAccessListStorageSlotOp>> {
+        self.tx_access_list_storage_slot
+            .iter()
+            .sorted()
+            .cloned()
+            .collect()
+    }
+
+    /// Returns a sorted vector of all of the [`TxRefundOp`]s contained inside
+    /// of the container.
+    pub fn sorted_tx_refund(&self) -> Vec<Operation<TxRefundOp>> {
+        self.tx_refund.iter().sorted().cloned().collect()
+    }
+
+    /// Returns a sorted vector of all of the [`AccountOp`]s contained inside of
+    /// the container.
+    pub fn sorted_account(&self) -> Vec<Operation<AccountOp>> {
+        self.account.iter().sorted().cloned().collect()
+    }
+
+    /// Returns a sorted vector of all of the [`AccountDestructedOp`]s contained
+    /// inside of the container.
+    pub fn sorted_account_destructed(
+        &self,
+    ) -> Vec<Operation<AccountDestructedOp>> {
+        self.account_destructed.iter().sorted().cloned().collect()
+    }
+
+    /// Returns a sorted vector of all of the [`CallContextOp`]s contained
+    /// inside of the container.
+    pub fn sorted_call_context(&self) -> Vec<Operation<CallContextOp>> {
+        self.call_context.iter().sorted().cloned().collect()
+    }
+
+    /// Returns a sorted vector of all of the [`LogOp`]s contained inside of
+    /// the container.
+    pub fn sorted_log(&self) -> Vec<Operation<LogOp>> {
+        self.log.iter().sorted().cloned().collect()
+    }
 }
 
 #[cfg(test)]
@@ -88,7 +210,7 @@ mod container_test {
     use crate::{
         eth_types::{Address, Word},
         evm::{GlobalCounter, MemoryAddress, StackAddress},
-        operation::RW,
+        operation::{AccountField, CallContextField, RW},
     };
 
     #[test]
@@ -113,15 +235,114 @@ mod container_test {
                 Word::default(),
             ),
         );
+        let tx_access_list_account_operation = Operation::new(
+            global_counter.inc_pre(),
+            TxAccessListAccountOp::new(
+                RW::WRITE,
+                1,
+                Address::zero(),
+                true,
+                false,
+            ),
+        );
+        let tx_access_list_storage_slot_operation = Operation::new(
+            global_counter.inc_pre(),
+            TxAccessListStorageSlotOp::new(
+                RW::WRITE,
+                1,
+                Address::zero(),
+                Word::default(),
+                true,
+                false,
+            ),
+        );
+        let tx_refund_operation = Operation::new(
+            global_counter.inc_pre(),
+            TxRefundOp::new(RW::WRITE, 1, 4800, 0),
+        );
+        let account_operation = Operation::new(
+            global_counter.inc_pre(),
+            AccountOp::new(
+                RW::WRITE,
+                Address::zero(),
+                AccountField::Nonce,
+                Word::from(0x1),
+                Word::default(),
+            ),
+        );
+        let call_context_operation = Operation::new(
+            global_counter.inc_pre(),
+            CallContextOp::new(
+                RW::READ,
+                1,
+                CallContextField::Depth,
+                Word::from(0x1),
+            ),
+        );
         let stack_ref = operation_container.insert(stack_operation.clone());
         let memory_ref = operation_container.insert(memory_operation.clone());
         let storage_ref = operation_container.insert(storage_operation.clone());
+        let tx_access_list_account_ref = operation_container
+            .insert(tx_access_list_account_operation.clone());
+        let tx_access_list_storage_slot_ref = operation_container
+            .insert(tx_access_list_storage_slot_operation.clone());
+        let tx_refund_ref =
+            operation_container.insert(tx_refund_operation.clone());
+        let account_ref = operation_container.insert(account_operation.clone());
+        let call_context_ref =
+            operation_container.insert(call_context_operation.clone());
 
         assert_eq!(operation_container.sorted_stack()[0], stack_operation);
         assert_eq!(operation_container.sorted_memory()[0], memory_operation);
         assert_eq!(operation_container.sorted_storage()[0], storage_operation);
+        assert_eq!(
+            operation_container.sorted_tx_access_list_account()[0],
+            tx_access_list_account_operation
+        );
+        assert_eq!(
+            operation_container.sorted_tx_access_list_storage_slot()[0],
+            tx_access_list_storage_slot_operation
+        );
+        assert_eq!(
+            operation_container.sorted_tx_refund()[0],
+            tx_refund_operation
+        );
+        assert_eq!(operation_container.sorted_account()[0], account_operation);
+        assert_eq!(
+            operation_container.sorted_call_context()[0],
+            call_context_operation
+        );
         assert_eq!(stack_ref, OperationRef::from((Target::Stack, 1)));
         assert_eq!(memory_ref, OperationRef::from((Target::Memory, 1)));
         assert_eq!(storage_ref, OperationRef::from((Target::Storage, 1)));
+        assert_eq!(
+            tx_access_list_account_ref,
+            OperationRef::from((Target::TxAccessListAccount, 1))
+        );
+        assert_eq!(
+            tx_access_list_storage_slot_ref,
+            OperationRef::from((Target::TxAccessListStorageSlot, 1))
+        );
+        assert_eq!(tx_refund_ref, OperationRef::from((Target::TxRefund, 1)));
+        assert_eq!(account_ref, OperationRef::from((Target::Account, 1)));
+        assert_eq!(
+            call_context_ref,
+            OperationRef::from((Target::CallContext, 1))
+        );
+
+        // The reversion flag is kept in the container
+        let storage_reversion_operation = Operation::new_reversion(
+            global_counter.inc_pre(),
+            StorageOp::new(
+                RW::WRITE,
+                Address::zero(),
+                Word::default(),
+                Word::default(),
+                Word::from(0x1),
+            ),
+        );
+        operation_container.insert(storage_reversion_operation);
+        assert!(!operation_container.storage[0].revert());
+        assert!(operation_container.storage[1].revert());
     }
 }
@@ -1,5 +1,14 @@
 use crate::eth_types::{Address, Word, H256};
-use std::collections::HashMap;
+use crate::Error;
+use ethers_core::utils::keccak256;
+use lazy_static::lazy_static;
+use std::collections::{HashMap, HashSet};
+
+lazy_static! {
+    /// Hash of the empty code, which is the code hash of the accounts without
+    /// code.
+    pub static ref CODE_HASH_EMPTY: H256 = H256(keccak256(&[0u8; 0]));
+}
 
 /// Account of the Ethereum State Trie, which contains an in-memory key-value
 /// database that represents the Account Storage Trie.
@@ -20,12 +29,50 @@ impl Account {
             codeHash: H256::zero(),
         }
     }
+
+    /// Return the hash of the code of this account, which is the empty code
+    /// hash when the account has no code.
+    pub fn code_hash(&self) -> H256 {
+        if self.codeHash.is_zero() {
+            *CODE_HASH_EMPTY
+        } else {
+            self.codeHash
+        }
+    }
+
+    /// Return if creating a contract at the address of this account collides
+    /// with it as defined in EIP-684: it has a nonce or code.
+    pub fn has_create_collision(&self) -> bool {
+        !self.nonce.is_zero() || self.code_hash() != *CODE_HASH_EMPTY
+    }
+
+    /// Return if this account is empty as defined in EIP-161: it has no
+    /// nonce, no balance and no code.
+    pub fn is_empty(&self) -> bool {
+        self.nonce.is_zero()
+            && self.balance.is_zero()
+            && self.code_hash() == *CODE_HASH_EMPTY
+    }
 }
 
 /// In-memory key-value database that represents the Ethereum State Trie.
 #[derive(Debug)]
 pub struct StateDB {
     state: HashMap<Address, Account>,
+    /// Code database, which maps a code hash to its code.
+    code: HashMap<H256, Vec<u8>>,
+    /// Storage values at the beginning of the current transaction (the
+    /// original values in EIP-2200) of the slots written in it.
+    committed_storage: HashMap<(Address, Word), Word>,
+    /// Gas refund counter of the current transaction.
+    refund: u64,
+    /// Accounts accessed in the current transaction (EIP-2929).
+    accessed_addresses: HashSet<Address>,
+    /// Storage slots accessed in the current transaction (EIP-2929).
+    accessed_storage: HashSet<(Address, Word)>,
+    /// Accounts destructed in the current transaction, which are removed from
+    /// the state at its end.
+    destructed_accounts: HashSet<Address>,
     acc_zero: Account,
     value_zero: Word,
 }
@@ -41,6 +88,12 @@ impl StateDB {
     pub fn new() -> Self {
         Self {
             state: HashMap::new(),
+            code: HashMap::new(),
+            committed_storage: HashMap::new(),
+            refund: 0,
+            accessed_addresses: HashSet::new(),
+            accessed_storage: HashSet::new(),
+            destructed_accounts: HashSet::new(),
             acc_zero: Account::zero(),
             value_zero: Word::zero(),
         }
@@ -104,6 +157,177 @@ impl StateDB {
         };
         (found, acc.storage.get_mut(key).expect("key not inserted"))
     }
+
+    /// Store `code` in the code database, and return its hash.
+    pub fn set_code(&mut self, code: Vec<u8>) -> H256 {
+        let code_hash = H256(keccak256(&code));
+        self.code.insert(code_hash, code);
+        code_hash
+    }
+
+    /// Get a reference to the code with hash `code_hash`.  Returns false and
+    /// an empty code when the code wasn't found in the code database.
+    pub fn get_code(&self, code_hash: &H256) -> (bool, &[u8]) {
+        if *code_hash == *CODE_HASH_EMPTY {
+            return (true, &[]);
+        }
+        match self.code.get(code_hash) {
+            Some(code) => (true, code.as_slice()),
+            None => (false, &[]),
+        }
+    }
+
+    /// Get a reference to the code of the [`Account`] at `addr`.  Returns
+    /// false and an empty code when the code wasn't found in the code
+    /// database.
+    pub fn get_account_code(&self, addr: &Address) -> (bool, &[u8]) {
+        let (_, acc) = self.get_account(addr);
+        self.get_code(&acc.code_hash())
+    }
+
+    /// Get a reference to the committed storage value from [`Account`] at
+    /// `addr`, at `key`, which is the value at the beginning of the current
+    /// transaction.  Returns false and a zero [`Word`] when the [`Account`] or
+    /// `key` wasn't found in the state.
+    pub fn get_committed_storage(
+        &self,
+        addr: &Address,
+        key: &Word,
+    ) -> (bool, &Word) {
+        match self.committed_storage.get(&(*addr, *key)) {
+            Some(value) => (true, value),
+            None => self.get_storage(addr, key),
+        }
+    }
+
+    /// Set the storage value from [`Account`] at `addr`, at `key`, keeping
+    /// track of the committed value of the slot in the current transaction.
+    /// Returns the previous value.
+    pub fn set_storage(
+        &mut self,
+        addr: &Address,
+        key: &Word,
+        value: Word,
+    ) -> Word {
+        let (_, value_prev) = self.get_storage(addr, key);
+        let value_prev = *value_prev;
+        self.committed_storage
+            .entry((*addr, *key))
+            .or_insert(value_prev);
+        let (_, slot) = self.get_storage_mut(addr, key);
+        *slot = value;
+        value_prev
+    }
+
+    /// Commit the changes of the previous transaction: the current storage
+    /// values become the committed ones, and the refund counter and the
+    /// access list are reset.
+    pub fn commit_tx(&mut self) {
+        self.committed_storage.clear();
+        self.refund = 0;
+        self.accessed_addresses.clear();
+        self.accessed_storage.clear();
+    }
+
+    /// Add the [`Account`] at `addr` to the access list of the current
+    /// transaction.  Returns whether it was already in it (warm).
+    pub fn add_account_to_access_list(&mut self, addr: &Address) -> bool {
+        !self.accessed_addresses.insert(*addr)
+    }
+
+    /// Add the storage slot at `key` of the [`Account`] at `addr` to the
+    /// access list of the current transaction.  Returns whether it was
+    /// already in it (warm).
+    pub fn add_account_storage_to_access_list(
+        &mut self,
+        addr: &Address,
+        key: &Word,
+    ) -> bool {
+        !self.accessed_storage.insert((*addr, *key))
+    }
+
+    /// Check whether the [`Account`] at `addr` is in the access list of the
+    /// current transaction (warm).
+    pub fn is_account_warm(&self, addr: &Address) -> bool {
+        self.accessed_addresses.contains(addr)
+    }
+
+    /// Check whether the storage slot at `key` of the [`Account`] at `addr`
+    /// is in the access list of the current transaction (warm).
+    pub fn is_account_storage_warm(&self, addr: &Address, key: &Word) -> bool {
+        self.accessed_storage.contains(&(*addr, *key))
+    }
+
+    /// Remove the [`Account`] at `addr` from the access list of the current
+    /// transaction, which happens when the call that added it is reverted.
+    pub fn remove_account_from_access_list(&mut self, addr: &Address) {
+        self.accessed_addresses.remove(addr);
+    }
+
+    /// Remove the storage slot at `key` of the [`Account`] at `addr` from the
+    /// access list of the current transaction, which happens when the call
+    /// that added it is reverted.
+    pub fn remove_account_storage_from_access_list(
+        &mut self,
+        addr: &Address,
+        key: &Word,
+    ) {
+        self.accessed_storage.remove(&(*addr, *key));
+    }
+
+    /// Mark the [`Account`] at `addr` as destructed in the current
+    /// transaction.  Returns whether it was already destructed.
+    pub fn destruct_account(&mut self, addr: &Address) -> bool {
+        !self.destructed_accounts.insert(*addr)
+    }
+
+    /// Unmark the [`Account`] at `addr` as destructed in the current
+    /// transaction, which happens when the call that destructed it is
+    /// reverted.
+    pub fn undo_destruct_account(&mut self, addr: &Address) {
+        self.destructed_accounts.remove(addr);
+    }
+
+    /// Return whether the [`Account`] at `addr` has been destructed in the
+    /// current transaction.
+    pub fn is_destructed(&self, addr: &Address) -> bool {
+        self.destructed_accounts.contains(addr)
+    }
+
+    /// Remove the accounts destructed in the current transaction from the
+    /// state, which happens at the end of the transaction.
+    pub fn clear_destructed_accounts(&mut self) {
+        for addr in self.destructed_accounts.drain() {
+            self.state.remove(&addr);
+        }
+    }
+
+    /// Get the gas refund counter of the current transaction.
+    pub fn refund(&self) -> u64 {
+        self.refund
+    }
+
+    /// Set the gas refund counter of the current transaction, which happens
+    /// when the call that updated it is reverted.
+    pub fn set_refund(&mut self, refund: u64) {
+        self.refund = refund;
+    }
+
+    /// Add `gas` to the refund counter of the current transaction.
+    pub fn add_refund(&mut self, gas: u64) {
+        self.refund += gas;
+    }
+
+    /// Subtract `gas` from the refund counter of the current transaction.
+    /// Returns [`Error::RefundCounterUnderflow`] when the counter would go
+    /// below zero, which doesn't happen in a consistent trace.
+    pub fn sub_refund(&mut self, gas: u64) -> Result<(), Error> {
+        self.refund = self
+            .refund
+            .checked_sub(gas)
+            .ok_or(Error::RefundCounterUnderflow)?;
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -165,4 +389,116 @@ mod statedb_tests {
         assert!(found);
         assert_eq!(value, &Word::from(102));
     }
+
+    #[test]
+    fn statedb_committed_storage() {
+        let addr = address!("0x0000000000000000000000000000000000000001");
+        let key = Word::from(2);
+        let mut statedb = StateDB::new();
+        *statedb.get_storage_mut(&addr, &key).1 = Word::from(100);
+
+        // Committed value is the current one when the slot isn't written
+        let (_, value) = statedb.get_committed_storage(&addr, &key);
+        assert_eq!(value, &Word::from(100));
+
+        // Write the slot twice in the transaction
+        let value_prev = statedb.set_storage(&addr, &key, Word::from(101));
+        assert_eq!(value_prev, Word::from(100));
+        let value_prev = statedb.set_storage(&addr, &key, Word::from(102));
+        assert_eq!(value_prev, Word::from(101));
+        let (_, value) = statedb.get_storage(&addr, &key);
+        assert_eq!(value, &Word::from(102));
+        let (_, value) = statedb.get_committed_storage(&addr, &key);
+        assert_eq!(value, &Word::from(100));
+
+        // Refund counter
+        statedb.add_refund(4800);
+        statedb.sub_refund(800).unwrap();
+        assert_eq!(statedb.refund(), 4000);
+        assert!(matches!(
+            statedb.sub_refund(4001),
+            Err(Error::RefundCounterUnderflow)
+        ));
+        assert_eq!(statedb.refund(), 4000);
+
+        // Commit the transaction
+        statedb.commit_tx();
+        let (_, value) = statedb.get_committed_storage(&addr, &key);
+        assert_eq!(value, &Word::from(102));
+        assert_eq!(statedb.refund(), 0);
+    }
+
+    #[test]
+    fn statedb_code() {
+        let addr_a = address!("0x0000000000000000000000000000000000000001");
+        let addr_b = address!("0x0000000000000000000000000000000000000002");
+        let mut statedb = StateDB::new();
+
+        // Get code of a non-existing account
+        assert_eq!(statedb.get_account_code(&addr_a), (true, &[][..]));
+        assert!(statedb.get_account(&addr_a).1.is_empty());
+
+        // Set code and get it by hash and from the account
+        let code = vec![0x60, 0x01, 0x00];
+        let code_hash = statedb.set_code(code.clone());
+        assert_eq!(code_hash, H256(keccak256(&code)));
+        assert_eq!(statedb.get_code(&code_hash), (true, &code[..]));
+        let (_, acc) = statedb.get_account_mut(&addr_a);
+        acc.codeHash = code_hash;
+        assert_eq!(statedb.get_account_code(&addr_a), (true, &code[..]));
+        assert!(!statedb.get_account(&addr_a).1.is_empty());
+
+        // Get missing code
+        let (_, acc) = statedb.get_account_mut(&addr_b);
+        acc.codeHash = H256::from_low_u64_be(1);
+        assert_eq!(statedb.get_account_code(&addr_b), (false, &[][..]));
+    }
+
+    #[test]
+    fn statedb_destructed_accounts() {
+        let addr_a = address!("0x0000000000000000000000000000000000000001");
+        let mut statedb = StateDB::new();
+
+        let (_, acc) = statedb.get_account_mut(&addr_a);
+        acc.balance = Word::from(100);
+        assert!(!statedb.is_destructed(&addr_a));
+
+        // The account stays in the state until the end of the transaction
+        assert!(!statedb.destruct_account(&addr_a));
+        assert!(statedb.destruct_account(&addr_a));
+        assert!(statedb.is_destructed(&addr_a));
+        assert!(statedb.get_account(&addr_a).0);
+
+        statedb.clear_destructed_accounts();
+        assert!(!statedb.is_destructed(&addr_a));
+        assert!(!statedb.get_account(&addr_a).0);
+    }
+
+    #[test]
+    fn statedb_access_list() {
+        let addr_a = address!("0x0000000000000000000000000000000000000001");
+        let key = Word::from(2);
+        let mut statedb = StateDB::new();
+
+        // The first access is cold and the following ones are warm
+        assert!(!statedb.is_account_warm(&addr_a));
+        assert!(!statedb.add_account_to_access_list(&addr_a));
+        assert!(statedb.add_account_to_access_list(&addr_a));
+        assert!(statedb.is_account_warm(&addr_a));
+        assert!(!statedb.is_account_storage_warm(&addr_a, &key));
+        assert!(!statedb.add_account_storage_to_access_list(&addr_a, &key));
+        assert!(statedb.add_account_storage_to_access_list(&addr_a, &key));
+        assert!(statedb.is_account_storage_warm(&addr_a, &key));
+
+        // Reverted accesses are cold again
+        statedb.remove_account_from_access_list(&addr_a);
+        statedb.remove_account_storage_from_access_list(&addr_a, &key);
+        assert!(!statedb.add_account_to_access_list(&addr_a));
+        assert!(!statedb.add_account_storage_to_access_list(&addr_a, &key));
+
+        // The access list is reset at the end of the transaction
+        statedb.commit_tx();
+        assert!(!statedb.add_account_to_access_list(&addr_a));
+        assert!(!statedb.add_account_storage_to_access_list(&addr_a, &key));
+    }
 }
@@ -94,7 +94,7 @@ func TraceTx(toAddress *common.Address, calldata []byte, config *runtime.Config,
 	if toAddress == nil {
 		_, _, _, err = runtime.Create(calldata, config)
 	} else {
-		_, _, err = runtime.Call(*toAddress, nil, config)
+		_, _, err = runtime.Call(*toAddress, calldata, config)
 	}
 
 	return FormatLogs(tracer.StructLogs()), err
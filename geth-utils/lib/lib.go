@@ -30,7 +30,7 @@ func CreateTrace(config *C.char) *C.char {
 		fmt.Fprintf(os.Stderr, "failed to load trace config, err: %v\n", err)
 	}
 
-	logs, err := gethutil.TraceTx(&gethConfig.target, nil, &gethConfig.config, gethConfig.contracts)
+	logs, err := gethutil.TraceTx(&gethConfig.target, gethConfig.input, &gethConfig.config, gethConfig.contracts)
 	if err != nil {
 		fmt.Fprintf(os.Stderr, "trace stopped unexpectedly, err: %v\n", err)
 	}
@@ -54,23 +54,27 @@ type GethConfig struct {
 	config    runtime.Config
 	contracts []gethutil.Account
 	target    common.Address
+	input     []byte
 }
 
 type BlockConstants struct {
-	Hash        common.Hash    `json:"hash"`
-	Coinbase    common.Address `json:"coinbase"`
-	Timestamp   *hexutil.Big   `json:"timestamp"`
-	BlockNumber *hexutil.Big   `json:"number"`
-	Difficulty  *hexutil.Big   `json:"difficulty"`
-	GasLimit    *hexutil.Big   `json:"gas_limit"`
-	ChainID     *hexutil.Big   `json:"chain_id"`
-	BaseFee     *hexutil.Big   `json:"base_fee"`
+	Hash          common.Hash    `json:"hash"`
+	Coinbase      common.Address `json:"coinbase"`
+	Timestamp     *hexutil.Big   `json:"timestamp"`
+	BlockNumber   *hexutil.Big   `json:"number"`
+	Difficulty    *hexutil.Big   `json:"difficulty"`
+	GasLimit      *hexutil.Big   `json:"gas_limit"`
+	ChainID       *hexutil.Big   `json:"chain_id"`
+	BaseFee       *hexutil.Big   `json:"base_fee"`
+	// Hashes of the previous blocks, from the oldest to the most recent one
+	HistoryHashes []*hexutil.Big `json:"history_hashes"`
 }
 
 type Transaction struct {
 	Origin   common.Address `json:"origin"`
 	GasLimit *hexutil.Big   `json:"gas_limit"`
 	Target   common.Address `json:"target"`
+	Input    hexutil.Bytes  `json:"input"`
 }
 
 type AccountData struct {
@@ -99,6 +103,8 @@ func (this *GethConfig) UnmarshalJSON(b []byte) error {
 		Time:        jConfig.Block.Timestamp.ToInt(),
 		Coinbase:    jConfig.Block.Coinbase,
 		BlockNumber: jConfig.Block.BlockNumber.ToInt(),
+		BaseFee:     jConfig.Block.BaseFee.ToInt(),
+		GetHashFn:   getHashFn(jConfig.Block),
 		ChainConfig: &params.ChainConfig{
 			ChainID:             jConfig.Block.ChainID.ToInt(),
 			HomesteadBlock:      big.NewInt(0),
@@ -130,8 +136,23 @@ func (this *GethConfig) UnmarshalJSON(b []byte) error {
 	}
 
 	this.target = jConfig.Transaction.Target
+	this.input = jConfig.Transaction.Input
 
 	return nil
 }
 
+// getHashFn returns the hash of the block n from the history hashes of the
+// block constants, which contains up to the 256 previous block hashes.
+func getHashFn(block BlockConstants) func(n uint64) common.Hash {
+	return func(n uint64) common.Hash {
+		number := block.BlockNumber.ToInt().Uint64()
+		distance := number - n
+		if n >= number || distance > uint64(len(block.HistoryHashes)) {
+			return common.Hash{}
+		}
+		hash := block.HistoryHashes[uint64(len(block.HistoryHashes))-distance]
+		return common.BigToHash(hash.ToInt())
+	}
+}
+
 func main() {}
@@ -6,6 +6,7 @@ use halo2::{
 };
 
 mod execution;
+mod exp_circuit;
 mod param;
 mod step;
 mod table;
@@ -13,12 +14,14 @@ mod util;
 
 pub use execution::bus_mapping_tmp;
 use execution::{bus_mapping_tmp::Block, ExecutionConfig};
+use exp_circuit::ExpCircuit;
 use table::{FixedTableTag, LookupTable};
 
 /// EvmCircuit implements verification of execution trace of a block.
 #[derive(Clone, Debug)]
 pub struct EvmCircuit<F> {
     fixed_table: [Column<Fixed>; 4],
+    exp_circuit: ExpCircuit,
     execution: ExecutionConfig<F>,
 }
 
@@ -37,6 +40,9 @@ impl<F: FieldExt> EvmCircuit<F> {
         BytecodeTable: LookupTable<F, 4>,
     {
         let fixed_table = [(); 4].map(|_| meta.fixed_column());
+        // The exponentiation table looked up by EXP is constrained by its own
+        // circuit, which is assigned along with the block.
+        let exp_circuit = ExpCircuit::configure(meta, randomness);
 
         let execution = ExecutionConfig::configure(
             meta,
@@ -45,10 +51,12 @@ impl<F: FieldExt> EvmCircuit<F> {
             tx_table,
             rw_table,
             bytecode_table,
+            exp_circuit.clone(),
         );
 
         Self {
             fixed_table,
+            exp_circuit,
             execution,
         }
     }
@@ -83,7 +91,9 @@ impl<F: FieldExt> EvmCircuit<F> {
 
                 Ok(())
             },
-        )
+        )?;
+
+        self.exp_circuit.load_u8_table(layouter)
     }
 
     /// Assign block
@@ -92,6 +102,11 @@ impl<F: FieldExt> EvmCircuit<F> {
         layouter: &mut impl Layouter<F>,
         block: &Block<F>,
     ) -> Result<(), Error> {
+        self.exp_circuit.assign(
+            layouter,
+            &block.exponentiations,
+            block.randomness,
+        )?;
         self.execution.assign_block(layouter, block)
     }
 }
@@ -360,12 +375,22 @@ mod test {
                 .map(|bytecode| bytecode.bytes.len())
                 .sum::<usize>(),
         ));
-
+        // The exponentiation circuit has an all-zero row before the steps of
+        // the exponentiations, and a table of the 256 byte values
+        let num_exp_rows = 1 + block
+            .exponentiations
+            .iter()
+            .map(|exponentiation| exponentiation.bits().len())
+            .sum::<usize>();
+        let k = k.max(log2_ceil(64 + num_exp_rows.max(256)));
+
+        // The randomness is also queried by the exponentiation circuit
         let randomness =
             vec![
                 block.randomness;
-                block.txs.iter().map(|tx| tx.steps.len()).sum::<usize>()
-                    * STEP_HEIGHT
+                (block.txs.iter().map(|tx| tx.steps.len()).sum::<usize>()
+                    * STEP_HEIGHT)
+                    .max(num_exp_rows)
             ];
         let circuit = TestCircuit::<F>::new(block, fixed_table_tags);
 
@@ -18,34 +18,46 @@ use halo2::{
 use std::collections::HashMap;
 
 mod add;
+mod addmod;
 mod bitwise;
 mod byte;
 mod comparator;
 mod dup;
 mod error_oog_pure_memory;
+mod exp;
 mod jump;
 mod jumpdest;
 mod jumpi;
 mod memory;
+mod mul_div_mod;
+mod mulmod;
 mod pc;
 mod pop;
 mod push;
+mod shift;
+mod signed_div_mod;
 mod signextend;
 mod stop;
 mod swap;
 use add::AddGadget;
+use addmod::AddModGadget;
 use bitwise::BitwiseGadget;
 use byte::ByteGadget;
 use comparator::ComparatorGadget;
 use dup::DupGadget;
 use error_oog_pure_memory::ErrorOOGPureMemoryGadget;
+use exp::ExpGadget;
 use jump::JumpGadget;
 use jumpdest::JumpdestGadget;
 use jumpi::JumpiGadget;
 use memory::MemoryGadget;
+use mul_div_mod::MulDivModGadget;
+use mulmod::MulModGadget;
 use pc::PcGadget;
 use pop::PopGadget;
 use push::PushGadget;
+use shift::ShiftGadget;
+use signed_div_mod::SignedDivModGadget;
 use signextend::SignextendGadget;
 use stop::StopGadget;
 use swap::SwapGadget;
@@ -71,6 +83,7 @@ pub mod bus_mapping_tmp {
         pub txs: Vec<Transaction<F>>,
         pub rws: Vec<Rw>,
         pub bytecodes: Vec<Bytecode>,
+        pub exponentiations: Vec<Exponentiation>,
     }
 
     #[derive(Debug, Default)]
@@ -273,6 +286,27 @@ pub mod bus_mapping_tmp {
         }
     }
 
+    #[derive(Debug)]
+    pub struct Exponentiation {
+        pub base: Word,
+        pub exponent: Word,
+    }
+
+    impl Exponentiation {
+        /// Returns the bits of the exponent from the most significant one,
+        /// which are taken by the steps of the square-and-multiply
+        /// exponentiation.  A zero exponent has a single step with a zero bit.
+        pub fn bits(&self) -> Vec<bool> {
+            if self.exponent.is_zero() {
+                return vec![false];
+            }
+            (0..self.exponent.bits())
+                .rev()
+                .map(|idx| self.exponent.bit(idx))
+                .collect()
+        }
+    }
+
     #[derive(Clone, Debug)]
     pub enum Rw {
         TxAccessListAccount {
@@ -395,36 +429,44 @@ pub(crate) struct ExecutionConfig<F> {
     step: Step<F>,
     presets_map: HashMap<ExecutionState, Vec<Preset<F>>>,
     add_gadget: AddGadget<F>,
+    addmod_gadget: AddModGadget<F>,
     bitwise_gadget: BitwiseGadget<F>,
     byte_gadget: ByteGadget<F>,
     comparator_gadget: ComparatorGadget<F>,
     dup_gadget: DupGadget<F>,
     error_oog_pure_memory_gadget: ErrorOOGPureMemoryGadget<F>,
+    exp_gadget: ExpGadget<F>,
     jump_gadget: JumpGadget<F>,
     jumpdest_gadget: JumpdestGadget<F>,
     jumpi_gadget: JumpiGadget<F>,
     memory_gadget: MemoryGadget<F>,
+    mul_div_mod_gadget: MulDivModGadget<F>,
+    mulmod_gadget: MulModGadget<F>,
     pc_gadget: PcGadget<F>,
     pop_gadget: PopGadget<F>,
     push_gadget: PushGadget<F>,
+    shift_gadget: ShiftGadget<F>,
+    signed_div_mod_gadget: SignedDivModGadget<F>,
     signextend_gadget: SignextendGadget<F>,
     stop_gadget: StopGadget<F>,
     swap_gadget: SwapGadget<F>,
 }
 
 impl<F: FieldExt> ExecutionConfig<F> {
-    pub(crate) fn configure<TxTable, RwTable, BytecodeTable>(
+    pub(crate) fn configure<TxTable, RwTable, BytecodeTable, ExpTable>(
         meta: &mut ConstraintSystem<F>,
         randomness: Column<Instance>,
         fixed_table: [Column<Fixed>; 4],
         tx_table: TxTable,
         rw_table: RwTable,
         bytecode_table: BytecodeTable,
+        exp_table: ExpTable,
     ) -> Self
     where
         TxTable: LookupTable<F, 4>,
         RwTable: LookupTable<F, 8>,
         BytecodeTable: LookupTable<F, 4>,
+        ExpTable: LookupTable<F, 4>,
     {
         let q_step = meta.complex_selector();
         let qs_byte_lookup = meta.advice_column();
@@ -507,18 +549,24 @@ impl<F: FieldExt> ExecutionConfig<F> {
         let config = Self {
             q_step,
             add_gadget: configure_gadget!(),
+            addmod_gadget: configure_gadget!(),
             bitwise_gadget: configure_gadget!(),
             byte_gadget: configure_gadget!(),
             comparator_gadget: configure_gadget!(),
             dup_gadget: configure_gadget!(),
             error_oog_pure_memory_gadget: configure_gadget!(),
+            exp_gadget: configure_gadget!(),
             jump_gadget: configure_gadget!(),
             jumpdest_gadget: configure_gadget!(),
             jumpi_gadget: configure_gadget!(),
             memory_gadget: configure_gadget!(),
+            mul_div_mod_gadget: configure_gadget!(),
+            mulmod_gadget: configure_gadget!(),
             pc_gadget: configure_gadget!(),
             pop_gadget: configure_gadget!(),
             push_gadget: configure_gadget!(),
+            shift_gadget: configure_gadget!(),
+            signed_div_mod_gadget: configure_gadget!(),
             signextend_gadget: configure_gadget!(),
             stop_gadget: configure_gadget!(),
             swap_gadget: configure_gadget!(),
@@ -533,6 +581,7 @@ impl<F: FieldExt> ExecutionConfig<F> {
             tx_table,
             rw_table,
             bytecode_table,
+            exp_table,
             independent_lookups,
         );
 
@@ -580,18 +629,21 @@ impl<F: FieldExt> ExecutionConfig<F> {
         gadget
     }
 
-    fn configure_lookup<TxTable, RwTable, BytecodeTable>(
+    #[allow(clippy::too_many_arguments)]
+    fn configure_lookup<TxTable, RwTable, BytecodeTable, ExpTable>(
         meta: &mut ConstraintSystem<F>,
         q_step: Selector,
         fixed_table: [Column<Fixed>; 4],
         tx_table: TxTable,
         rw_table: RwTable,
         bytecode_table: BytecodeTable,
+        exp_table: ExpTable,
         independent_lookups: Vec<Vec<Lookup<F>>>,
     ) where
         TxTable: LookupTable<F, 4>,
         RwTable: LookupTable<F, 8>,
         BytecodeTable: LookupTable<F, 4>,
+        ExpTable: LookupTable<F, 4>,
     {
         // Because one and only one ExecutionState is enabled at a step, we then
         // know only one of independent_lookups will be enabled at a step, so we
@@ -652,6 +704,7 @@ impl<F: FieldExt> ExecutionConfig<F> {
         lookup!(Table::Tx, tx_table);
         lookup!(Table::Rw, rw_table);
         lookup!(Table::Bytecode, bytecode_table);
+        lookup!(Table::Exp, exp_table);
     }
 
     pub fn assign_block(
@@ -720,12 +773,20 @@ impl<F: FieldExt> ExecutionConfig<F> {
         match step.execution_state {
             ExecutionState::STOP => assign_exec_step!(self.stop_gadget),
             ExecutionState::ADD => assign_exec_step!(self.add_gadget),
+            ExecutionState::MUL => assign_exec_step!(self.mul_div_mod_gadget),
+            ExecutionState::SDIV => {
+                assign_exec_step!(self.signed_div_mod_gadget)
+            }
+            ExecutionState::ADDMOD => assign_exec_step!(self.addmod_gadget),
+            ExecutionState::MULMOD => assign_exec_step!(self.mulmod_gadget),
+            ExecutionState::EXP => assign_exec_step!(self.exp_gadget),
             ExecutionState::BITWISE => assign_exec_step!(self.bitwise_gadget),
             ExecutionState::SIGNEXTEND => {
                 assign_exec_step!(self.signextend_gadget)
             }
             ExecutionState::CMP => assign_exec_step!(self.comparator_gadget),
             ExecutionState::BYTE => assign_exec_step!(self.byte_gadget),
+            ExecutionState::SHL => assign_exec_step!(self.shift_gadget),
             ExecutionState::POP => assign_exec_step!(self.pop_gadget),
             ExecutionState::MEMORY => assign_exec_step!(self.memory_gadget),
             ExecutionState::PC => assign_exec_step!(self.pc_gadget),
@@ -199,6 +199,7 @@ mod test {
                 },
             ],
             bytecodes: vec![bytecode],
+            ..Default::default()
         };
         assert_eq!(run_test_circuit_incomplete_fixed_table(block), Ok(()));
     }
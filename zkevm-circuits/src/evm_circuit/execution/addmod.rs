@@ -0,0 +1,321 @@
+use crate::{
+    evm_circuit::{
+        execution::{
+            bus_mapping_tmp::{Block, Call, ExecStep, Transaction},
+            ExecutionGadget,
+        },
+        step::ExecutionState,
+        util::{
+            common_gadget::SameContextGadget,
+            constraint_builder::{
+                ConstraintBuilder, StepStateTransition, Transition::Delta,
+            },
+            math_gadget::{IsZeroGadget, LtWordGadget, MulAddWords512Gadget},
+            split_u256, sum, word_halves_expr, word_limbs_expr, Cell, Word,
+        },
+    },
+    util::Expr,
+};
+use bus_mapping::eth_types::{self, ToLittleEndian};
+use halo2::{arithmetic::FieldExt, circuit::Region, plonk::Error};
+
+// AddModGadget verifies ADDMOD by checking a + b == k * n + r with the 512-bit
+// multiplication, where the quotient k has up to 257 bits, and r < n. The sum
+// is given by the halves of a and b added together, so it doesn't need to fit
+// in 256 bits. When n is zero, the sum is replaced by zero, which forces r to
+// be zero as well.
+#[derive(Clone, Debug)]
+pub(crate) struct AddModGadget<F> {
+    same_context: SameContextGadget<F>,
+    words: [Word<F>; 4],
+    k_lo: Word<F>,
+    k_hi: Cell<F>,
+    n_is_zero: IsZeroGadget<F>,
+    mul_add_words_512: MulAddWords512Gadget<F>,
+    lt: LtWordGadget<F>,
+}
+
+impl<F: FieldExt> ExecutionGadget<F> for AddModGadget<F> {
+    const NAME: &'static str = "ADDMOD";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::ADDMOD;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+
+        let a = cb.query_word();
+        let b = cb.query_word();
+        let n = cb.query_word();
+        let r = cb.query_word();
+        let k_lo = cb.query_word();
+        let k_hi = cb.query_bool();
+        let n_is_zero = IsZeroGadget::construct(cb, sum::expr(&n.cells));
+
+        // 0 * 0 + (a + b) == k * n + r, with a + b being zero when n is zero
+        let [a_lo, a_hi] = word_halves_expr(&a);
+        let [b_lo, b_hi] = word_halves_expr(&b);
+        let sum_halves = [a_lo + b_lo, a_hi + b_hi]
+            .map(|half| (1.expr() - n_is_zero.expr()) * half);
+        let mul_add_words_512 = MulAddWords512Gadget::construct(
+            cb,
+            [
+                [0.expr(), 0.expr(), 0.expr(), 0.expr()],
+                [0.expr(), 0.expr(), 0.expr(), 0.expr()],
+                word_limbs_expr(&n),
+            ],
+            [
+                word_limbs_expr(&k_lo),
+                [k_hi.expr(), 0.expr(), 0.expr(), 0.expr()],
+            ],
+            [sum_halves, word_halves_expr(&r)],
+        );
+        let lt = LtWordGadget::construct(cb, &r, &n);
+        cb.require_zero(
+            "r < n when n != 0",
+            (1.expr() - n_is_zero.expr()) * (1.expr() - lt.expr()),
+        );
+
+        // Pop a, b and n from the stack, push r on the stack
+        cb.stack_pop(a.expr());
+        cb.stack_pop(b.expr());
+        cb.stack_pop(n.expr());
+        cb.stack_push(r.expr());
+
+        // State transition
+        let step_state_transition = StepStateTransition {
+            rw_counter: Delta(4.expr()),
+            program_counter: Delta(1.expr()),
+            stack_pointer: Delta(2.expr()),
+            ..Default::default()
+        };
+        let same_context = SameContextGadget::construct(
+            cb,
+            opcode,
+            step_state_transition,
+            None,
+        );
+
+        Self {
+            same_context,
+            words: [a, b, n, r],
+            k_lo,
+            k_hi,
+            n_is_zero,
+            mul_add_words_512,
+            lt,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction<F>,
+        _: &Call<F>,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        self.same_context.assign_exec_step(region, offset, step)?;
+
+        let [a, b, n, r] = [0, 1, 2, 3]
+            .map(|idx| block.rws[step.rw_indices[idx]].stack_value());
+        for (word, value) in self.words.iter().zip([a, b, n, r]) {
+            word.assign(region, offset, Some(value.to_le_bytes()))?;
+        }
+        self.n_is_zero
+            .assign(region, offset, sum::value(&n.to_le_bytes()))?;
+
+        // The sum of the halves is replaced by zero when n is zero
+        let (sum_halves, k_lo, k_hi) = if n.is_zero() {
+            (
+                [eth_types::Word::zero(); 2],
+                eth_types::Word::zero(),
+                eth_types::Word::zero(),
+            )
+        } else {
+            let (a_lo, a_hi) = split_u256(&a);
+            let (b_lo, b_hi) = split_u256(&b);
+            // The 257-bit sum is computed in 512 bits
+            let k = (a.full_mul(1.into()) + b.full_mul(1.into()))
+                .div_mod(n.into())
+                .0;
+            (
+                [a_lo + b_lo, a_hi + b_hi],
+                eth_types::Word([k.0[0], k.0[1], k.0[2], k.0[3]]),
+                eth_types::Word::from(k.0[4]),
+            )
+        };
+        self.k_lo.assign(region, offset, Some(k_lo.to_le_bytes()))?;
+        self.k_hi
+            .assign(region, offset, Some(F::from(k_hi.low_u64())))?;
+        let (r_lo, r_hi) = split_u256(&r);
+        self.mul_add_words_512.assign(
+            region,
+            offset,
+            [eth_types::Word::zero(), eth_types::Word::zero(), n],
+            [k_lo, k_hi],
+            [sum_halves, [r_lo, r_hi]],
+        )?;
+        self.lt.assign(region, offset, r, n)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::evm_circuit::{
+        execution::bus_mapping_tmp::{
+            Block, Bytecode, Call, ExecStep, Rw, Transaction,
+        },
+        step::ExecutionState,
+        test::{rand_word, run_test_circuit_incomplete_fixed_table},
+        util::RandomLinearCombination,
+    };
+    use bus_mapping::{
+        eth_types::{ToBigEndian, ToLittleEndian, Word},
+        evm::OpcodeId,
+    };
+    use halo2::{arithmetic::BaseExt, dev::VerifyFailure};
+    use pairing::bn256::Fr as Fp;
+
+    fn run_test(
+        a: Word,
+        b: Word,
+        n: Word,
+        r: Word,
+    ) -> Result<(), Vec<VerifyFailure>> {
+        let randomness = Fp::rand();
+        let bytecode = Bytecode::new(
+            [
+                vec![OpcodeId::PUSH32.as_u8()],
+                n.to_be_bytes().to_vec(),
+                vec![OpcodeId::PUSH32.as_u8()],
+                b.to_be_bytes().to_vec(),
+                vec![OpcodeId::PUSH32.as_u8()],
+                a.to_be_bytes().to_vec(),
+                vec![OpcodeId::ADDMOD.as_u8(), OpcodeId::STOP.as_u8()],
+            ]
+            .concat(),
+        );
+        let block = Block {
+            randomness,
+            txs: vec![Transaction {
+                calls: vec![Call {
+                    id: 1,
+                    is_root: false,
+                    is_create: false,
+                    opcode_source:
+                        RandomLinearCombination::random_linear_combine(
+                            bytecode.hash.to_le_bytes(),
+                            randomness,
+                        ),
+                }],
+                steps: vec![
+                    ExecStep {
+                        rw_indices: vec![0, 1, 2, 3],
+                        execution_state: ExecutionState::ADDMOD,
+                        rw_counter: 1,
+                        program_counter: 99,
+                        stack_pointer: 1021,
+                        gas_left: 8,
+                        gas_cost: 8,
+                        opcode: Some(OpcodeId::ADDMOD),
+                        ..Default::default()
+                    },
+                    ExecStep {
+                        execution_state: ExecutionState::STOP,
+                        rw_counter: 5,
+                        program_counter: 100,
+                        stack_pointer: 1023,
+                        gas_left: 0,
+                        opcode: Some(OpcodeId::STOP),
+                        ..Default::default()
+                    },
+                ],
+                ..Default::default()
+            }],
+            rws: vec![
+                Rw::Stack {
+                    rw_counter: 1,
+                    is_write: false,
+                    call_id: 1,
+                    stack_pointer: 1021,
+                    value: a,
+                },
+                Rw::Stack {
+                    rw_counter: 2,
+                    is_write: false,
+                    call_id: 1,
+                    stack_pointer: 1022,
+                    value: b,
+                },
+                Rw::Stack {
+                    rw_counter: 3,
+                    is_write: false,
+                    call_id: 1,
+                    stack_pointer: 1023,
+                    value: n,
+                },
+                Rw::Stack {
+                    rw_counter: 4,
+                    is_write: true,
+                    call_id: 1,
+                    stack_pointer: 1023,
+                    value: r,
+                },
+            ],
+            bytecodes: vec![bytecode],
+            ..Default::default()
+        };
+        run_test_circuit_incomplete_fixed_table(block)
+    }
+
+    fn test_ok(a: Word, b: Word, n: Word, r: Word) {
+        assert_eq!(run_test(a, b, n, r), Ok(()));
+    }
+
+    fn test_err(a: Word, b: Word, n: Word, r: Word) {
+        assert!(run_test(a, b, n, r).is_err());
+    }
+
+    #[test]
+    fn addmod_gadget_simple() {
+        test_ok(7.into(), 18.into(), 10.into(), 5.into());
+        test_ok(7.into(), 18.into(), 1.into(), Word::zero());
+        test_ok(7.into(), 18.into(), Word::zero(), Word::zero());
+    }
+
+    #[test]
+    fn addmod_gadget_overflow() {
+        // (2^256 - 1) + (2^256 - 1) = 2^257 - 2 = 2 ⋅ (2^256 - 2) + 2
+        test_ok(Word::MAX, Word::MAX, Word::MAX - 1, 2.into());
+        // (2^256 - 1) + 2 = 2^256 + 1 ≡ 3 (mod 7), since 2^256 ≡ 2 (mod 7)
+        test_ok(Word::MAX, 2.into(), 7.into(), 3.into());
+    }
+
+    #[test]
+    fn addmod_gadget_rand() {
+        let a = rand_word();
+        let b = rand_word();
+        let n = rand_word();
+        let (sum, overflow) = (a % n).overflowing_add(b % n);
+        let r = if overflow || sum >= n {
+            sum.overflowing_sub(n).0
+        } else {
+            sum
+        };
+        test_ok(a, b, n, r);
+    }
+
+    #[test]
+    fn addmod_gadget_wrong_result() {
+        // r is congruent to the sum but not less than n
+        test_err(7.into(), 18.into(), 10.into(), 15.into());
+        // The result is zero when n is zero
+        test_err(7.into(), 18.into(), Word::zero(), 25.into());
+        // (2^256 - 1) + 2 wrapped at 2^256 is 1 ≡ 1 (mod 7), while the sum
+        // itself is ≡ 3 (mod 7)
+        test_err(Word::MAX, 2.into(), 7.into(), 1.into());
+    }
+}
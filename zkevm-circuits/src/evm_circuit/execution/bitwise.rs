@@ -308,6 +308,7 @@ mod test {
                 },
             ],
             bytecodes: vec![bytecode],
+            ..Default::default()
         };
         assert_eq!(run_test_circuit_complete_fixed_table(block), Ok(()));
     }
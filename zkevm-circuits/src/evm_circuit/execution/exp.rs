@@ -0,0 +1,263 @@
+use crate::{
+    evm_circuit::{
+        execution::{
+            bus_mapping_tmp::{Block, Call, ExecStep, Transaction},
+            ExecutionGadget,
+        },
+        step::ExecutionState,
+        table::Lookup,
+        util::{
+            common_gadget::SameContextGadget,
+            constraint_builder::{
+                ConstraintBuilder, StepStateTransition, Transition::Delta,
+            },
+            math_gadget::ByteSizeGadget,
+            Word,
+        },
+    },
+    util::Expr,
+};
+use bus_mapping::{eth_types::ToLittleEndian, evm::GasCost};
+use halo2::{arithmetic::FieldExt, circuit::Region, plonk::Error};
+
+// ExpGadget verifies EXP by looking up the last step of the square-and-multiply
+// exponentiation in the exponentiation table, whose consecutive steps are
+// verified by the ExpCircuit, since 256 steps don't fit in a single execution
+// step. The gas cost is the constant cost plus the cost of each byte of the
+// exponent.
+#[derive(Clone, Debug)]
+pub(crate) struct ExpGadget<F> {
+    same_context: SameContextGadget<F>,
+    base: Word<F>,
+    exponent: Word<F>,
+    exponentiation: Word<F>,
+    exponent_byte_size: ByteSizeGadget<F>,
+}
+
+impl<F: FieldExt> ExecutionGadget<F> for ExpGadget<F> {
+    const NAME: &'static str = "EXP";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::EXP;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+
+        let base = cb.query_word();
+        let exponent = cb.query_word();
+        let exponentiation = cb.query_word();
+
+        // Pop base and exponent from the stack, push exponentiation on the
+        // stack
+        cb.stack_pop(base.expr());
+        cb.stack_pop(exponent.expr());
+        cb.stack_push(exponentiation.expr());
+
+        // Lookup the last step of the exponentiation
+        cb.add_lookup(Lookup::Exp {
+            is_last: 1.expr(),
+            base: base.expr(),
+            exponent: exponent.expr(),
+            exponentiation: exponentiation.expr(),
+        });
+
+        // Gas cost of EXP is dynamic with the byte size of the exponent
+        let exponent_byte_size = ByteSizeGadget::construct(cb, &exponent);
+        let dynamic_gas_cost = GasCost::SLOW.expr()
+            + GasCost::EXP_BYTE.expr() * exponent_byte_size.expr();
+
+        // State transition
+        let step_state_transition = StepStateTransition {
+            rw_counter: Delta(3.expr()),
+            program_counter: Delta(1.expr()),
+            stack_pointer: Delta(1.expr()),
+            ..Default::default()
+        };
+        let same_context = SameContextGadget::construct(
+            cb,
+            opcode,
+            step_state_transition,
+            Some(dynamic_gas_cost),
+        );
+
+        Self {
+            same_context,
+            base,
+            exponent,
+            exponentiation,
+            exponent_byte_size,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction<F>,
+        _: &Call<F>,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        self.same_context.assign_exec_step(region, offset, step)?;
+
+        let [base, exponent, exponentiation] =
+            [0, 1, 2].map(|idx| block.rws[step.rw_indices[idx]].stack_value());
+        self.base.assign(region, offset, Some(base.to_le_bytes()))?;
+        self.exponent
+            .assign(region, offset, Some(exponent.to_le_bytes()))?;
+        self.exponentiation.assign(
+            region,
+            offset,
+            Some(exponentiation.to_le_bytes()),
+        )?;
+        self.exponent_byte_size.assign(region, offset, exponent)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::evm_circuit::{
+        execution::bus_mapping_tmp::{
+            Block, Bytecode, Call, ExecStep, Exponentiation, Rw, Transaction,
+        },
+        step::ExecutionState,
+        test::{rand_word, run_test_circuit_incomplete_fixed_table},
+        util::RandomLinearCombination,
+    };
+    use bus_mapping::{
+        eth_types::{ToBigEndian, ToLittleEndian, Word},
+        evm::{GasCost, OpcodeId},
+    };
+    use halo2::{arithmetic::BaseExt, dev::VerifyFailure};
+    use pairing::bn256::Fr as Fp;
+
+    fn run_test(
+        base: Word,
+        exponent: Word,
+        exponentiation: Word,
+    ) -> Result<(), Vec<VerifyFailure>> {
+        let randomness = Fp::rand();
+        let bytecode = Bytecode::new(
+            [
+                vec![OpcodeId::PUSH32.as_u8()],
+                exponent.to_be_bytes().to_vec(),
+                vec![OpcodeId::PUSH32.as_u8()],
+                base.to_be_bytes().to_vec(),
+                vec![OpcodeId::EXP.as_u8(), OpcodeId::STOP.as_u8()],
+            ]
+            .concat(),
+        );
+        let gas_cost = GasCost::SLOW.as_u64()
+            + GasCost::EXP_BYTE.as_u64() * ((exponent.bits() as u64 + 7) / 8);
+        let block = Block {
+            randomness,
+            txs: vec![Transaction {
+                calls: vec![Call {
+                    id: 1,
+                    is_root: false,
+                    is_create: false,
+                    opcode_source:
+                        RandomLinearCombination::random_linear_combine(
+                            bytecode.hash.to_le_bytes(),
+                            randomness,
+                        ),
+                }],
+                steps: vec![
+                    ExecStep {
+                        rw_indices: vec![0, 1, 2],
+                        execution_state: ExecutionState::EXP,
+                        rw_counter: 1,
+                        program_counter: 66,
+                        stack_pointer: 1022,
+                        gas_left: gas_cost,
+                        gas_cost,
+                        opcode: Some(OpcodeId::EXP),
+                        ..Default::default()
+                    },
+                    ExecStep {
+                        execution_state: ExecutionState::STOP,
+                        rw_counter: 4,
+                        program_counter: 67,
+                        stack_pointer: 1023,
+                        gas_left: 0,
+                        opcode: Some(OpcodeId::STOP),
+                        ..Default::default()
+                    },
+                ],
+                ..Default::default()
+            }],
+            rws: vec![
+                Rw::Stack {
+                    rw_counter: 1,
+                    is_write: false,
+                    call_id: 1,
+                    stack_pointer: 1022,
+                    value: base,
+                },
+                Rw::Stack {
+                    rw_counter: 2,
+                    is_write: false,
+                    call_id: 1,
+                    stack_pointer: 1023,
+                    value: exponent,
+                },
+                Rw::Stack {
+                    rw_counter: 3,
+                    is_write: true,
+                    call_id: 1,
+                    stack_pointer: 1023,
+                    value: exponentiation,
+                },
+            ],
+            bytecodes: vec![bytecode],
+            exponentiations: vec![Exponentiation { base, exponent }],
+        };
+        run_test_circuit_incomplete_fixed_table(block)
+    }
+
+    fn test_ok(base: Word, exponent: Word, exponentiation: Word) {
+        assert_eq!(run_test(base, exponent, exponentiation), Ok(()));
+    }
+
+    fn test_err(base: Word, exponent: Word, exponentiation: Word) {
+        assert!(run_test(base, exponent, exponentiation).is_err());
+    }
+
+    #[test]
+    fn exp_gadget_simple() {
+        test_ok(3.into(), 5.into(), 243.into());
+        test_ok(2.into(), 255.into(), Word::one() << 255);
+        test_ok(2.into(), 256.into(), Word::zero());
+        test_ok(0x0102.into(), 0x0201.into(), {
+            let mut exponentiation = Word::one();
+            for _ in 0..0x0201 {
+                exponentiation =
+                    exponentiation.overflowing_mul(0x0102.into()).0;
+            }
+            exponentiation
+        });
+    }
+
+    #[test]
+    fn exp_gadget_zero() {
+        test_ok(Word::zero(), Word::zero(), Word::one());
+        test_ok(7.into(), Word::zero(), Word::one());
+        test_ok(Word::zero(), 7.into(), Word::zero());
+    }
+
+    #[test]
+    fn exp_gadget_rand() {
+        let base = rand_word();
+        let exponent = rand_word();
+        test_ok(base, exponent, base.overflowing_pow(exponent).0);
+    }
+
+    #[test]
+    fn exp_gadget_wrong_result() {
+        test_err(3.into(), 5.into(), 242.into());
+        test_err(Word::zero(), Word::zero(), Word::zero());
+        // 2^256 wraps to zero
+        test_err(2.into(), 256.into(), Word::MAX);
+    }
+}
@@ -176,6 +176,7 @@ mod test {
                 value: Word::from(destination),
             }],
             bytecodes: vec![bytecode],
+            ..Default::default()
         };
         assert_eq!(run_test_circuit_incomplete_fixed_table(block), Ok(()));
     }
@@ -114,6 +114,7 @@ mod test {
             }],
             rws: vec![],
             bytecodes: vec![bytecode],
+            ..Default::default()
         };
         assert_eq!(run_test_circuit_incomplete_fixed_table(block), Ok(()));
     }
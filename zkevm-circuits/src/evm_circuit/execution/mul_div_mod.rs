@@ -0,0 +1,336 @@
+use crate::{
+    evm_circuit::{
+        execution::{
+            bus_mapping_tmp::{Block, Call, ExecStep, Transaction},
+            ExecutionGadget,
+        },
+        step::ExecutionState,
+        util::{
+            common_gadget::SameContextGadget,
+            constraint_builder::{
+                ConstraintBuilder, StepStateTransition, Transition::Delta,
+            },
+            math_gadget::{
+                IsEqualGadget, IsZeroGadget, LtWordGadget, MulAddWordsGadget,
+            },
+            select, sum,
+        },
+    },
+    util::Expr,
+};
+use bus_mapping::{
+    eth_types::{ToLittleEndian, Word},
+    evm::OpcodeId,
+};
+use halo2::{arithmetic::FieldExt, circuit::Region, plonk::Error};
+
+// MulDivModGadget verifies MUL, DIV and MOD at the same time by checking
+// a * b + c == d, where:
+// - MUL: we annotate stack as [a, b, ...] and [d, ...], and c is zero.
+// - DIV: we annotate stack as [d, b, ...] and [a, ...], and c is the remainder.
+// - MOD: we annotate stack as [d, b, ...] and [c, ...], and a is the quotient.
+// For DIV and MOD, the remainder is required to be less than the divisor and
+// a * b + c is required not to overflow. When the divisor b is zero, we set a
+// to zero and c to d, and the pushed result is zero.
+#[derive(Clone, Debug)]
+pub(crate) struct MulDivModGadget<F> {
+    same_context: SameContextGadget<F>,
+    mul_add_words: MulAddWordsGadget<F>,
+    is_div: IsEqualGadget<F>,
+    is_mod: IsEqualGadget<F>,
+    divisor_is_zero: IsZeroGadget<F>,
+    lt_word: LtWordGadget<F>,
+}
+
+impl<F: FieldExt> ExecutionGadget<F> for MulDivModGadget<F> {
+    const NAME: &'static str = "MUL";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::MUL;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+
+        let is_div =
+            IsEqualGadget::construct(cb, opcode.expr(), OpcodeId::DIV.expr());
+        let is_mod =
+            IsEqualGadget::construct(cb, opcode.expr(), OpcodeId::MOD.expr());
+        let is_mul = 1.expr() - is_div.expr() - is_mod.expr();
+
+        let a = cb.query_word();
+        let b = cb.query_word();
+        let c = cb.query_word();
+        let d = cb.query_word();
+        let mul_add_words = MulAddWordsGadget::construct(
+            cb,
+            [a.clone(), b.clone(), c.clone(), d.clone()],
+        );
+        let divisor_is_zero = IsZeroGadget::construct(cb, sum::expr(&b.cells));
+        let lt_word = LtWordGadget::construct(cb, &c, &b);
+
+        // MUL: c is zero and the product is allowed to overflow
+        cb.require_zero("c == 0 for MUL", is_mul.clone() * sum::expr(&c.cells));
+        // DIV and MOD: remainder < divisor when divisor != 0, and no overflow
+        cb.require_zero(
+            "c < b for DIV and MOD when b != 0",
+            (1.expr() - is_mul.clone())
+                * (1.expr() - divisor_is_zero.expr())
+                * (1.expr() - lt_word.expr()),
+        );
+        cb.require_zero(
+            "a ⋅ b + c doesn't overflow for DIV and MOD",
+            (1.expr() - is_mul.clone()) * mul_add_words.overflow(),
+        );
+
+        // MUL: Pop a and b from the stack, push d on the stack
+        // DIV: Pop d and b from the stack, push a on the stack
+        // MOD: Pop d and b from the stack, push c on the stack
+        // When DIV or MOD has a zero divisor, push zero on the stack.
+        cb.stack_pop(select::expr(is_mul.clone(), a.expr(), d.expr()));
+        cb.stack_pop(b.expr());
+        cb.stack_push(
+            is_mul * d.expr()
+                + (1.expr() - divisor_is_zero.expr())
+                    * (is_div.expr() * a.expr() + is_mod.expr() * c.expr()),
+        );
+
+        // State transition
+        let step_state_transition = StepStateTransition {
+            rw_counter: Delta(3.expr()),
+            program_counter: Delta(1.expr()),
+            stack_pointer: Delta(1.expr()),
+            ..Default::default()
+        };
+        let same_context = SameContextGadget::construct(
+            cb,
+            opcode,
+            step_state_transition,
+            None,
+        );
+
+        Self {
+            same_context,
+            mul_add_words,
+            is_div,
+            is_mod,
+            divisor_is_zero,
+            lt_word,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction<F>,
+        _: &Call<F>,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        self.same_context.assign_exec_step(region, offset, step)?;
+
+        let opcode = step.opcode.unwrap();
+        self.is_div.assign(
+            region,
+            offset,
+            F::from(opcode.as_u64()),
+            F::from(OpcodeId::DIV.as_u64()),
+        )?;
+        self.is_mod.assign(
+            region,
+            offset,
+            F::from(opcode.as_u64()),
+            F::from(OpcodeId::MOD.as_u64()),
+        )?;
+
+        let [pop1, pop2, push] =
+            [0, 1, 2].map(|idx| block.rws[step.rw_indices[idx]].stack_value());
+        let (a, b, c, d) = match opcode {
+            OpcodeId::MUL => (pop1, pop2, Word::zero(), push),
+            _ if pop2.is_zero() => (Word::zero(), pop2, pop1, pop1),
+            OpcodeId::DIV => (push, pop2, pop1 - push * pop2, pop1),
+            OpcodeId::MOD => (pop1 / pop2, pop2, push, pop1),
+            _ => unreachable!(),
+        };
+        self.mul_add_words.assign(region, offset, [a, b, c, d])?;
+        self.divisor_is_zero.assign(
+            region,
+            offset,
+            sum::value(&b.to_le_bytes()),
+        )?;
+        self.lt_word.assign(region, offset, c, b)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::evm_circuit::{
+        execution::bus_mapping_tmp::{
+            Block, Bytecode, Call, ExecStep, Rw, Transaction,
+        },
+        step::ExecutionState,
+        test::{rand_word, run_test_circuit_incomplete_fixed_table},
+        util::RandomLinearCombination,
+    };
+    use bus_mapping::{
+        eth_types::{ToBigEndian, ToLittleEndian, Word},
+        evm::OpcodeId,
+    };
+    use halo2::{arithmetic::BaseExt, dev::VerifyFailure};
+    use pairing::bn256::Fr as Fp;
+
+    fn run_test(
+        opcode: OpcodeId,
+        a: Word,
+        b: Word,
+        c: Word,
+    ) -> Result<(), Vec<VerifyFailure>> {
+        let randomness = Fp::rand();
+        let bytecode = Bytecode::new(
+            [
+                vec![OpcodeId::PUSH32.as_u8()],
+                b.to_be_bytes().to_vec(),
+                vec![OpcodeId::PUSH32.as_u8()],
+                a.to_be_bytes().to_vec(),
+                vec![opcode.as_u8(), OpcodeId::STOP.as_u8()],
+            ]
+            .concat(),
+        );
+        let block = Block {
+            randomness,
+            txs: vec![Transaction {
+                calls: vec![Call {
+                    id: 1,
+                    is_root: false,
+                    is_create: false,
+                    opcode_source:
+                        RandomLinearCombination::random_linear_combine(
+                            bytecode.hash.to_le_bytes(),
+                            randomness,
+                        ),
+                }],
+                steps: vec![
+                    ExecStep {
+                        rw_indices: vec![0, 1, 2],
+                        execution_state: ExecutionState::MUL,
+                        rw_counter: 1,
+                        program_counter: 66,
+                        stack_pointer: 1022,
+                        gas_left: 5,
+                        gas_cost: 5,
+                        opcode: Some(opcode),
+                        ..Default::default()
+                    },
+                    ExecStep {
+                        execution_state: ExecutionState::STOP,
+                        rw_counter: 4,
+                        program_counter: 67,
+                        stack_pointer: 1023,
+                        gas_left: 0,
+                        opcode: Some(OpcodeId::STOP),
+                        ..Default::default()
+                    },
+                ],
+                ..Default::default()
+            }],
+            rws: vec![
+                Rw::Stack {
+                    rw_counter: 1,
+                    is_write: false,
+                    call_id: 1,
+                    stack_pointer: 1022,
+                    value: a,
+                },
+                Rw::Stack {
+                    rw_counter: 2,
+                    is_write: false,
+                    call_id: 1,
+                    stack_pointer: 1023,
+                    value: b,
+                },
+                Rw::Stack {
+                    rw_counter: 3,
+                    is_write: true,
+                    call_id: 1,
+                    stack_pointer: 1023,
+                    value: c,
+                },
+            ],
+            bytecodes: vec![bytecode],
+            ..Default::default()
+        };
+        run_test_circuit_incomplete_fixed_table(block)
+    }
+
+    fn test_ok(opcode: OpcodeId, a: Word, b: Word, c: Word) {
+        assert_eq!(run_test(opcode, a, b, c), Ok(()));
+    }
+
+    fn test_err(opcode: OpcodeId, a: Word, b: Word, c: Word) {
+        assert!(run_test(opcode, a, b, c).is_err());
+    }
+
+    #[test]
+    fn mul_div_mod_gadget_simple() {
+        test_ok(
+            OpcodeId::MUL,
+            0x030201.into(),
+            0x0201.into(),
+            0x06070401.into(),
+        );
+        test_ok(
+            OpcodeId::DIV,
+            0x06070401.into(),
+            0x0201.into(),
+            0x030201.into(),
+        );
+        test_ok(OpcodeId::MOD, 0x06070403.into(), 0x0201.into(), 0x02.into());
+    }
+
+    #[test]
+    fn mul_div_mod_gadget_divisor_zero() {
+        test_ok(OpcodeId::DIV, 0x030201.into(), Word::zero(), Word::zero());
+        test_ok(OpcodeId::MOD, 0x030201.into(), Word::zero(), Word::zero());
+    }
+
+    #[test]
+    fn mul_div_mod_gadget_rand() {
+        let a = rand_word();
+        let b = rand_word();
+        test_ok(OpcodeId::MUL, a, b, a.overflowing_mul(b).0);
+        test_ok(OpcodeId::DIV, a, b, a / b);
+        test_ok(OpcodeId::MOD, a, b, a % b);
+        // Small divisor so that the quotient spans multiple limbs
+        let b = Word::from(rand_word().low_u64());
+        test_ok(OpcodeId::DIV, a, b, a / b);
+        test_ok(OpcodeId::MOD, a, b, a % b);
+    }
+
+    #[test]
+    fn mul_div_mod_gadget_wrong_result() {
+        // The result is zero when the divisor is zero
+        test_err(
+            OpcodeId::DIV,
+            0x030201.into(),
+            Word::zero(),
+            0x030201.into(),
+        );
+        test_err(OpcodeId::DIV, 0x030201.into(), Word::zero(), Word::one());
+        test_err(
+            OpcodeId::MOD,
+            0x030201.into(),
+            Word::zero(),
+            0x030201.into(),
+        );
+        test_err(OpcodeId::MUL, 0x030201.into(), 0x0201.into(), Word::zero());
+        // The quotient is one less than expected, so the remainder equals the
+        // divisor
+        test_err(
+            OpcodeId::DIV,
+            0x06070401.into(),
+            0x0201.into(),
+            0x030200.into(),
+        );
+    }
+}
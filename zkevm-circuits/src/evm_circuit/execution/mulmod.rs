@@ -0,0 +1,298 @@
+use crate::{
+    evm_circuit::{
+        execution::{
+            bus_mapping_tmp::{Block, Call, ExecStep, Transaction},
+            ExecutionGadget,
+        },
+        step::ExecutionState,
+        util::{
+            common_gadget::SameContextGadget,
+            constraint_builder::{
+                ConstraintBuilder, StepStateTransition, Transition::Delta,
+            },
+            math_gadget::{IsZeroGadget, LtWordGadget, MulAddWords512Gadget},
+            split_u256, sum, word_halves_expr, word_limbs_expr, Word,
+        },
+    },
+    util::Expr,
+};
+use bus_mapping::eth_types::{self, ToLittleEndian};
+use halo2::{arithmetic::FieldExt, circuit::Region, plonk::Error};
+
+// MulModGadget verifies MULMOD by checking a * b == k * n + r with the 512-bit
+// multiplication, where the quotient k has up to 512 bits, and r < n. When n
+// is zero, a is replaced by zero in the multiplication, which forces r to be
+// zero as well.
+#[derive(Clone, Debug)]
+pub(crate) struct MulModGadget<F> {
+    same_context: SameContextGadget<F>,
+    words: [Word<F>; 4],
+    k: [Word<F>; 2],
+    n_is_zero: IsZeroGadget<F>,
+    mul_add_words_512: MulAddWords512Gadget<F>,
+    lt: LtWordGadget<F>,
+}
+
+impl<F: FieldExt> ExecutionGadget<F> for MulModGadget<F> {
+    const NAME: &'static str = "MULMOD";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::MULMOD;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+
+        let a = cb.query_word();
+        let b = cb.query_word();
+        let n = cb.query_word();
+        let r = cb.query_word();
+        let k = [cb.query_word(), cb.query_word()];
+        let n_is_zero = IsZeroGadget::construct(cb, sum::expr(&n.cells));
+
+        // a * b + 0 == k * n + r, with a being zero when n is zero
+        let a_limbs = word_limbs_expr(&a)
+            .map(|limb| (1.expr() - n_is_zero.expr()) * limb);
+        let mul_add_words_512 = MulAddWords512Gadget::construct(
+            cb,
+            [a_limbs, word_limbs_expr(&b), word_limbs_expr(&n)],
+            [word_limbs_expr(&k[0]), word_limbs_expr(&k[1])],
+            [[0.expr(), 0.expr()], word_halves_expr(&r)],
+        );
+        let lt = LtWordGadget::construct(cb, &r, &n);
+        cb.require_zero(
+            "r < n when n != 0",
+            (1.expr() - n_is_zero.expr()) * (1.expr() - lt.expr()),
+        );
+
+        // Pop a, b and n from the stack, push r on the stack
+        cb.stack_pop(a.expr());
+        cb.stack_pop(b.expr());
+        cb.stack_pop(n.expr());
+        cb.stack_push(r.expr());
+
+        // State transition
+        let step_state_transition = StepStateTransition {
+            rw_counter: Delta(4.expr()),
+            program_counter: Delta(1.expr()),
+            stack_pointer: Delta(2.expr()),
+            ..Default::default()
+        };
+        let same_context = SameContextGadget::construct(
+            cb,
+            opcode,
+            step_state_transition,
+            None,
+        );
+
+        Self {
+            same_context,
+            words: [a, b, n, r],
+            k,
+            n_is_zero,
+            mul_add_words_512,
+            lt,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction<F>,
+        _: &Call<F>,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        self.same_context.assign_exec_step(region, offset, step)?;
+
+        let [a, b, n, r] = [0, 1, 2, 3]
+            .map(|idx| block.rws[step.rw_indices[idx]].stack_value());
+        for (word, value) in self.words.iter().zip([a, b, n, r]) {
+            word.assign(region, offset, Some(value.to_le_bytes()))?;
+        }
+        self.n_is_zero
+            .assign(region, offset, sum::value(&n.to_le_bytes()))?;
+
+        // The multiplication is done with a replaced by zero when n is zero
+        let (a, k) = if n.is_zero() {
+            (eth_types::Word::zero(), [eth_types::Word::zero(); 2])
+        } else {
+            let k = a.full_mul(b).div_mod(n.into()).0;
+            (
+                a,
+                [
+                    eth_types::Word([k.0[0], k.0[1], k.0[2], k.0[3]]),
+                    eth_types::Word([k.0[4], k.0[5], k.0[6], k.0[7]]),
+                ],
+            )
+        };
+        for (word, value) in self.k.iter().zip(k) {
+            word.assign(region, offset, Some(value.to_le_bytes()))?;
+        }
+        let (r_lo, r_hi) = split_u256(&r);
+        self.mul_add_words_512.assign(
+            region,
+            offset,
+            [a, b, n],
+            k,
+            [[eth_types::Word::zero(); 2], [r_lo, r_hi]],
+        )?;
+        self.lt.assign(region, offset, r, n)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::evm_circuit::{
+        execution::bus_mapping_tmp::{
+            Block, Bytecode, Call, ExecStep, Rw, Transaction,
+        },
+        step::ExecutionState,
+        test::{rand_word, run_test_circuit_incomplete_fixed_table},
+        util::RandomLinearCombination,
+    };
+    use bus_mapping::{
+        eth_types::{ToBigEndian, ToLittleEndian, Word},
+        evm::OpcodeId,
+    };
+    use halo2::{arithmetic::BaseExt, dev::VerifyFailure};
+    use pairing::bn256::Fr as Fp;
+
+    fn run_test(
+        a: Word,
+        b: Word,
+        n: Word,
+        r: Word,
+    ) -> Result<(), Vec<VerifyFailure>> {
+        let randomness = Fp::rand();
+        let bytecode = Bytecode::new(
+            [
+                vec![OpcodeId::PUSH32.as_u8()],
+                n.to_be_bytes().to_vec(),
+                vec![OpcodeId::PUSH32.as_u8()],
+                b.to_be_bytes().to_vec(),
+                vec![OpcodeId::PUSH32.as_u8()],
+                a.to_be_bytes().to_vec(),
+                vec![OpcodeId::MULMOD.as_u8(), OpcodeId::STOP.as_u8()],
+            ]
+            .concat(),
+        );
+        let block = Block {
+            randomness,
+            txs: vec![Transaction {
+                calls: vec![Call {
+                    id: 1,
+                    is_root: false,
+                    is_create: false,
+                    opcode_source:
+                        RandomLinearCombination::random_linear_combine(
+                            bytecode.hash.to_le_bytes(),
+                            randomness,
+                        ),
+                }],
+                steps: vec![
+                    ExecStep {
+                        rw_indices: vec![0, 1, 2, 3],
+                        execution_state: ExecutionState::MULMOD,
+                        rw_counter: 1,
+                        program_counter: 99,
+                        stack_pointer: 1021,
+                        gas_left: 8,
+                        gas_cost: 8,
+                        opcode: Some(OpcodeId::MULMOD),
+                        ..Default::default()
+                    },
+                    ExecStep {
+                        execution_state: ExecutionState::STOP,
+                        rw_counter: 5,
+                        program_counter: 100,
+                        stack_pointer: 1023,
+                        gas_left: 0,
+                        opcode: Some(OpcodeId::STOP),
+                        ..Default::default()
+                    },
+                ],
+                ..Default::default()
+            }],
+            rws: vec![
+                Rw::Stack {
+                    rw_counter: 1,
+                    is_write: false,
+                    call_id: 1,
+                    stack_pointer: 1021,
+                    value: a,
+                },
+                Rw::Stack {
+                    rw_counter: 2,
+                    is_write: false,
+                    call_id: 1,
+                    stack_pointer: 1022,
+                    value: b,
+                },
+                Rw::Stack {
+                    rw_counter: 3,
+                    is_write: false,
+                    call_id: 1,
+                    stack_pointer: 1023,
+                    value: n,
+                },
+                Rw::Stack {
+                    rw_counter: 4,
+                    is_write: true,
+                    call_id: 1,
+                    stack_pointer: 1023,
+                    value: r,
+                },
+            ],
+            bytecodes: vec![bytecode],
+            ..Default::default()
+        };
+        run_test_circuit_incomplete_fixed_table(block)
+    }
+
+    fn test_ok(a: Word, b: Word, n: Word, r: Word) {
+        assert_eq!(run_test(a, b, n, r), Ok(()));
+    }
+
+    fn test_err(a: Word, b: Word, n: Word, r: Word) {
+        assert!(run_test(a, b, n, r).is_err());
+    }
+
+    #[test]
+    fn mulmod_gadget_simple() {
+        test_ok(7.into(), 18.into(), 10.into(), 6.into());
+        test_ok(7.into(), 18.into(), 1.into(), Word::zero());
+        test_ok(7.into(), 18.into(), Word::zero(), Word::zero());
+    }
+
+    #[test]
+    fn mulmod_gadget_overflow() {
+        // (2^256 - 1) ⋅ (2^256 - 1) ≡ 1 (mod 2^256 - 2)
+        test_ok(Word::MAX, Word::MAX, Word::MAX - 1, 1.into());
+        // (2^256 - 1) ⋅ 2 = 2^257 - 2 ≡ 2 (mod 7), since 2^257 ≡ 4 (mod 7)
+        test_ok(Word::MAX, 2.into(), 7.into(), 2.into());
+    }
+
+    #[test]
+    fn mulmod_gadget_rand() {
+        let a = rand_word();
+        let b = rand_word();
+        let n = rand_word();
+        let r = a.full_mul(b).div_mod(n.into()).1;
+        test_ok(a, b, n, Word([r.0[0], r.0[1], r.0[2], r.0[3]]));
+    }
+
+    #[test]
+    fn mulmod_gadget_wrong_result() {
+        // r is congruent to the product but not less than n
+        test_err(7.into(), 18.into(), 10.into(), 16.into());
+        // The result is zero when n is zero
+        test_err(7.into(), 18.into(), Word::zero(), 126.into());
+        // (2^256 - 1) ⋅ 2 wrapped at 2^256 is 2^256 - 2 ≡ 0 (mod 7), while the
+        // product itself is ≡ 2 (mod 7)
+        test_err(Word::MAX, 2.into(), 7.into(), Word::zero());
+        // The quotient (2^256 - 1)^2 fills all the 512 bits
+        test_err(Word::MAX, Word::MAX, 1.into(), 1.into());
+    }
+}
@@ -157,6 +157,7 @@ mod test {
                 value: Word::from(33),
             }],
             bytecodes: vec![bytecode],
+            ..Default::default()
         };
         assert_eq!(run_test_circuit_incomplete_fixed_table(block), Ok(()));
     }
@@ -231,6 +231,7 @@ mod test {
                 value: Word::from_big_endian(bytes),
             }],
             bytecodes: vec![bytecode],
+            ..Default::default()
         };
         assert_eq!(run_test_circuit_incomplete_fixed_table(block), Ok(()));
     }
@@ -0,0 +1,471 @@
+use crate::{
+    evm_circuit::{
+        execution::{
+            bus_mapping_tmp::{Block, Call, ExecStep, Transaction},
+            ExecutionGadget,
+        },
+        step::ExecutionState,
+        table::{FixedTableTag, Lookup},
+        util::{
+            common_gadget::SameContextGadget,
+            constraint_builder::{
+                ConstraintBuilder, StepStateTransition, Transition::Delta,
+            },
+            math_gadget::{IsEqualGadget, IsZeroGadget},
+            select, sum, Cell, Word,
+        },
+    },
+    util::Expr,
+};
+use bus_mapping::{eth_types::ToLittleEndian, evm::OpcodeId};
+use halo2::{
+    arithmetic::FieldExt,
+    circuit::Region,
+    plonk::{Error, Expression},
+};
+
+// ShiftGadget verifies SHL, SHR and SAR. The least significant byte of the
+// shift is decomposed into byte_shift * 8 + bit_shift, then each byte of the
+// value is shifted left by bit_shift (SHL) or 8 - bit_shift (SHR and SAR)
+// bits with a lookup to the ShiftLeftByte fixed table, which splits the
+// shifted byte into lo and hi bytes. The bit-shifted word is assembled from
+// lo and hi bytes of adjacent bytes, and is then moved by byte_shift bytes.
+// When any other byte of the shift is non-zero, the shift is at least 256 and
+// the result is zero. SAR of a negative value is verified as SHR of its
+// bitwise complement, with the result complemented again, so that the vacant
+// bits are filled with ones.
+// Note that bit_shift is allowed to be 8, which shifts the same as increasing
+// byte_shift by one, so the result is still uniquely determined.
+#[derive(Clone, Debug)]
+pub(crate) struct ShiftGadget<F> {
+    same_context: SameContextGadget<F>,
+    shift: Word<F>,
+    value: Word<F>,
+    result: Word<F>,
+    is_shl: IsEqualGadget<F>,
+    is_sar: IsEqualGadget<F>,
+    sign_byte: Cell<F>,
+    shift_hi_is_zero: IsZeroGadget<F>,
+    byte_shift_selectors: [Cell<F>; 32],
+    bit_shift: Cell<F>,
+    lo: [Cell<F>; 32],
+    hi: [Cell<F>; 32],
+}
+
+impl<F: FieldExt> ExecutionGadget<F> for ShiftGadget<F> {
+    const NAME: &'static str = "SHL";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::SHL;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+
+        let is_shl =
+            IsEqualGadget::construct(cb, opcode.expr(), OpcodeId::SHL.expr());
+        let is_sar =
+            IsEqualGadget::construct(cb, opcode.expr(), OpcodeId::SAR.expr());
+
+        let shift = cb.query_word();
+        let value = cb.query_word();
+        let result = cb.query_word();
+
+        // Lookup the sign byte of value, which is 0xff when value is negative
+        // and 0 otherwise. The bits are complemented only for SAR of a
+        // negative value.
+        let sign_byte = cb.query_cell();
+        cb.add_lookup(Lookup::Fixed {
+            tag: FixedTableTag::SignByte.expr(),
+            values: [value.cells[31].expr(), sign_byte.expr(), 0.expr()],
+        });
+        let is_complemented = is_sar.expr()
+            * sign_byte.expr()
+            * Expression::Constant(F::from(0xff).invert().unwrap());
+
+        // Decompose the least significant byte of shift
+        let shift_hi_is_zero =
+            IsZeroGadget::construct(cb, sum::expr(&shift.cells[1..]));
+        let byte_shift_selectors = [(); 32].map(|_| cb.query_bool());
+        let bit_shift = cb.query_cell();
+        cb.require_equal(
+            "Exactly one byte shift selector is enabled",
+            sum::expr(&byte_shift_selectors),
+            1.expr(),
+        );
+        cb.require_equal(
+            "shift[0] == byte_shift * 8 + bit_shift",
+            shift.cells[0].expr(),
+            sum::expr(byte_shift_selectors.iter().enumerate().map(
+                |(byte_shift, selector)| {
+                    (byte_shift * 8).expr() * selector.expr()
+                },
+            )) + bit_shift.expr(),
+        );
+
+        // Shift each byte of value left by bit_shift bits for SHL and by
+        // 8 - bit_shift bits for SHR and SAR, which is lo + hi * 256
+        let lo = cb.query_bytes();
+        let hi = cb.query_bytes();
+        let bit_shift_left = select::expr(
+            is_shl.expr(),
+            bit_shift.expr(),
+            8.expr() - bit_shift.expr(),
+        );
+        for idx in 0..32 {
+            cb.add_lookup(Lookup::Fixed {
+                tag: FixedTableTag::ShiftLeftByte.expr(),
+                values: [
+                    select::expr(
+                        is_complemented.clone(),
+                        255.expr() - value.cells[idx].expr(),
+                        value.cells[idx].expr(),
+                    ),
+                    bit_shift_left.clone(),
+                    lo[idx].expr() + hi[idx].expr() * 256.expr(),
+                ],
+            });
+        }
+
+        // Assemble the bit-shifted word, where for SHL hi is carried to the
+        // next byte, and for SHR and SAR lo is carried to the previous byte
+        let shl_bytes = (0..32)
+            .map(|idx| {
+                if idx == 0 {
+                    lo[idx].expr()
+                } else {
+                    lo[idx].expr() + hi[idx - 1].expr()
+                }
+            })
+            .collect::<Vec<_>>();
+        let shr_bytes = (0..32)
+            .map(|idx| {
+                if idx == 31 {
+                    hi[idx].expr()
+                } else {
+                    hi[idx].expr() + lo[idx + 1].expr()
+                }
+            })
+            .collect::<Vec<_>>();
+
+        // Move the bit-shifted word by byte_shift bytes
+        for idx in 0..32 {
+            let shl_byte = sum::expr(
+                byte_shift_selectors[..=idx]
+                    .iter()
+                    .zip(shl_bytes[..=idx].iter().rev())
+                    .map(|(selector, byte)| selector.expr() * byte.clone()),
+            );
+            let shr_byte = sum::expr(
+                byte_shift_selectors[..32 - idx]
+                    .iter()
+                    .zip(shr_bytes[idx..].iter())
+                    .map(|(selector, byte)| selector.expr() * byte.clone()),
+            );
+            let shifted_byte = shift_hi_is_zero.expr()
+                * select::expr(is_shl.expr(), shl_byte, shr_byte);
+            cb.require_equal(
+                "result[i] == shifted[i], complemented for negative SAR",
+                result.cells[idx].expr(),
+                select::expr(
+                    is_complemented.clone(),
+                    255.expr() - shifted_byte.clone(),
+                    shifted_byte,
+                ),
+            );
+        }
+
+        // Pop shift and value from the stack, push result on the stack
+        cb.stack_pop(shift.expr());
+        cb.stack_pop(value.expr());
+        cb.stack_push(result.expr());
+
+        // State transition
+        let step_state_transition = StepStateTransition {
+            rw_counter: Delta(3.expr()),
+            program_counter: Delta(1.expr()),
+            stack_pointer: Delta(1.expr()),
+            ..Default::default()
+        };
+        let same_context = SameContextGadget::construct(
+            cb,
+            opcode,
+            step_state_transition,
+            None,
+        );
+
+        Self {
+            same_context,
+            shift,
+            value,
+            result,
+            is_shl,
+            is_sar,
+            sign_byte,
+            shift_hi_is_zero,
+            byte_shift_selectors,
+            bit_shift,
+            lo,
+            hi,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction<F>,
+        _: &Call<F>,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        self.same_context.assign_exec_step(region, offset, step)?;
+
+        let opcode = step.opcode.unwrap();
+        self.is_shl.assign(
+            region,
+            offset,
+            F::from(opcode.as_u64()),
+            F::from(OpcodeId::SHL.as_u64()),
+        )?;
+        self.is_sar.assign(
+            region,
+            offset,
+            F::from(opcode.as_u64()),
+            F::from(OpcodeId::SAR.as_u64()),
+        )?;
+
+        let [shift, value, result] =
+            [0, 1, 2].map(|idx| block.rws[step.rw_indices[idx]].stack_value());
+        self.shift
+            .assign(region, offset, Some(shift.to_le_bytes()))?;
+        self.value
+            .assign(region, offset, Some(value.to_le_bytes()))?;
+        self.result
+            .assign(region, offset, Some(result.to_le_bytes()))?;
+
+        let is_neg = value.bit(255);
+        self.sign_byte.assign(
+            region,
+            offset,
+            Some(F::from(if is_neg { 0xff } else { 0 })),
+        )?;
+        let is_complemented = opcode == OpcodeId::SAR && is_neg;
+
+        let shift = shift.to_le_bytes();
+        self.shift_hi_is_zero.assign(
+            region,
+            offset,
+            sum::value(&shift[1..]),
+        )?;
+        let (byte_shift, bit_shift) =
+            (shift[0] as usize / 8, shift[0] as u64 % 8);
+        for (idx, selector) in self.byte_shift_selectors.iter().enumerate() {
+            selector.assign(
+                region,
+                offset,
+                Some(F::from((idx == byte_shift) as u64)),
+            )?;
+        }
+        self.bit_shift
+            .assign(region, offset, Some(F::from(bit_shift)))?;
+
+        let bit_shift_left = if opcode == OpcodeId::SHL {
+            bit_shift
+        } else {
+            8 - bit_shift
+        };
+        for (idx, byte) in value.to_le_bytes().iter().enumerate() {
+            let byte = if is_complemented { 0xff - byte } else { *byte };
+            let shifted = (byte as u64) << bit_shift_left;
+            self.lo[idx].assign(
+                region,
+                offset,
+                Some(F::from(shifted & 0xff)),
+            )?;
+            self.hi[idx].assign(region, offset, Some(F::from(shifted >> 8)))?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::evm_circuit::{
+        execution::bus_mapping_tmp::{
+            Block, Bytecode, Call, ExecStep, Rw, Transaction,
+        },
+        step::ExecutionState,
+        table::FixedTableTag,
+        test::{rand_range, rand_word, run_test_circuit},
+        util::RandomLinearCombination,
+    };
+    use bus_mapping::{
+        eth_types::{ToBigEndian, ToLittleEndian, Word},
+        evm::OpcodeId,
+    };
+    use halo2::{arithmetic::BaseExt, dev::VerifyFailure};
+    use pairing::bn256::Fr as Fp;
+
+    fn run_test(
+        opcode: OpcodeId,
+        shift: Word,
+        value: Word,
+        result: Word,
+    ) -> Result<(), Vec<VerifyFailure>> {
+        let randomness = Fp::rand();
+        let bytecode = Bytecode::new(
+            [
+                vec![OpcodeId::PUSH32.as_u8()],
+                value.to_be_bytes().to_vec(),
+                vec![OpcodeId::PUSH32.as_u8()],
+                shift.to_be_bytes().to_vec(),
+                vec![opcode.as_u8(), OpcodeId::STOP.as_u8()],
+            ]
+            .concat(),
+        );
+        let block = Block {
+            randomness,
+            txs: vec![Transaction {
+                calls: vec![Call {
+                    id: 1,
+                    is_root: false,
+                    is_create: false,
+                    opcode_source:
+                        RandomLinearCombination::random_linear_combine(
+                            bytecode.hash.to_le_bytes(),
+                            randomness,
+                        ),
+                }],
+                steps: vec![
+                    ExecStep {
+                        rw_indices: vec![0, 1, 2],
+                        execution_state: ExecutionState::SHL,
+                        rw_counter: 1,
+                        program_counter: 66,
+                        stack_pointer: 1022,
+                        gas_left: 3,
+                        gas_cost: 3,
+                        opcode: Some(opcode),
+                        ..Default::default()
+                    },
+                    ExecStep {
+                        execution_state: ExecutionState::STOP,
+                        rw_counter: 4,
+                        program_counter: 67,
+                        stack_pointer: 1023,
+                        gas_left: 0,
+                        opcode: Some(OpcodeId::STOP),
+                        ..Default::default()
+                    },
+                ],
+                ..Default::default()
+            }],
+            rws: vec![
+                Rw::Stack {
+                    rw_counter: 1,
+                    is_write: false,
+                    call_id: 1,
+                    stack_pointer: 1022,
+                    value: shift,
+                },
+                Rw::Stack {
+                    rw_counter: 2,
+                    is_write: false,
+                    call_id: 1,
+                    stack_pointer: 1023,
+                    value,
+                },
+                Rw::Stack {
+                    rw_counter: 3,
+                    is_write: true,
+                    call_id: 1,
+                    stack_pointer: 1023,
+                    value: result,
+                },
+            ],
+            bytecodes: vec![bytecode],
+            ..Default::default()
+        };
+        run_test_circuit(
+            block,
+            vec![
+                FixedTableTag::Range16,
+                FixedTableTag::Range32,
+                FixedTableTag::Range256,
+                FixedTableTag::Range512,
+                FixedTableTag::SignByte,
+                FixedTableTag::ShiftLeftByte,
+                FixedTableTag::ResponsibleOpcode,
+            ],
+        )
+    }
+
+    fn test_ok(opcode: OpcodeId, shift: Word, value: Word, result: Word) {
+        assert_eq!(run_test(opcode, shift, value, result), Ok(()));
+    }
+
+    fn test_err(opcode: OpcodeId, shift: Word, value: Word, result: Word) {
+        assert!(run_test(opcode, shift, value, result).is_err());
+    }
+
+    #[test]
+    fn shift_gadget_simple() {
+        test_ok(OpcodeId::SHL, 4.into(), 0x0f.into(), 0xf0.into());
+        test_ok(OpcodeId::SHL, 12.into(), 0x0102.into(), 0x102000.into());
+        test_ok(OpcodeId::SHL, 255.into(), 3.into(), Word::one() << 255);
+        test_ok(OpcodeId::SHR, 4.into(), 0xf0.into(), 0x0f.into());
+        test_ok(OpcodeId::SHR, 12.into(), 0x102000.into(), 0x0102.into());
+        test_ok(OpcodeId::SHR, 255.into(), Word::MAX, Word::one());
+        test_ok(OpcodeId::SAR, 4.into(), 0xf0.into(), 0x0f.into());
+        test_ok(OpcodeId::SAR, 4.into(), Word::MAX - 0x0f, Word::MAX);
+        test_ok(
+            OpcodeId::SAR,
+            8.into(),
+            Word::one() << 255,
+            Word::MAX << 247,
+        );
+    }
+
+    #[test]
+    fn shift_gadget_overflow() {
+        test_ok(OpcodeId::SHL, 256.into(), Word::MAX, Word::zero());
+        test_ok(OpcodeId::SHR, 256.into(), Word::MAX, Word::zero());
+        test_ok(OpcodeId::SHR, Word::MAX, Word::MAX, Word::zero());
+        test_ok(OpcodeId::SAR, 256.into(), Word::MAX >> 1, Word::zero());
+        test_ok(OpcodeId::SAR, 300.into(), Word::MAX - 1, Word::MAX);
+    }
+
+    #[test]
+    fn shift_gadget_rand() {
+        let shift = rand_range(0..256usize);
+        let value = rand_word();
+        test_ok(OpcodeId::SHL, shift.into(), value, value << shift);
+        test_ok(OpcodeId::SHR, shift.into(), value, value >> shift);
+        test_ok(
+            OpcodeId::SAR,
+            shift.into(),
+            value,
+            if value.bit(255) {
+                !(!value >> shift)
+            } else {
+                value >> shift
+            },
+        );
+        test_ok(OpcodeId::SHL, rand_word(), value, Word::zero());
+    }
+
+    #[test]
+    fn shift_gadget_wrong_result() {
+        test_err(OpcodeId::SHL, 4.into(), 0x0f.into(), 0x0f.into());
+        // Shifts of at least 256 bits result in zero, or in -1 for SAR of a
+        // negative value
+        test_err(OpcodeId::SHL, 256.into(), Word::MAX, Word::MAX);
+        test_err(OpcodeId::SHR, 256.into(), Word::MAX, Word::one());
+        test_err(OpcodeId::SAR, 300.into(), Word::MAX - 1, Word::zero());
+        test_err(OpcodeId::SAR, 256.into(), Word::MAX >> 1, Word::MAX);
+        // Only the low byte of the shift is below 256
+        let shift = (Word::one() << 128) + 1;
+        test_err(OpcodeId::SHL, shift, 0x0f.into(), 0x1e.into());
+        test_err(OpcodeId::SHR, shift, 0xf0.into(), 0x78.into());
+    }
+}
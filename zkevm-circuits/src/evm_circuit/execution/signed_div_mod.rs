@@ -0,0 +1,432 @@
+use crate::{
+    evm_circuit::{
+        execution::{
+            bus_mapping_tmp::{Block, Call, ExecStep, Transaction},
+            ExecutionGadget,
+        },
+        step::ExecutionState,
+        table::{FixedTableTag, Lookup},
+        util::{
+            common_gadget::SameContextGadget,
+            constraint_builder::{
+                ConstraintBuilder, StepStateTransition, Transition::Delta,
+            },
+            math_gadget::{
+                AbsWordGadget, IsZeroGadget, LtWordGadget, MulAddWordsGadget,
+                PairSelectGadget,
+            },
+            select, sum, Cell,
+        },
+    },
+    util::Expr,
+};
+use bus_mapping::{
+    eth_types::{ToLittleEndian, Word},
+    evm::OpcodeId,
+};
+use halo2::{
+    arithmetic::FieldExt,
+    circuit::Region,
+    plonk::{Error, Expression},
+};
+
+// SignedDivModGadget verifies SDIV and SMOD at the same time by checking
+// |quotient| * |divisor| + |remainder| == |dividend| on the absolute values,
+// with the signs of quotient and remainder constrained separately:
+// - the remainder has the sign of the dividend,
+// - the quotient is negative when the signs of dividend and divisor differ,
+//   except for -2^255 / -1 which overflows to -2^255.
+// When the divisor is zero, we set the quotient to zero and the remainder to
+// the dividend, and the pushed result is zero.
+#[derive(Clone, Debug)]
+pub(crate) struct SignedDivModGadget<F> {
+    same_context: SameContextGadget<F>,
+    is_sdiv: PairSelectGadget<F>,
+    quotient: AbsWordGadget<F>,
+    divisor: AbsWordGadget<F>,
+    remainder: AbsWordGadget<F>,
+    dividend: AbsWordGadget<F>,
+    mul_add_words: MulAddWordsGadget<F>,
+    remainder_abs_lt_divisor_abs: LtWordGadget<F>,
+    quotient_abs_sign_byte: Cell<F>,
+    divisor_is_zero: IsZeroGadget<F>,
+    quotient_is_zero: IsZeroGadget<F>,
+    remainder_is_zero: IsZeroGadget<F>,
+}
+
+impl<F: FieldExt> ExecutionGadget<F> for SignedDivModGadget<F> {
+    const NAME: &'static str = "SDIV";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::SDIV;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+
+        let is_sdiv = PairSelectGadget::construct(
+            cb,
+            opcode.expr(),
+            OpcodeId::SDIV.expr(),
+            OpcodeId::SMOD.expr(),
+        );
+
+        let quotient = AbsWordGadget::construct(cb);
+        let divisor = AbsWordGadget::construct(cb);
+        let remainder = AbsWordGadget::construct(cb);
+        let dividend = AbsWordGadget::construct(cb);
+        let mul_add_words = MulAddWordsGadget::construct(
+            cb,
+            [
+                quotient.x_abs().clone(),
+                divisor.x_abs().clone(),
+                remainder.x_abs().clone(),
+                dividend.x_abs().clone(),
+            ],
+        );
+        let remainder_abs_lt_divisor_abs =
+            LtWordGadget::construct(cb, remainder.x_abs(), divisor.x_abs());
+        let divisor_is_zero =
+            IsZeroGadget::construct(cb, sum::expr(&divisor.x().cells));
+        let quotient_is_zero =
+            IsZeroGadget::construct(cb, sum::expr(&quotient.x().cells));
+        let remainder_is_zero =
+            IsZeroGadget::construct(cb, sum::expr(&remainder.x().cells));
+
+        // |quotient| is at least 2^255 only when the quotient is -2^255, which
+        // happens when -2^255 is divided by -1.
+        let quotient_abs_sign_byte = cb.query_cell();
+        cb.add_lookup(Lookup::Fixed {
+            tag: FixedTableTag::SignByte.expr(),
+            values: [
+                quotient.x_abs().cells[31].expr(),
+                quotient_abs_sign_byte.expr(),
+                0.expr(),
+            ],
+        });
+        let quotient_is_signed_overflow = quotient_abs_sign_byte.expr()
+            * Expression::Constant(F::from(0xff).invert().unwrap());
+
+        cb.require_zero(
+            "|remainder| < |divisor| when divisor != 0",
+            (1.expr() - divisor_is_zero.expr())
+                * (1.expr() - remainder_abs_lt_divisor_abs.expr()),
+        );
+        cb.require_zero(
+            "|quotient| ⋅ |divisor| + |remainder| doesn't overflow",
+            mul_add_words.overflow(),
+        );
+        cb.require_zero(
+            "sign(remainder) == sign(dividend) when remainder != 0",
+            (1.expr() - remainder_is_zero.expr())
+                * (remainder.is_neg() - dividend.is_neg()),
+        );
+        // The quotient is negative when the signs of dividend and divisor
+        // differ, unless it's zero or -2^255.
+        let signs_differ = dividend.is_neg() + divisor.is_neg()
+            - 2.expr() * dividend.is_neg() * divisor.is_neg();
+        cb.require_zero(
+            "sign(quotient) == sign(dividend) ^ sign(divisor)",
+            (1.expr() - quotient_is_zero.expr())
+                * (1.expr() - quotient_is_signed_overflow)
+                * (quotient.is_neg() - signs_differ),
+        );
+
+        // Pop dividend and divisor from the stack, push quotient for SDIV or
+        // remainder for SMOD on the stack, or zero when divisor is zero.
+        cb.stack_pop(dividend.x().expr());
+        cb.stack_pop(divisor.x().expr());
+        cb.stack_push(
+            (1.expr() - divisor_is_zero.expr())
+                * select::expr(
+                    is_sdiv.expr().0,
+                    quotient.x().expr(),
+                    remainder.x().expr(),
+                ),
+        );
+
+        // State transition
+        let step_state_transition = StepStateTransition {
+            rw_counter: Delta(3.expr()),
+            program_counter: Delta(1.expr()),
+            stack_pointer: Delta(1.expr()),
+            ..Default::default()
+        };
+        let same_context = SameContextGadget::construct(
+            cb,
+            opcode,
+            step_state_transition,
+            None,
+        );
+
+        Self {
+            same_context,
+            is_sdiv,
+            quotient,
+            divisor,
+            remainder,
+            dividend,
+            mul_add_words,
+            remainder_abs_lt_divisor_abs,
+            quotient_abs_sign_byte,
+            divisor_is_zero,
+            quotient_is_zero,
+            remainder_is_zero,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction<F>,
+        _: &Call<F>,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        self.same_context.assign_exec_step(region, offset, step)?;
+
+        let opcode = step.opcode.unwrap();
+        self.is_sdiv.assign(
+            region,
+            offset,
+            F::from(opcode.as_u64()),
+            F::from(OpcodeId::SDIV.as_u64()),
+            F::from(OpcodeId::SMOD.as_u64()),
+        )?;
+
+        let [dividend, divisor] = [step.rw_indices[0], step.rw_indices[1]]
+            .map(|idx| block.rws[idx].stack_value());
+        let neg = |x: Word| Word::zero().overflowing_sub(x).0;
+        let (quotient, remainder) = if divisor.is_zero() {
+            (Word::zero(), dividend)
+        } else {
+            let dividend_abs = if dividend.bit(255) {
+                neg(dividend)
+            } else {
+                dividend
+            };
+            let divisor_abs = if divisor.bit(255) {
+                neg(divisor)
+            } else {
+                divisor
+            };
+            let quotient_abs = dividend_abs / divisor_abs;
+            let remainder_abs = dividend_abs % divisor_abs;
+            (
+                if dividend.bit(255) != divisor.bit(255) {
+                    neg(quotient_abs)
+                } else {
+                    quotient_abs
+                },
+                if dividend.bit(255) {
+                    neg(remainder_abs)
+                } else {
+                    remainder_abs
+                },
+            )
+        };
+
+        let quotient_abs = self.quotient.assign(region, offset, quotient)?;
+        let divisor_abs = self.divisor.assign(region, offset, divisor)?;
+        let remainder_abs = self.remainder.assign(region, offset, remainder)?;
+        let dividend_abs = self.dividend.assign(region, offset, dividend)?;
+        self.mul_add_words.assign(
+            region,
+            offset,
+            [quotient_abs, divisor_abs, remainder_abs, dividend_abs],
+        )?;
+        self.remainder_abs_lt_divisor_abs.assign(
+            region,
+            offset,
+            remainder_abs,
+            divisor_abs,
+        )?;
+        self.quotient_abs_sign_byte.assign(
+            region,
+            offset,
+            Some(F::from(if quotient_abs.bit(255) { 0xff } else { 0 })),
+        )?;
+        for (is_zero, value) in [
+            (&self.divisor_is_zero, divisor),
+            (&self.quotient_is_zero, quotient),
+            (&self.remainder_is_zero, remainder),
+        ] {
+            is_zero.assign(region, offset, sum::value(&value.to_le_bytes()))?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::evm_circuit::{
+        execution::bus_mapping_tmp::{
+            Block, Bytecode, Call, ExecStep, Rw, Transaction,
+        },
+        step::ExecutionState,
+        test::{rand_word, run_test_circuit_incomplete_fixed_table},
+        util::RandomLinearCombination,
+    };
+    use bus_mapping::{
+        eth_types::{ToBigEndian, ToLittleEndian, Word},
+        evm::OpcodeId,
+    };
+    use halo2::{arithmetic::BaseExt, dev::VerifyFailure};
+    use pairing::bn256::Fr as Fp;
+
+    fn neg(x: Word) -> Word {
+        Word::zero().overflowing_sub(x).0
+    }
+
+    fn run_test(
+        opcode: OpcodeId,
+        a: Word,
+        b: Word,
+        c: Word,
+    ) -> Result<(), Vec<VerifyFailure>> {
+        let randomness = Fp::rand();
+        let bytecode = Bytecode::new(
+            [
+                vec![OpcodeId::PUSH32.as_u8()],
+                b.to_be_bytes().to_vec(),
+                vec![OpcodeId::PUSH32.as_u8()],
+                a.to_be_bytes().to_vec(),
+                vec![opcode.as_u8(), OpcodeId::STOP.as_u8()],
+            ]
+            .concat(),
+        );
+        let block = Block {
+            randomness,
+            txs: vec![Transaction {
+                calls: vec![Call {
+                    id: 1,
+                    is_root: false,
+                    is_create: false,
+                    opcode_source:
+                        RandomLinearCombination::random_linear_combine(
+                            bytecode.hash.to_le_bytes(),
+                            randomness,
+                        ),
+                }],
+                steps: vec![
+                    ExecStep {
+                        rw_indices: vec![0, 1, 2],
+                        execution_state: ExecutionState::SDIV,
+                        rw_counter: 1,
+                        program_counter: 66,
+                        stack_pointer: 1022,
+                        gas_left: 5,
+                        gas_cost: 5,
+                        opcode: Some(opcode),
+                        ..Default::default()
+                    },
+                    ExecStep {
+                        execution_state: ExecutionState::STOP,
+                        rw_counter: 4,
+                        program_counter: 67,
+                        stack_pointer: 1023,
+                        gas_left: 0,
+                        opcode: Some(OpcodeId::STOP),
+                        ..Default::default()
+                    },
+                ],
+                ..Default::default()
+            }],
+            rws: vec![
+                Rw::Stack {
+                    rw_counter: 1,
+                    is_write: false,
+                    call_id: 1,
+                    stack_pointer: 1022,
+                    value: a,
+                },
+                Rw::Stack {
+                    rw_counter: 2,
+                    is_write: false,
+                    call_id: 1,
+                    stack_pointer: 1023,
+                    value: b,
+                },
+                Rw::Stack {
+                    rw_counter: 3,
+                    is_write: true,
+                    call_id: 1,
+                    stack_pointer: 1023,
+                    value: c,
+                },
+            ],
+            bytecodes: vec![bytecode],
+            ..Default::default()
+        };
+        run_test_circuit_incomplete_fixed_table(block)
+    }
+
+    fn test_ok(opcode: OpcodeId, a: Word, b: Word, c: Word) {
+        assert_eq!(run_test(opcode, a, b, c), Ok(()));
+    }
+
+    fn test_err(opcode: OpcodeId, a: Word, b: Word, c: Word) {
+        assert!(run_test(opcode, a, b, c).is_err());
+    }
+
+    #[test]
+    fn signed_div_mod_gadget_simple() {
+        // 7 / 2 = 3, 7 % 2 = 1
+        test_ok(OpcodeId::SDIV, 7.into(), 2.into(), 3.into());
+        test_ok(OpcodeId::SMOD, 7.into(), 2.into(), 1.into());
+        // -7 / 2 = -3, -7 % 2 = -1
+        test_ok(OpcodeId::SDIV, neg(7.into()), 2.into(), neg(3.into()));
+        test_ok(OpcodeId::SMOD, neg(7.into()), 2.into(), neg(1.into()));
+        // 7 / -2 = -3, 7 % -2 = 1
+        test_ok(OpcodeId::SDIV, 7.into(), neg(2.into()), neg(3.into()));
+        test_ok(OpcodeId::SMOD, 7.into(), neg(2.into()), 1.into());
+        // -7 / -2 = 3, -7 % -2 = -1
+        test_ok(OpcodeId::SDIV, neg(7.into()), neg(2.into()), 3.into());
+        test_ok(OpcodeId::SMOD, neg(7.into()), neg(2.into()), neg(1.into()));
+    }
+
+    #[test]
+    fn signed_div_mod_gadget_edge_cases() {
+        let int_min = Word::one() << 255;
+        let minus_one = neg(Word::one());
+        // -2^255 / -1 overflows to -2^255, and -2^255 % -1 = 0
+        test_ok(OpcodeId::SDIV, int_min, minus_one, int_min);
+        test_ok(OpcodeId::SMOD, int_min, minus_one, Word::zero());
+        // -2^255 / 1 = -2^255
+        test_ok(OpcodeId::SDIV, int_min, Word::one(), int_min);
+        // Division by zero results in zero
+        test_ok(OpcodeId::SDIV, neg(7.into()), Word::zero(), Word::zero());
+        test_ok(OpcodeId::SMOD, neg(7.into()), Word::zero(), Word::zero());
+    }
+
+    #[test]
+    fn signed_div_mod_gadget_rand() {
+        let a = rand_word();
+        let b = rand_word();
+        let [a_abs, b_abs] =
+            [a, b].map(|x| if x.bit(255) { neg(x) } else { x });
+        let quotient = if a.bit(255) != b.bit(255) {
+            neg(a_abs / b_abs)
+        } else {
+            a_abs / b_abs
+        };
+        let remainder = if a.bit(255) {
+            neg(a_abs % b_abs)
+        } else {
+            a_abs % b_abs
+        };
+        test_ok(OpcodeId::SDIV, a, b, quotient);
+        test_ok(OpcodeId::SMOD, a, b, remainder);
+    }
+
+    #[test]
+    fn signed_div_mod_gadget_wrong_result() {
+        // The remainder takes the sign of the dividend, and the quotient is
+        // negative when the signs differ
+        test_err(OpcodeId::SMOD, neg(7.into()), 3.into(), Word::one());
+        test_err(OpcodeId::SMOD, 7.into(), neg(3.into()), neg(Word::one()));
+        test_err(OpcodeId::SDIV, neg(7.into()), 3.into(), 2.into());
+        // The result is zero when the divisor is zero
+        test_err(OpcodeId::SMOD, neg(7.into()), Word::zero(), neg(7.into()));
+    }
+}
@@ -0,0 +1,440 @@
+use crate::{
+    evm_circuit::{
+        execution::bus_mapping_tmp::Exponentiation,
+        table::LookupTable,
+        util::{
+            from_bytes, pow_of_two_expr, split_u256, split_u256_limb64, sum,
+            RandomLinearCombination,
+        },
+    },
+    util::Expr,
+};
+use bus_mapping::eth_types::{ToLittleEndian, Word};
+use halo2::{
+    arithmetic::FieldExt,
+    circuit::{Layouter, Region},
+    plonk::{
+        Advice, Column, ConstraintSystem, Error, Expression, Fixed, Instance,
+        Selector, VirtualCells,
+    },
+    poly::Rotation,
+};
+
+/// ExpCircuit constrains the exponentiation table looked up by EXP, whose rows
+/// are the steps of the square-and-multiply exponentiations of a block.  Each
+/// step takes the next bit of the exponent from the most significant one,
+/// squares the result of the previous step and multiplies it by the base when
+/// the bit is set, so the result of every step is the base to the power of
+/// the bits taken so far, modulo 2^256.  The first step of an exponentiation
+/// starts from the exponent 0 and the result 1, and the following ones keep
+/// the base, so any step flagged as the last one can be looked up.
+///
+/// The words are decomposed in bytes, and the multiplications modulo 2^256 are
+/// done on their 64-bit limbs with the carries of the low and high 128-bit
+/// halves, like in `MulAddWordsGadget`.
+#[derive(Clone, Debug)]
+pub struct ExpCircuit {
+    q_enable: Selector,
+    q_first: Selector,
+    is_first: Column<Advice>,
+    is_last: Column<Advice>,
+    bit: Column<Advice>,
+    // Bit 127 of the previous exponent, which is carried to the high half
+    // when it's doubled
+    exponent_carry: Column<Advice>,
+    base: [Column<Advice>; 32],
+    exponent: [Column<Advice>; 32],
+    square: [Column<Advice>; 32],
+    exponentiation: [Column<Advice>; 32],
+    square_carries: [[Column<Advice>; 9]; 2],
+    mul_carries: [[Column<Advice>; 9]; 2],
+    // Random linear combinations of the base, the exponent and the
+    // exponentiation, which are the looked up values
+    rlcs: [Column<Advice>; 3],
+    u8_table: Column<Fixed>,
+}
+
+impl ExpCircuit {
+    /// Configure ExpCircuit
+    pub fn configure<F: FieldExt>(
+        meta: &mut ConstraintSystem<F>,
+        randomness: Column<Instance>,
+    ) -> Self {
+        let q_enable = meta.complex_selector();
+        let q_first = meta.complex_selector();
+        let [is_first, is_last, bit, exponent_carry] =
+            [(); 4].map(|_| meta.advice_column());
+        let [base, exponent, square, exponentiation] =
+            [(); 4].map(|_| [(); 32].map(|_| meta.advice_column()));
+        let [square_carries, mul_carries] = [(); 2]
+            .map(|_| [(); 2].map(|_| [(); 9].map(|_| meta.advice_column())));
+        let rlcs = [(); 3].map(|_| meta.advice_column());
+        let u8_table = meta.fixed_column();
+
+        meta.create_gate("Exponentiation step", |meta| {
+            let q_enable = meta.query_selector(q_enable);
+            let q_first = meta.query_selector(q_first);
+            let randomness = meta.query_instance(randomness, Rotation::cur());
+
+            let [is_first, is_last, bit, exponent_carry] =
+                [is_first, is_last, bit, exponent_carry]
+                    .map(|column| meta.query_advice(column, Rotation::cur()));
+            let is_last_prev = meta.query_advice(is_last, Rotation::prev());
+            let not_first = 1.expr() - is_first.clone();
+
+            let base_prev = query_bytes(meta, base, Rotation::prev());
+            let exponent_prev = query_bytes(meta, exponent, Rotation::prev());
+            let exponentiation_prev =
+                query_bytes(meta, exponentiation, Rotation::prev());
+            let [base, exponent, square, exponentiation] =
+                [base, exponent, square, exponentiation]
+                    .map(|columns| query_bytes(meta, columns, Rotation::cur()));
+            let [square_carries, mul_carries] = [square_carries, mul_carries]
+                .map(|carries| {
+                    carries.map(|carry| {
+                        from_bytes::expr(&query_bytes(
+                            meta,
+                            carry,
+                            Rotation::cur(),
+                        ))
+                    })
+                });
+            let [base_rlc, exponent_rlc, exponentiation_rlc] =
+                rlcs.map(|column| meta.query_advice(column, Rotation::cur()));
+
+            let halves = |bytes: &[Expression<F>; 32]| {
+                [&bytes[..16], &bytes[16..]].map(from_bytes::expr)
+            };
+            let limbs = |bytes: &[Expression<F>; 32]| {
+                [0, 1, 2, 3].map(|idx| from_bytes::expr(&bytes[idx * 8..][..8]))
+            };
+
+            let mut constraints = vec![
+                is_first.clone() * (1.expr() - is_first.clone()),
+                is_last.clone() * (1.expr() - is_last),
+                bit.clone() * (1.expr() - bit.clone()),
+                exponent_carry.clone() * (1.expr() - exponent_carry.clone()),
+                // An exponentiation starts at the first row, and after the
+                // last step of the previous one
+                q_first.clone() * (1.expr() - is_first.clone()),
+                (1.expr() - q_first) * (is_first.clone() - is_last_prev),
+            ];
+
+            // The base is kept from the previous step
+            let [base_lo, base_hi] = halves(&base);
+            let [base_prev_lo, base_prev_hi] = halves(&base_prev);
+            constraints.push(not_first.clone() * (base_lo - base_prev_lo));
+            constraints.push(not_first.clone() * (base_hi - base_prev_hi));
+
+            // The exponent is the previous one doubled plus the bit
+            let [exponent_lo, exponent_hi] = halves(&exponent);
+            let [exponent_prev_lo, exponent_prev_hi] = halves(&exponent_prev);
+            constraints.push(
+                exponent_lo + exponent_carry.clone() * pow_of_two_expr(128)
+                    - not_first.clone() * exponent_prev_lo * 2.expr()
+                    - bit.clone(),
+            );
+            constraints.push(
+                exponent_hi
+                    - not_first.clone() * exponent_prev_hi * 2.expr()
+                    - exponent_carry,
+            );
+
+            // The square is the one of the previous result, which is 1 at the
+            // first step
+            let exponentiation_prev = limbs(&exponentiation_prev);
+            let exponentiation_prev = [0, 1, 2, 3].map(|idx| {
+                let limb = not_first.clone() * exponentiation_prev[idx].clone();
+                if idx == 0 {
+                    limb + is_first.clone()
+                } else {
+                    limb
+                }
+            });
+            constraints.extend(mul_constraints(
+                &exponentiation_prev,
+                &exponentiation_prev,
+                &square,
+                square_carries,
+            ));
+
+            // The result is the square multiplied by the base when the bit is
+            // set, and by 1 otherwise
+            let base_limbs = limbs(&base);
+            let multiplier = [0, 1, 2, 3].map(|idx| {
+                let limb = bit.clone() * base_limbs[idx].clone();
+                if idx == 0 {
+                    limb + 1.expr() - bit.clone()
+                } else {
+                    limb
+                }
+            });
+            constraints.extend(mul_constraints(
+                &limbs(&square),
+                &multiplier,
+                &exponentiation,
+                mul_carries,
+            ));
+
+            // The looked up values are the random linear combinations of the
+            // words
+            for (rlc, bytes) in [
+                (base_rlc, base),
+                (exponent_rlc, exponent),
+                (exponentiation_rlc, exponentiation),
+            ] {
+                constraints.push(
+                    rlc - RandomLinearCombination::random_linear_combine_expr(
+                        bytes,
+                        randomness.clone(),
+                    ),
+                );
+            }
+
+            constraints
+                .into_iter()
+                .map(move |constraint| q_enable.clone() * constraint)
+        });
+
+        // Range check of all the bytes
+        for column in [base, exponent, square, exponentiation]
+            .iter()
+            .flatten()
+            .chain(square_carries.iter().flatten())
+            .chain(mul_carries.iter().flatten())
+        {
+            meta.lookup_any(|meta| {
+                let q_enable = meta.query_selector(q_enable);
+                vec![(
+                    q_enable * meta.query_advice(*column, Rotation::cur()),
+                    meta.query_fixed(u8_table, Rotation::cur()),
+                )]
+            });
+        }
+
+        Self {
+            q_enable,
+            q_first,
+            is_first,
+            is_last,
+            bit,
+            exponent_carry,
+            base,
+            exponent,
+            square,
+            exponentiation,
+            square_carries,
+            mul_carries,
+            rlcs,
+            u8_table,
+        }
+    }
+
+    /// Load the table of the byte values used for the range checks
+    pub fn load_u8_table<F: FieldExt>(
+        &self,
+        layouter: &mut impl Layouter<F>,
+    ) -> Result<(), Error> {
+        layouter.assign_region(
+            || "u8 table",
+            |mut region| {
+                for value in 0..256 {
+                    region.assign_fixed(
+                        || format!("u8 table row {}", value),
+                        self.u8_table,
+                        value,
+                        || Ok(F::from(value as u64)),
+                    )?;
+                }
+                Ok(())
+            },
+        )
+    }
+
+    /// Assign the steps of the exponentiations of a block, after an all-zero
+    /// row which is looked up by the execution steps other than EXP.
+    pub fn assign<F: FieldExt>(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        exponentiations: &[Exponentiation],
+        randomness: F,
+    ) -> Result<(), Error> {
+        layouter.assign_region(
+            || "exp table",
+            |mut region| {
+                self.assign_step(
+                    &mut region,
+                    0,
+                    [false; 3],
+                    [Word::zero(); 3],
+                    randomness,
+                )?;
+
+                let mut offset = 1;
+                self.q_first.enable(&mut region, offset)?;
+                for exponentiation in exponentiations.iter() {
+                    let bits = exponentiation.bits();
+                    let mut exponent = Word::zero();
+                    let mut result = Word::one();
+                    for (idx, bit) in bits.iter().enumerate() {
+                        self.q_enable.enable(&mut region, offset)?;
+                        let (exponent_next, result_next) = self.assign_step(
+                            &mut region,
+                            offset,
+                            [idx == 0, idx + 1 == bits.len(), *bit],
+                            [exponentiation.base, exponent, result],
+                            randomness,
+                        )?;
+                        exponent = exponent_next;
+                        result = result_next;
+                        offset += 1;
+                    }
+                }
+                Ok(())
+            },
+        )
+    }
+
+    /// Assigns a step from the exponent and the result of the previous one,
+    /// and returns the ones of this step.
+    fn assign_step<F: FieldExt>(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        [is_first, is_last, bit]: [bool; 3],
+        [base, exponent_prev, exponentiation_prev]: [Word; 3],
+        randomness: F,
+    ) -> Result<(Word, Word), Error> {
+        let exponent = (exponent_prev << 1) + Word::from(bit as u64);
+        let exponent_carry = exponent_prev.bit(127);
+        let (square, square_carries) =
+            mul_with_carries(exponentiation_prev, exponentiation_prev);
+        let multiplier = if bit { base } else { Word::one() };
+        let (exponentiation, mul_carries) =
+            mul_with_carries(square, multiplier);
+
+        for (column, value) in [
+            (self.is_first, is_first),
+            (self.is_last, is_last),
+            (self.bit, bit),
+            (self.exponent_carry, exponent_carry),
+        ] {
+            region.assign_advice(
+                || format!("exp table flag row {}", offset),
+                column,
+                offset,
+                || Ok(F::from(value as u64)),
+            )?;
+        }
+        for (columns, word) in [
+            (self.base, base),
+            (self.exponent, exponent),
+            (self.square, square),
+            (self.exponentiation, exponentiation),
+        ] {
+            for (column, byte) in columns.iter().zip(word.to_le_bytes()) {
+                region.assign_advice(
+                    || format!("exp table byte row {}", offset),
+                    *column,
+                    offset,
+                    || Ok(F::from(byte as u64)),
+                )?;
+            }
+        }
+        for (columns, carry) in self
+            .square_carries
+            .iter()
+            .zip(square_carries)
+            .chain(self.mul_carries.iter().zip(mul_carries))
+        {
+            for (column, byte) in columns.iter().zip(carry.to_le_bytes()) {
+                region.assign_advice(
+                    || format!("exp table carry row {}", offset),
+                    *column,
+                    offset,
+                    || Ok(F::from(byte as u64)),
+                )?;
+            }
+        }
+        for (column, word) in
+            self.rlcs.iter().zip([base, exponent, exponentiation])
+        {
+            region.assign_advice(
+                || format!("exp table rlc row {}", offset),
+                *column,
+                offset,
+                || {
+                    Ok(RandomLinearCombination::random_linear_combine(
+                        word.to_le_bytes(),
+                        randomness,
+                    ))
+                },
+            )?;
+        }
+
+        Ok((exponent, exponentiation))
+    }
+}
+
+impl<F: FieldExt> LookupTable<F, 4> for ExpCircuit {
+    fn table_exprs(&self, meta: &mut VirtualCells<F>) -> [Expression<F>; 4] {
+        let q_enable = meta.query_selector(self.q_enable);
+        let is_last = meta.query_advice(self.is_last, Rotation::cur());
+        let [base, exponent, exponentiation] = self
+            .rlcs
+            .map(|column| meta.query_advice(column, Rotation::cur()));
+        [q_enable * is_last, base, exponent, exponentiation]
+    }
+}
+
+fn query_bytes<F: FieldExt, const N: usize>(
+    meta: &mut VirtualCells<F>,
+    columns: [Column<Advice>; N],
+    rotation: Rotation,
+) -> [Expression<F>; N] {
+    columns.map(|column| meta.query_advice(column, rotation))
+}
+
+/// Returns the constraints of `a * b == product (mod 2^256)` from the 64-bit
+/// limbs of `a` and `b`, the bytes of `product` and the carries of its low and
+/// high 128-bit halves.
+fn mul_constraints<F: FieldExt>(
+    a: &[Expression<F>; 4],
+    b: &[Expression<F>; 4],
+    product: &[Expression<F>; 32],
+    [carry_lo, carry_hi]: [Expression<F>; 2],
+) -> [Expression<F>; 2] {
+    let t = |k: usize| {
+        sum::expr((0..=k).map(|idx| a[idx].clone() * b[k - idx].clone()))
+    };
+    let product_lo = from_bytes::expr(&product[..16]);
+    let product_hi = from_bytes::expr(&product[16..]);
+
+    [
+        t(0) + t(1) * pow_of_two_expr(64)
+            - product_lo
+            - carry_lo.clone() * pow_of_two_expr(128),
+        t(2) + t(3) * pow_of_two_expr(64) + carry_lo
+            - product_hi
+            - carry_hi * pow_of_two_expr(128),
+    ]
+}
+
+/// Returns `a * b` modulo 2^256 and the carries of its low and high 128-bit
+/// halves.
+fn mul_with_carries(a: Word, b: Word) -> (Word, [Word; 2]) {
+    let a_limbs = split_u256_limb64(&a);
+    let b_limbs = split_u256_limb64(&b);
+    let t = |k: usize| {
+        (0..=k).fold(Word::zero(), |acc, idx| {
+            acc + a_limbs[idx] * b_limbs[k - idx]
+        })
+    };
+
+    let product = a.overflowing_mul(b).0;
+    let (product_lo, product_hi) = split_u256(&product);
+    let carry_lo = (t(0) + (t(1) << 64) - product_lo) >> 128;
+    let carry_hi = (t(2) + (t(3) << 64) + carry_lo - product_hi) >> 128;
+
+    (product, [carry_lo, carry_hi])
+}
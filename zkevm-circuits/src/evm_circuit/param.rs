@@ -1,6 +1,11 @@
 // Step dimension
 pub const STEP_WIDTH: usize = 32;
-pub const STEP_HEIGHT: usize = 10;
+// Each execution gadget fits in a single step.  The first 4 rows hold the step
+// state (the execution state selectors and NUM_CELLS_STEP_STATE), and a row
+// holds either byte cells or other cells, so a word takes a full row.  The
+// largest gadget is SDIV, with 8 words and 50 other bytes in 10 rows, plus 1
+// row of other cells.
+pub const STEP_HEIGHT: usize = 15;
 pub const NUM_CELLS_STEP_STATE: usize = 10;
 
 /// The maximum number of bytes that a field element
@@ -19,12 +19,9 @@ pub enum ExecutionState {
     BeginTx,
     // Opcode successful cases
     STOP,
-    ADD, // ADD, SUB
-    MUL,
-    DIV,
-    SDIV,
-    MOD,
-    SMOD,
+    ADD,  // ADD, SUB
+    MUL,  // MUL, DIV, MOD
+    SDIV, // SDIV, SMOD
     ADDMOD,
     MULMOD,
     EXP,
@@ -35,9 +32,7 @@ pub enum ExecutionState {
     BITWISE, // AND, OR, XOR
     NOT,
     BYTE,
-    SHL,
-    SHR,
-    SAR,
+    SHL, // SHL, SHR, SAR
     SHA3,
     ADDRESS,
     BALANCE,
@@ -133,10 +128,7 @@ impl ExecutionState {
             Self::STOP,
             Self::ADD,
             Self::MUL,
-            Self::DIV,
             Self::SDIV,
-            Self::MOD,
-            Self::SMOD,
             Self::ADDMOD,
             Self::MULMOD,
             Self::EXP,
@@ -148,8 +140,6 @@ impl ExecutionState {
             Self::NOT,
             Self::BYTE,
             Self::SHL,
-            Self::SHR,
-            Self::SAR,
             Self::SHA3,
             Self::ADDRESS,
             Self::BALANCE,
@@ -238,11 +228,8 @@ impl ExecutionState {
         match self {
             Self::STOP => vec![OpcodeId::STOP],
             Self::ADD => vec![OpcodeId::ADD, OpcodeId::SUB],
-            Self::MUL => vec![OpcodeId::MUL],
-            Self::DIV => vec![OpcodeId::DIV],
-            Self::SDIV => vec![OpcodeId::SDIV],
-            Self::MOD => vec![OpcodeId::MOD],
-            Self::SMOD => vec![OpcodeId::SMOD],
+            Self::MUL => vec![OpcodeId::MUL, OpcodeId::DIV, OpcodeId::MOD],
+            Self::SDIV => vec![OpcodeId::SDIV, OpcodeId::SMOD],
             Self::ADDMOD => vec![OpcodeId::ADDMOD],
             Self::MULMOD => vec![OpcodeId::MULMOD],
             Self::EXP => vec![OpcodeId::EXP],
@@ -253,9 +240,7 @@ impl ExecutionState {
             Self::BITWISE => vec![OpcodeId::AND, OpcodeId::OR, OpcodeId::XOR],
             Self::NOT => vec![OpcodeId::NOT],
             Self::BYTE => vec![OpcodeId::BYTE],
-            Self::SHL => vec![OpcodeId::SHL],
-            Self::SHR => vec![OpcodeId::SHR],
-            Self::SAR => vec![OpcodeId::SAR],
+            Self::SHL => vec![OpcodeId::SHL, OpcodeId::SHR, OpcodeId::SAR],
             Self::SHA3 => vec![OpcodeId::SHA3],
             Self::ADDRESS => vec![OpcodeId::ADDRESS],
             Self::BALANCE => vec![OpcodeId::BALANCE],
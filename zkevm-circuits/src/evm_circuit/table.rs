@@ -31,6 +31,7 @@ pub enum FixedTableTag {
     BitwiseAnd,
     BitwiseOr,
     BitwiseXor,
+    ShiftLeftByte,
     ResponsibleOpcode,
 }
 
@@ -45,6 +46,7 @@ impl FixedTableTag {
             Self::BitwiseAnd,
             Self::BitwiseOr,
             Self::BitwiseXor,
+            Self::ShiftLeftByte,
             Self::ResponsibleOpcode,
         ]
         .iter()
@@ -97,6 +99,16 @@ impl FixedTableTag {
                     [tag, F::from(lhs), F::from(rhs), F::from(lhs ^ rhs)]
                 })
             })),
+            Self::ShiftLeftByte => Box::new((0..9).flat_map(move |shift| {
+                (0..256).map(move |value| {
+                    [
+                        tag,
+                        F::from(value),
+                        F::from(shift),
+                        F::from(value << shift),
+                    ]
+                })
+            })),
             Self::ResponsibleOpcode => Box::new(
                 ExecutionState::iterator().flat_map(move |execution_state| {
                     execution_state.responsible_opcodes().into_iter().map(
@@ -188,6 +200,7 @@ pub(crate) enum Table {
     Tx,
     Rw,
     Bytecode,
+    Exp,
 }
 
 #[derive(Clone, Debug)]
@@ -239,6 +252,20 @@ pub(crate) enum Lookup<F> {
         /// data portion of PUSH* operations.
         is_code: Expression<F>,
     },
+    /// Lookup to exponentiation table, which contains the steps of the
+    /// square-and-multiply exponentiation of all EXP in this block.
+    Exp {
+        /// A boolean value to specify if the step is the last one of an
+        /// exponentiation, whose exponent is the complete one.
+        is_last: Expression<F>,
+        /// Base of the exponentiation.
+        base: Expression<F>,
+        /// Exponent of the step, which is a prefix of the bits of the
+        /// complete exponent.
+        exponent: Expression<F>,
+        /// Result of the step, which is base^exponent modulo 2^256.
+        exponentiation: Expression<F>,
+    },
     /// Conditional lookup enabled by the first element.
     Conditional(Expression<F>, Box<Lookup<F>>),
 }
@@ -254,6 +281,7 @@ impl<F: FieldExt> Lookup<F> {
             Self::Tx { .. } => Table::Tx,
             Self::Rw { .. } => Table::Rw,
             Self::Bytecode { .. } => Table::Bytecode,
+            Self::Exp { .. } => Table::Exp,
             Self::Conditional(_, lookup) => lookup.table(),
         }
     }
@@ -297,6 +325,17 @@ impl<F: FieldExt> Lookup<F> {
                     is_code.clone(),
                 ]
             }
+            Self::Exp {
+                is_last,
+                base,
+                exponent,
+                exponentiation,
+            } => vec![
+                is_last.clone(),
+                base.clone(),
+                exponent.clone(),
+                exponentiation.clone(),
+            ],
             Self::Conditional(condition, lookup) => lookup
                 .input_exprs()
                 .into_iter()
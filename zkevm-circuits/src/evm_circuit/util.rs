@@ -252,3 +252,31 @@ pub(crate) fn split_u256(value: &U256) -> (U256, U256) {
         U256([value.0[2], value.0[3], 0, 0]),
     )
 }
+
+/// Split a U256 value into 4 64-bit limbs stored in U256 values.
+pub(crate) fn split_u256_limb64(value: &U256) -> [U256; 4] {
+    [
+        U256([value.0[0], 0, 0, 0]),
+        U256([value.0[1], 0, 0, 0]),
+        U256([value.0[2], 0, 0, 0]),
+        U256([value.0[3], 0, 0, 0]),
+    ]
+}
+
+/// Returns the low and high 128-bit halves of a word as expressions.
+pub(crate) fn word_halves_expr<F: FieldExt>(
+    word: &Word<F>,
+) -> [Expression<F>; 2] {
+    [
+        from_bytes::expr(&word.cells[..16]),
+        from_bytes::expr(&word.cells[16..]),
+    ]
+}
+
+/// Returns the 4 64-bit limbs of a word as expressions.
+pub(crate) fn word_limbs_expr<F: FieldExt>(
+    word: &Word<F>,
+) -> [Expression<F>; 4] {
+    [0, 1, 2, 3]
+        .map(|idx| from_bytes::expr(&word.cells[idx * 8..(idx + 1) * 8]))
+}
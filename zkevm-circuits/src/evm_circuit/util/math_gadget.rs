@@ -1,9 +1,11 @@
 use crate::{
     evm_circuit::{
         param::MAX_BYTES_FIELD,
+        table::{FixedTableTag, Lookup},
         util::{
             self, constraint_builder::ConstraintBuilder, from_bytes,
-            pow_of_two, pow_of_two_expr, select, split_u256, sum, Cell,
+            pow_of_two, pow_of_two_expr, select, split_u256, split_u256_limb64,
+            sum, Cell,
         },
     },
     util::Expr,
@@ -200,6 +202,357 @@ impl<F: FieldExt, const N: usize> AddWordsGadget<F, N> {
     }
 }
 
+/// Construction of 256-bit words multiplication and addition `a * b + c == d`
+/// (modulo 2^256), which is useful for opcode MUL, DIV and MOD.  For MUL `c`
+/// is set to zero, while for DIV and MOD `a` is the quotient, `b` the divisor,
+/// `c` the remainder and `d` the dividend.
+///
+/// The multiplication is done on the 64-bit limbs `a0..a3` and `b0..b3`:
+/// - `t0 = a0 * b0` contributes to the bits `0..128`
+/// - `t1 = a0 * b1 + a1 * b0` contributes to the bits `64..193`
+/// - `t2 = a0 * b2 + a1 * b1 + a2 * b0` contributes to the bits `128..258`
+/// - `t3 = a0 * b3 + a1 * b2 + a2 * b1 + a3 * b0` contributes to the bits
+///   `192..322`
+///
+/// The contributions beyond the low 128 bits of `t0 + t1 ⋅ 2^64 + c_lo` are
+/// carried by `carry_lo`, and the ones beyond 256 bits of the high half by
+/// `carry_hi`, which both fit in 9 bytes.  The product overflows 256 bits
+/// when `carry_hi` or any of the remaining limb products is non-zero.
+#[derive(Clone, Debug)]
+pub(crate) struct MulAddWordsGadget<F> {
+    a: util::Word<F>,
+    b: util::Word<F>,
+    c: util::Word<F>,
+    d: util::Word<F>,
+    carry_lo: [Cell<F>; 9],
+    carry_hi: [Cell<F>; 9],
+    overflow: Expression<F>,
+}
+
+impl<F: FieldExt> MulAddWordsGadget<F> {
+    pub(crate) fn construct(
+        cb: &mut ConstraintBuilder<F>,
+        [a, b, c, d]: [util::Word<F>; 4],
+    ) -> Self {
+        let carry_lo = cb.query_bytes();
+        let carry_hi = cb.query_bytes();
+        let carry_lo_expr = from_bytes::expr(&carry_lo);
+        let carry_hi_expr = from_bytes::expr(&carry_hi);
+
+        let a_limbs = [0, 1, 2, 3]
+            .map(|idx| from_bytes::expr(&a.cells[idx * 8..(idx + 1) * 8]));
+        let b_limbs = [0, 1, 2, 3]
+            .map(|idx| from_bytes::expr(&b.cells[idx * 8..(idx + 1) * 8]));
+        let limb_product = |terms: &[(usize, usize)]| {
+            sum::expr(
+                terms
+                    .iter()
+                    .map(|(i, j)| a_limbs[*i].clone() * b_limbs[*j].clone()),
+            )
+        };
+        let t0 = limb_product(&[(0, 0)]);
+        let t1 = limb_product(&[(0, 1), (1, 0)]);
+        let t2 = limb_product(&[(0, 2), (1, 1), (2, 0)]);
+        let t3 = limb_product(&[(0, 3), (1, 2), (2, 1), (3, 0)]);
+        let overflow = carry_hi_expr.clone()
+            + limb_product(&[(1, 3), (2, 2), (3, 1), (2, 3), (3, 2), (3, 3)]);
+
+        let c_lo = from_bytes::expr(&c.cells[..16]);
+        let c_hi = from_bytes::expr(&c.cells[16..]);
+        let d_lo = from_bytes::expr(&d.cells[..16]);
+        let d_hi = from_bytes::expr(&d.cells[16..]);
+
+        cb.require_equal(
+            "t0 + t1 ⋅ 2^64 + c_lo == d_lo + carry_lo ⋅ 2^128",
+            t0 + t1 * pow_of_two_expr(64) + c_lo,
+            d_lo + carry_lo_expr.clone() * pow_of_two_expr(128),
+        );
+        cb.require_equal(
+            "t2 + t3 ⋅ 2^64 + c_hi + carry_lo == d_hi + carry_hi ⋅ 2^128",
+            t2 + t3 * pow_of_two_expr(64) + c_hi + carry_lo_expr,
+            d_hi + carry_hi_expr * pow_of_two_expr(128),
+        );
+
+        Self {
+            a,
+            b,
+            c,
+            d,
+            carry_lo,
+            carry_hi,
+            overflow,
+        }
+    }
+
+    pub(crate) fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        [a, b, c, d]: [Word; 4],
+    ) -> Result<(), Error> {
+        for (word, value) in [&self.a, &self.b, &self.c, &self.d]
+            .iter()
+            .zip([a, b, c, d])
+        {
+            word.assign(region, offset, Some(value.to_le_bytes()))?;
+        }
+
+        let a_limbs = split_u256_limb64(&a);
+        let b_limbs = split_u256_limb64(&b);
+        let (c_lo, c_hi) = split_u256(&c);
+        let (d_lo, d_hi) = split_u256(&d);
+
+        let t0 = a_limbs[0] * b_limbs[0];
+        let t1 = a_limbs[0] * b_limbs[1] + a_limbs[1] * b_limbs[0];
+        let t2 = a_limbs[0] * b_limbs[2]
+            + a_limbs[1] * b_limbs[1]
+            + a_limbs[2] * b_limbs[0];
+        let t3 = a_limbs[0] * b_limbs[3]
+            + a_limbs[1] * b_limbs[2]
+            + a_limbs[2] * b_limbs[1]
+            + a_limbs[3] * b_limbs[0];
+
+        let carry_lo = (t0 + (t1 << 64) + c_lo - d_lo) >> 128;
+        let carry_hi = (t2 + (t3 << 64) + c_hi + carry_lo - d_hi) >> 128;
+
+        for (cells, value) in
+            [(&self.carry_lo, carry_lo), (&self.carry_hi, carry_hi)]
+        {
+            for (cell, byte) in cells.iter().zip(value.to_le_bytes()) {
+                cell.assign(region, offset, Some(F::from(byte as u64)))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns a non-zero value when `a * b + c` overflows 256 bits.
+    pub(crate) fn overflow(&self) -> Expression<F> {
+        self.overflow.clone()
+    }
+}
+
+/// Construction of 512-bit multiplication and addition
+/// `a * b + c == k * n + r`, which is useful for opcodes ADDMOD and MULMOD
+/// whose intermediate results don't fit in 256 bits.  The operands are given
+/// as expressions so that the callers can use their own cells: `a`, `b` and
+/// `n` as 64-bit limbs, `k` as the 64-bit limbs of its low and high 256 bits
+/// since it can have up to 512 bits, and `c` and `r` as 128-bit halves.
+///
+/// With `t_m` being the sum of `a_i * b_j` minus the sum of `k_i * n_j` where
+/// `i + j == m`, both sides are compared in the 128-bit chunks
+/// `t_2j + t_2j+1 ⋅ 2^64 + c_j - r_j`, whose absolute values are below
+/// `2^196`.  Each chunk plus the previous carry is required to be the next
+/// carry times `2^128`, and the last one to be zero.  The carries are signed
+/// and stored in 9 bytes with an offset of `2^71`.  None of these equations
+/// can wrap around the field, so they hold over the integers, and so does
+/// their weighted sum `a * b + c - k * n - r == 0`.
+#[derive(Clone, Debug)]
+pub(crate) struct MulAddWords512Gadget<F> {
+    carries: [[Cell<F>; 9]; 5],
+}
+
+impl<F: FieldExt> MulAddWords512Gadget<F> {
+    pub(crate) fn construct(
+        cb: &mut ConstraintBuilder<F>,
+        [a, b, n]: [[Expression<F>; 4]; 3],
+        [k_lo, k_hi]: [[Expression<F>; 4]; 2],
+        [c, r]: [[Expression<F>; 2]; 2],
+    ) -> Self {
+        let k = [k_lo, k_hi].concat();
+        let carries = [(); 5].map(|_| cb.query_bytes());
+        let carry_exprs = carries
+            .clone()
+            .map(|carry| from_bytes::expr(&carry) - pow_of_two_expr(71));
+
+        let t = (0..12)
+            .map(|m| {
+                sum::expr(
+                    (0..4)
+                        .filter(|i| m >= *i && m - i < 4)
+                        .map(|i| a[i].clone() * b[m - i].clone()),
+                ) - sum::expr(
+                    (0..4)
+                        .filter(|j| m >= *j && m - j < 8)
+                        .map(|j| k[m - j].clone() * n[j].clone()),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        for idx in 0..6 {
+            let mut chunk = t[2 * idx].clone()
+                + t[2 * idx + 1].clone() * pow_of_two_expr(64);
+            if idx < 2 {
+                chunk = chunk + c[idx].clone() - r[idx].clone();
+            }
+            if idx > 0 {
+                chunk = chunk + carry_exprs[idx - 1].clone();
+            }
+            match carry_exprs.get(idx) {
+                Some(carry) => cb.require_equal(
+                    "chunk_j + carry_j-1 == carry_j ⋅ 2^128",
+                    chunk,
+                    carry.clone() * pow_of_two_expr(128),
+                ),
+                None => cb.require_zero("chunk_5 + carry_4 == 0", chunk),
+            }
+        }
+
+        Self { carries }
+    }
+
+    /// Assigns the carries, with `c` and `r` given as their 128-bit halves.
+    /// The words are expected to be assigned by the caller.
+    pub(crate) fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        [a, b, n]: [Word; 3],
+        [k_lo, k_hi]: [Word; 2],
+        [c, r]: [[Word; 2]; 2],
+    ) -> Result<(), Error> {
+        let [a, b, n] = [a, b, n].map(|word| split_u256_limb64(&word));
+        let k = [split_u256_limb64(&k_lo), split_u256_limb64(&k_hi)].concat();
+        let t = (0..12)
+            .map(|m| {
+                (
+                    (0..4)
+                        .filter(|i| m >= *i && m - i < 4)
+                        .fold(Word::zero(), |acc, i| acc + a[i] * b[m - i]),
+                    (0..4)
+                        .filter(|j| m >= *j && m - j < 8)
+                        .fold(Word::zero(), |acc, j| acc + k[m - j] * n[j]),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        // The carry is kept with its offset, and the chunk is shifted by
+        // 2^199 - 2^71 so that the subtraction doesn't underflow.
+        let mut carry = Word::one() << 71;
+        for (idx, cells) in self.carries.iter().enumerate() {
+            let (mut lhs, mut rhs) = (
+                t[2 * idx].0 + (t[2 * idx + 1].0 << 64),
+                t[2 * idx].1 + (t[2 * idx + 1].1 << 64),
+            );
+            if idx < 2 {
+                lhs = lhs + c[idx];
+                rhs = rhs + r[idx];
+            }
+            carry = (lhs + carry + (Word::one() << 199)
+                - (Word::one() << 71)
+                - rhs)
+                >> 128;
+            for (cell, byte) in cells.iter().zip(carry.to_le_bytes()) {
+                cell.assign(region, offset, Some(F::from(byte as u64)))?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Construction of the absolute value `x_abs` of a 256-bit word `x` in two's
+/// complement, which is useful for opcodes operating on signed values.  The
+/// sign of `x` is looked up from its most significant byte in the `SignByte`
+/// fixed table, the same way SIGNEXTEND does.  When `x` is negative we require
+/// `x + x_abs == 2^256`, so the absolute value of `-2^255` is `2^255`.
+#[derive(Clone, Debug)]
+pub(crate) struct AbsWordGadget<F> {
+    x: util::Word<F>,
+    x_abs: util::Word<F>,
+    sign_byte: Cell<F>,
+    carry_lo: Cell<F>,
+    is_neg: Expression<F>,
+}
+
+impl<F: FieldExt> AbsWordGadget<F> {
+    pub(crate) fn construct(cb: &mut ConstraintBuilder<F>) -> Self {
+        let x = cb.query_word();
+        let x_abs = cb.query_word();
+        let sign_byte = cb.query_cell();
+        let carry_lo = cb.query_bool();
+
+        // Lookup the sign byte, which is 0xff when x is negative and 0
+        // otherwise.
+        cb.add_lookup(Lookup::Fixed {
+            tag: FixedTableTag::SignByte.expr(),
+            values: [x.cells[31].expr(), sign_byte.expr(), 0.expr()],
+        });
+        let is_neg = sign_byte.expr()
+            * Expression::Constant(F::from(0xff).invert().unwrap());
+
+        let x_lo = from_bytes::expr(&x.cells[..16]);
+        let x_hi = from_bytes::expr(&x.cells[16..]);
+        let x_abs_lo = from_bytes::expr(&x_abs.cells[..16]);
+        let x_abs_hi = from_bytes::expr(&x_abs.cells[16..]);
+
+        cb.add_constraint(
+            "x_abs == x when x >= 0",
+            (1.expr() - is_neg.clone()) * (x_abs.expr() - x.expr()),
+        );
+        cb.add_constraint(
+            "x_lo + x_abs_lo == carry_lo ⋅ 2^128 when x < 0",
+            is_neg.clone()
+                * (x_lo + x_abs_lo - carry_lo.expr() * pow_of_two_expr(128)),
+        );
+        cb.add_constraint(
+            "x_hi + x_abs_hi + carry_lo == 2^128 when x < 0",
+            is_neg.clone()
+                * (x_hi + x_abs_hi + carry_lo.expr() - pow_of_two_expr(128)),
+        );
+
+        Self {
+            x,
+            x_abs,
+            sign_byte,
+            carry_lo,
+            is_neg,
+        }
+    }
+
+    /// Assigns `x` and returns its absolute value.
+    pub(crate) fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        x: Word,
+    ) -> Result<Word, Error> {
+        let is_neg = x.bit(255);
+        let x_abs = if is_neg {
+            Word::zero().overflowing_sub(x).0
+        } else {
+            x
+        };
+        let carry_lo = is_neg && !split_u256(&x).0.is_zero();
+
+        self.x.assign(region, offset, Some(x.to_le_bytes()))?;
+        self.x_abs
+            .assign(region, offset, Some(x_abs.to_le_bytes()))?;
+        self.sign_byte.assign(
+            region,
+            offset,
+            Some(F::from(if is_neg { 0xff } else { 0 })),
+        )?;
+        self.carry_lo
+            .assign(region, offset, Some(F::from(carry_lo as u64)))?;
+
+        Ok(x_abs)
+    }
+
+    pub(crate) fn x(&self) -> &util::Word<F> {
+        &self.x
+    }
+
+    pub(crate) fn x_abs(&self) -> &util::Word<F> {
+        &self.x_abs
+    }
+
+    /// Returns `1` when `x < 0`, and returns `0` otherwise.
+    pub(crate) fn is_neg(&self) -> Expression<F> {
+        self.is_neg.clone()
+    }
+}
+
 /// Requires that the passed in value is within the specified range.
 /// `NUM_BYTES` is required to be `<= 31`.
 #[derive(Clone, Debug)]
@@ -357,6 +710,160 @@ impl<F: FieldExt, const NUM_BYTES: usize> ComparisonGadget<F, NUM_BYTES> {
     }
 }
 
+/// Returns `1` when `lhs < rhs`, and returns `0` otherwise, with `lhs` and
+/// `rhs` being 256-bit words.
+#[derive(Clone, Debug)]
+pub(crate) struct LtWordGadget<F> {
+    comparison_hi: ComparisonGadget<F, 16>,
+    lt_lo: LtGadget<F, 16>,
+}
+
+impl<F: FieldExt> LtWordGadget<F> {
+    pub(crate) fn construct(
+        cb: &mut ConstraintBuilder<F>,
+        lhs: &util::Word<F>,
+        rhs: &util::Word<F>,
+    ) -> Self {
+        let comparison_hi = ComparisonGadget::construct(
+            cb,
+            from_bytes::expr(&lhs.cells[16..]),
+            from_bytes::expr(&rhs.cells[16..]),
+        );
+        let lt_lo = LtGadget::construct(
+            cb,
+            from_bytes::expr(&lhs.cells[..16]),
+            from_bytes::expr(&rhs.cells[..16]),
+        );
+
+        Self {
+            comparison_hi,
+            lt_lo,
+        }
+    }
+
+    pub(crate) fn expr(&self) -> Expression<F> {
+        // `lhs < rhs` when:
+        // - `lhs[16..32] < rhs[16..32]` OR
+        // - `lhs[16..32] == rhs[16..32]` AND `lhs[0..16] < rhs[0..16]`
+        let (lt_hi, eq_hi) = self.comparison_hi.expr();
+        select::expr(lt_hi, 1.expr(), eq_hi * self.lt_lo.expr())
+    }
+
+    pub(crate) fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        lhs: Word,
+        rhs: Word,
+    ) -> Result<F, Error> {
+        let (lhs_lo, lhs_hi) = split_u256(&lhs);
+        let (rhs_lo, rhs_hi) = split_u256(&rhs);
+        let (lt_hi, eq_hi) = self.comparison_hi.assign(
+            region,
+            offset,
+            F::from_u128(lhs_hi.as_u128()),
+            F::from_u128(rhs_hi.as_u128()),
+        )?;
+        let (lt_lo, _) = self.lt_lo.assign(
+            region,
+            offset,
+            F::from_u128(lhs_lo.as_u128()),
+            F::from_u128(rhs_lo.as_u128()),
+        )?;
+        Ok(select::value(lt_hi, F::one(), eq_hi * lt_lo))
+    }
+}
+
+/// Returns the number of bytes needed to represent a 256-bit word, which is
+/// the index of its most significant non-zero byte plus one, or zero when the
+/// word is zero.
+#[derive(Clone, Debug)]
+pub(crate) struct ByteSizeGadget<F> {
+    /// One-hot selectors of the byte size, ranging from 0 to 32
+    byte_size_selectors: [Cell<F>; 33],
+    /// Inverse of the most significant non-zero byte
+    most_significant_byte_inverse: Cell<F>,
+    byte_size: Expression<F>,
+}
+
+impl<F: FieldExt> ByteSizeGadget<F> {
+    pub(crate) fn construct(
+        cb: &mut ConstraintBuilder<F>,
+        word: &util::Word<F>,
+    ) -> Self {
+        let byte_size_selectors = [(); 33].map(|_| cb.query_bool());
+        let most_significant_byte_inverse = cb.query_cell();
+
+        cb.require_equal(
+            "Exactly one byte size selector is enabled",
+            sum::expr(&byte_size_selectors),
+            1.expr(),
+        );
+        for (byte_size, selector) in byte_size_selectors.iter().enumerate() {
+            // Bytes are in range of [0, 256) so their sum is zero only when
+            // all of them are zero.
+            cb.add_constraint(
+                "Bytes beyond the byte size are zero",
+                selector.expr() * sum::expr(&word.cells[byte_size..]),
+            );
+            if byte_size > 0 {
+                cb.add_constraint(
+                    "The most significant byte is non-zero",
+                    selector.expr()
+                        * (1.expr()
+                            - word.cells[byte_size - 1].expr()
+                                * most_significant_byte_inverse.expr()),
+                );
+            }
+        }
+
+        let byte_size =
+            sum::expr(byte_size_selectors.iter().enumerate().map(
+                |(byte_size, selector)| byte_size.expr() * selector.expr(),
+            ));
+
+        Self {
+            byte_size_selectors,
+            most_significant_byte_inverse,
+            byte_size,
+        }
+    }
+
+    pub(crate) fn expr(&self) -> Expression<F> {
+        self.byte_size.clone()
+    }
+
+    pub(crate) fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        word: Word,
+    ) -> Result<u64, Error> {
+        let byte_size = (word.bits() + 7) / 8;
+        for (idx, selector) in self.byte_size_selectors.iter().enumerate() {
+            selector.assign(
+                region,
+                offset,
+                Some(F::from((idx == byte_size) as u64)),
+            )?;
+        }
+        let most_significant_byte_inverse = if byte_size > 0 {
+            F::from(word.to_le_bytes()[byte_size - 1] as u64)
+                .invert()
+                .unwrap()
+        } else {
+            F::zero()
+        };
+        self.most_significant_byte_inverse.assign(
+            region,
+            offset,
+            Some(most_significant_byte_inverse),
+        )?;
+
+        Ok(byte_size as u64)
+    }
+}
+
 /// Returns (is_a, is_b):
 /// - `is_a` is `1` when `value == a`, else `0`
 /// - `is_b` is `1` when `value == b`, else `0`